@@ -1,6 +1,8 @@
 use std::collections::HashSet;
+use std::ops::Bound;
 use proc_macro2::TokenStream;
-use syn::{ItemEnum, Error, Generics, Ident, Result, LitInt, Path};
+use syn::{ItemEnum, Error, Expr, ExprLit, ExprRange, Generics, Ident, Lit, Result, LitInt, Path, RangeLimits, Token};
+use syn::parse::{Parse, ParseStream};
 
 use ebml_iterable_specification::TagDataType;
 use quote::ToTokens;
@@ -18,6 +20,131 @@ pub struct Variant<'a> {
     pub id_attr: (u64, Attribute<'a>),
     pub data_type_attr: (TagDataType, Path, Attribute<'a>),
     pub parent_attr: Option<(Ident, Attribute<'a>)>,
+    pub default_attr: Option<(Expr, Attribute<'a>)>,
+    pub range_attr: Option<(RangeArgs, Attribute<'a>)>,
+    pub length_attr: Option<(RangeArgs, Attribute<'a>)>,
+    pub occurs_attr: Option<(OccursArgs, Attribute<'a>)>,
+    pub global_attr: Option<(GlobalArgs, Attribute<'a>)>,
+}
+
+///
+/// The arguments given to an `#[occurs(min, max)]` attribute.  A `max` of `_` means the tag can occur any number of times.
+///
+pub struct OccursArgs {
+    pub min: u64,
+    pub max: Option<u64>,
+}
+
+impl Parse for OccursArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let min = input.parse::<LitInt>()?.base10_parse::<u64>()?;
+        input.parse::<Token![,]>()?;
+        let max = if input.peek(Token![_]) {
+            input.parse::<Token![_]>()?;
+            None
+        } else {
+            let lit = input.parse::<LitInt>()?;
+            let max = lit.base10_parse::<u64>()?;
+            if max < min {
+                return Err(Error::new_spanned(lit, "maximum occurrences cannot be less than minimum occurrences"));
+            }
+            Some(max)
+        };
+        Ok(OccursArgs { min, max })
+    }
+}
+
+///
+/// The levels given to a `#[global]` attribute.  These are written as a rust range of levels (e.g. `1..`, `0..=2`), and a plain `#[global]` allows any level.
+///
+pub struct GlobalArgs {
+    pub min: u64,
+    pub max: Option<u64>,
+}
+
+impl Parse for GlobalArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.is_empty() {
+            return Ok(GlobalArgs { min: 0, max: None });
+        }
+
+        let level = |expr: &Expr| match expr {
+            Expr::Lit(ExprLit { lit: Lit::Int(lit), .. }) => lit.base10_parse::<u64>(),
+            expr => Err(Error::new_spanned(expr, "levels must be integer literals")),
+        };
+        let range = input.parse::<ExprRange>()?;
+        let empty = || Error::new_spanned(&range, "the range of levels is empty");
+        let min = range.from.as_deref().map_or(Ok(0), level)?;
+        let max = match (range.to.as_deref(), &range.limits) {
+            (None, _) => None,
+            (Some(to), RangeLimits::Closed(_)) => Some(level(to)?),
+            (Some(to), RangeLimits::HalfOpen(_)) => Some(level(to)?.checked_sub(1).ok_or_else(empty)?),
+        };
+        if max.is_some_and(|max| max < min) {
+            return Err(empty());
+        }
+        Ok(GlobalArgs { min, max })
+    }
+}
+
+///
+/// The bounds given to a `#[range()]` or `#[length()]` attribute.  These are written either as a rust range (e.g. `1..`, `0..=5`), a comparison (e.g. `> 0.0`), a lower and an upper comparison separated by a comma (e.g. `> 0.0, <= 1.0`), or a single exact value.
+///
+pub struct RangeArgs {
+    pub min: Bound<Expr>,
+    pub max: Bound<Expr>,
+}
+
+impl RangeArgs {
+    fn parse_comparison(input: ParseStream) -> Result<Option<(Bound<Expr>, Bound<Expr>)>> {
+        let bounds = if input.peek(Token![>=]) {
+            input.parse::<Token![>=]>()?;
+            (Bound::Included(input.parse()?), Bound::Unbounded)
+        } else if input.peek(Token![>]) {
+            input.parse::<Token![>]>()?;
+            (Bound::Excluded(input.parse()?), Bound::Unbounded)
+        } else if input.peek(Token![<=]) {
+            input.parse::<Token![<=]>()?;
+            (Bound::Unbounded, Bound::Included(input.parse()?))
+        } else if input.peek(Token![<]) {
+            input.parse::<Token![<]>()?;
+            (Bound::Unbounded, Bound::Excluded(input.parse()?))
+        } else {
+            return Ok(None);
+        };
+        Ok(Some(bounds))
+    }
+}
+
+impl Parse for RangeArgs {
+    fn parse(input: ParseStream) -> Result<Self> {
+        let (min, max) = if let Some((min, max)) = Self::parse_comparison(input)? {
+            if input.peek(Token![,]) {
+                let comma = input.parse::<Token![,]>()?;
+                match (min, max, Self::parse_comparison(input)?) {
+                    (min, Bound::Unbounded, Some((Bound::Unbounded, max))) => (min, max),
+                    (Bound::Unbounded, max, Some((min, Bound::Unbounded))) => (min, max),
+                    _ => return Err(Error::new(comma.span, "a range can only combine a lower bound (`>` or `>=`) with an upper bound (`<` or `<=`)")),
+                }
+            } else {
+                (min, max)
+            }
+        } else {
+            match input.parse::<Expr>()? {
+                Expr::Range(ExprRange { from, limits, to, .. }) => {
+                    let min = from.map_or(Bound::Unbounded, |from| Bound::Included(*from));
+                    let max = match (to, limits) {
+                        (None, _) => Bound::Unbounded,
+                        (Some(to), RangeLimits::HalfOpen(_)) => Bound::Excluded(*to),
+                        (Some(to), RangeLimits::Closed(_)) => Bound::Included(*to),
+                    };
+                    (min, max)
+                },
+                value => (Bound::Included(value.clone()), Bound::Included(value)),
+            }
+        };
+        Ok(RangeArgs { min, max })
+    }
 }
 
 pub struct Attribute<'a> {
@@ -48,6 +175,11 @@ impl<'a> Variant<'a> {
         let mut id_attr: Option<(u64, Attribute<'a>)> = None;
         let mut data_type_attr: Option<(TagDataType, Path, Attribute<'a>)> = None;
         let mut parent_attr: Option<(Ident, Attribute<'a>)> = None;
+        let mut default_attr: Option<(Expr, Attribute<'a>)> = None;
+        let mut range_attr: Option<(RangeArgs, Attribute<'a>)> = None;
+        let mut length_attr: Option<(RangeArgs, Attribute<'a>)> = None;
+        let mut occurs_attr: Option<(OccursArgs, Attribute<'a>)> = None;
+        let mut global_attr: Option<(GlobalArgs, Attribute<'a>)> = None;
 
         for attr in &node.attrs {
             if attr.path.is_ident("id") {
@@ -80,6 +212,10 @@ impl<'a> Variant<'a> {
                     TagDataType::Binary
                 } else if data_type_name == "Float" {
                     TagDataType::Float
+                } else if data_type_name == "Date" {
+                    TagDataType::Date
+                } else if data_type_name == "String" {
+                    TagDataType::String
                 } else if data_type_name == "Master" {
                     TagDataType::Master
                 } else {
@@ -103,6 +239,47 @@ impl<'a> Variant<'a> {
                     original: &attr,
                     tokens: &attr.tokens,
                 }))
+            } else if attr.path.is_ident("default") {
+                if default_attr.is_some() {
+                    return Err(Error::new_spanned(node, format!("duplicate {} attribute", attr.to_token_stream())));
+                }
+                let val = attr.parse_args::<Expr>().map_err(|err| Error::new(err.span(), format!("{} requires a value expression", attr.to_token_stream())))?;
+                default_attr = Some((val, Attribute {
+                    original: attr,
+                    tokens: &attr.tokens,
+                }));
+            } else if attr.path.is_ident("range") || attr.path.is_ident("length") {
+                let target = if attr.path.is_ident("range") { &mut range_attr } else { &mut length_attr };
+                if target.is_some() {
+                    return Err(Error::new_spanned(node, format!("duplicate {} attribute", attr.to_token_stream())));
+                }
+                let val = attr.parse_args::<RangeArgs>().map_err(|err| Error::new(err.span(), format!("{} requires a range (e.g. `1..`, `0..=5` or `> 0.0`)", attr.to_token_stream())))?;
+                *target = Some((val, Attribute {
+                    original: attr,
+                    tokens: &attr.tokens,
+                }));
+            } else if attr.path.is_ident("occurs") {
+                if occurs_attr.is_some() {
+                    return Err(Error::new_spanned(node, format!("duplicate {} attribute", attr.to_token_stream())));
+                }
+                let val = attr.parse_args::<OccursArgs>()?;
+                occurs_attr = Some((val, Attribute {
+                    original: attr,
+                    tokens: &attr.tokens,
+                }));
+            } else if attr.path.is_ident("global") {
+                if global_attr.is_some() {
+                    return Err(Error::new_spanned(node, format!("duplicate {} attribute", attr.to_token_stream())));
+                }
+                let val = if attr.tokens.is_empty() {
+                    GlobalArgs { min: 0, max: None }
+                } else {
+                    attr.parse_args::<GlobalArgs>().map_err(|err| Error::new(err.span(), format!("{} requires a range of levels (e.g. `1..` or `0..=2`)", attr.to_token_stream())))?
+                };
+                global_attr = Some((val, Attribute {
+                    original: attr,
+                    tokens: &attr.tokens,
+                }));
             }
         }
 
@@ -114,12 +291,39 @@ impl<'a> Variant<'a> {
             return Err(Error::new_spanned(node, "#[data_type] attribute is required when using #[ebml_specification] attribute"));
         };
 
+        if let Some((_, attr)) = &default_attr {
+            if data_type_attr.0 == TagDataType::Master {
+                return Err(Error::new_spanned(attr.original, "#[default] attribute is not supported on Master variants"));
+            }
+        }
+
+        if let Some((_, attr)) = &range_attr {
+            if !matches!(data_type_attr.0, TagDataType::UnsignedInt | TagDataType::Integer | TagDataType::Float) {
+                return Err(Error::new_spanned(attr.original, "#[range] attribute is only supported on UnsignedInt, Integer and Float variants"));
+            }
+        }
+
+        if let Some((_, attr)) = &length_attr {
+            if !matches!(data_type_attr.0, TagDataType::Utf8 | TagDataType::String | TagDataType::Binary) {
+                return Err(Error::new_spanned(attr.original, "#[length] attribute is only supported on Utf8, String and Binary variants"));
+            }
+        }
+
+        if let (Some((_, attr)), Some(_)) = (&global_attr, &parent_attr) {
+            return Err(Error::new_spanned(attr.original, "#[global] variants cannot have a #[parent], since they may occur within any parent"));
+        }
+
         Ok(Variant {
             original: node,
             ident: node.ident.clone(),
             id_attr,
             data_type_attr,
-            parent_attr
+            parent_attr,
+            default_attr,
+            range_attr,
+            length_attr,
+            occurs_attr,
+            global_attr,
         })
     }
 }
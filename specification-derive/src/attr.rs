@@ -1,10 +1,11 @@
 use proc_macro2::TokenStream;
 use std::str::FromStr;
 use std::collections::{BTreeSet, HashMap};
+use std::ops::Bound;
 use syn::spanned::Spanned;
-use syn::{Attribute, ItemEnum, Result, Error, Visibility, Fields, FieldsUnnamed, Path, Ident, Variant};
+use syn::{Attribute, Expr, ItemEnum, Result, Error, Visibility, Fields, FieldsUnnamed, Path, Ident, Variant};
 use quote::{quote, quote_spanned, ToTokens};
-use ebml_iterable_specification::TagDataType;
+use ebml_iterable_specification::{GlobalLevels, TagDataType};
 use ebml_iterable_specification::TagDataType::Master;
 
 use super::ast::Enum;
@@ -66,6 +67,7 @@ pub fn impl_ebml_specification(original: &mut ItemEnum) -> Result<TokenStream> {
 
 fn modify_orig(original: &mut ItemEnum) -> Result<TokenStream> {
     let spanned_master_enum = spanned_master_enum(original).clone();
+    let spanned_date = spanned_date(original);
     for var in original.variants.iter_mut() {
         let data_type_attribute: &Attribute = var
             .attrs
@@ -89,12 +91,16 @@ fn modify_orig(original: &mut ItemEnum) -> Result<TokenStream> {
             quote!( (::std::vec::Vec<u8>) )
         } else if data_type == "Float" {
             quote!( (f64) )
+        } else if data_type == "Date" {
+            quote!( (#spanned_date) )
+        } else if data_type == "String" {
+            quote!( (String) )
         } else {
             return Err(Error::new_spanned(data_type_attribute.clone(), format!("unknown data_type \"{}\"", data_type)));
         };
 
         var.attrs.retain(|a| {
-            if a.path.is_ident("id") || a.path.is_ident("data_type") || a.path.is_ident("parent") {
+            if a.path.is_ident("id") || a.path.is_ident("data_type") || a.path.is_ident("parent") || a.path.is_ident("default") || a.path.is_ident("range") || a.path.is_ident("length") || a.path.is_ident("occurs") || a.path.is_ident("global") {
                 false
             } else {
                 true
@@ -131,6 +137,24 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
         }
     });
 
+    let get_tag_name = input.variants.iter().map(|var: &crate::ast::Variant| {
+        let name = var.ident.to_string();
+        let id = &var.id_attr.0;
+
+        quote! {
+            #id => Some(#name),
+        }
+    });
+
+    let get_tag_id_by_name = input.variants.iter().map(|var: &crate::ast::Variant| {
+        let name = var.ident.to_string();
+        let id = &var.id_attr.0;
+
+        quote! {
+            #name => Some(#id),
+        }
+    });
+
     let get_tag = |ret_val: String| {
         move |var: &crate::ast::Variant| {
             let name = &var.ident;
@@ -152,6 +176,7 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
     }
 
     let roots: Vec<u64> = input.variants.iter().filter_map(|it| if it.parent_attr.is_none() { Some(it.id_attr.0) } else { None }).collect();
+    let globals: Vec<u64> = input.variants.iter().filter_map(|it| global_levels(it).map(|_| it.id_attr.0)).collect();
 
     let is_child = input.variants.iter().map(|var: &crate::ast::Variant| {
         let name = &var.ident;
@@ -171,8 +196,9 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
         ids.extend(&roots);
         ids.extend(parents);
         ids.extend(siblings);
-        ids.remove(&0xBF); // Remove global CRC 32 element
-        ids.remove(&0xEC); // Remove global Void 32 element
+        for global in &globals {
+            ids.remove(global);
+        }
         let len = ids.len();
 
         quote! {
@@ -207,6 +233,14 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Master))
         .map(get_tag(String::from("data")));
 
+    let get_date_tag = input.variants.iter()
+        .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Date))
+        .map(get_tag(String::from("data")));
+
+    let get_string_tag = input.variants.iter()
+        .filter(|v| matches!(&v.data_type_attr.0, TagDataType::String))
+        .map(get_tag(String::from("data")));
+
     let as_data = |var: &crate::ast::Variant| {
         let name = &var.ident;
 
@@ -239,6 +273,125 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
         .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Master))
         .map(as_data);
 
+    let as_date = input.variants.iter()
+        .filter(|v| matches!(&v.data_type_attr.0, TagDataType::Date))
+        .map(as_data);
+
+    let as_string = input.variants.iter()
+        .filter(|v| matches!(&v.data_type_attr.0, TagDataType::String))
+        .map(as_data);
+
+    let spanned_date = spanned_date(input.original);
+
+    let get_default = input.variants.iter().filter_map(|var: &crate::ast::Variant| {
+        let (value, _) = var.default_attr.as_ref()?;
+        let name = &var.ident;
+        let id = &var.id_attr.0;
+        let value = match &var.data_type_attr.0 {
+            TagDataType::Utf8 | TagDataType::String => quote!( ::std::string::String::from(#value) ),
+            TagDataType::Binary => quote!( ::std::vec::Vec::from(&#value[..]) ),
+            TagDataType::Date => quote!( #spanned_date::from_nanos(#value) ),
+            _ => quote!( #value ),
+        };
+
+        Some(quote! {
+            #id => Some(#ty::#name(#value)),
+        })
+    });
+
+    let mut children_with_defaults: HashMap<&Ident, Vec<u64>> = HashMap::new();
+    for var in input.variants.iter().filter(|var| var.default_attr.is_some()) {
+        if let Some((parent, _)) = var.parent_attr.as_ref() {
+            children_with_defaults.entry(parent).or_default().push(var.id_attr.0);
+        }
+    }
+    let get_children_with_defaults = input.variants.iter().filter_map(|var: &crate::ast::Variant| {
+        let children = children_with_defaults.get(&var.ident)?;
+        let id = &var.id_attr.0;
+
+        Some(quote! {
+            #id => &[#(#children),*],
+        })
+    });
+
+    let spanned_value_range = spanned_value_range(input.original);
+    let get_range = |data_types: &'static [TagDataType], length: bool| {
+        let spanned_value_range = &spanned_value_range;
+        input.variants.iter()
+            .filter(move |v| data_types.contains(&v.data_type_attr.0))
+            .filter_map(move |var: &crate::ast::Variant| {
+                let (range, _) = if length { var.length_attr.as_ref()? } else { var.range_attr.as_ref()? };
+                let id = &var.id_attr.0;
+                let min = bound_tokens(&range.min);
+                let max = bound_tokens(&range.max);
+
+                Some(quote! {
+                    #id => Some(#spanned_value_range::new(#min, #max)),
+                })
+            })
+    };
+    let get_unsigned_int_range = get_range(&[TagDataType::UnsignedInt], false);
+    let get_signed_int_range = get_range(&[TagDataType::Integer], false);
+    let get_float_range = get_range(&[TagDataType::Float], false);
+    let get_length_range = get_range(&[TagDataType::Utf8, TagDataType::String, TagDataType::Binary], true);
+
+    let spanned_occurs = spanned_occurs(input.original);
+    let get_occurs = input.variants.iter().filter_map(|var: &crate::ast::Variant| {
+        let (occurs, _) = var.occurs_attr.as_ref()?;
+        let id = &var.id_attr.0;
+        let min = occurs.min;
+        let max = match occurs.max {
+            Some(max) => quote!( Some(#max) ),
+            None => quote!( None ),
+        };
+
+        Some(quote! {
+            #id => Some(#spanned_occurs::new(#min, #max)),
+        })
+    });
+
+    let mut mandatory_children: HashMap<&Ident, Vec<u64>> = HashMap::new();
+    for var in input.variants.iter().filter(|var| var.occurs_attr.as_ref().is_some_and(|(occurs, _)| occurs.min > 0)) {
+        if let Some((parent, _)) = var.parent_attr.as_ref() {
+            mandatory_children.entry(parent).or_default().push(var.id_attr.0);
+        }
+    }
+    let get_mandatory_children = input.variants.iter().filter_map(|var: &crate::ast::Variant| {
+        let children = mandatory_children.get(&var.ident)?;
+        let id = &var.id_attr.0;
+
+        Some(quote! {
+            #id => &[#(#children),*],
+        })
+    });
+
+    let tag_ids = input.variants.iter().map(|var| var.id_attr.0);
+
+    let get_parent_id = input.variants.iter().filter_map(|var: &crate::ast::Variant| {
+        let (parent, _) = var.parent_attr.as_ref()?;
+        let id = &var.id_attr.0;
+        let parent_id = &map.get(parent).unwrap().id_attr.0;
+
+        Some(quote! {
+            #id => Some(#parent_id),
+        })
+    });
+
+    let spanned_global_levels = spanned_global_levels(input.original);
+    let get_global_levels = input.variants.iter().filter_map(|var: &crate::ast::Variant| {
+        let levels = global_levels(var)?;
+        let id = &var.id_attr.0;
+        let min = levels.min;
+        let max = match levels.max {
+            Some(max) => quote!( Some(#max) ),
+            None => quote!( None ),
+        };
+
+        Some(quote! {
+            #id => Some(#spanned_global_levels::new(#min, #max)),
+        })
+    });
+
     let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
     let ebml_spec_trait = spanned_ebml_specification_trait(input.original);
     let ebml_tag_trait = spanned_ebml_tag_trait(input.original);
@@ -297,9 +450,111 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
                 }
             }
 
+            fn get_date_tag(id: u64, data: #spanned_date) -> Option<#ty> {
+                match id {
+                    #(#get_date_tag)*
+                    _ => None
+                }
+            }
+
+            fn get_string_tag(id: u64, data: String) -> Option<#ty> {
+                match id {
+                    #(#get_string_tag)*
+                    _ => None
+                }
+            }
+
             fn get_raw_tag(id: u64, data: &[u8]) -> #ty {
                 #ty::RawTag(id, data.to_vec())
             }
+
+            fn get_tag_name(id: u64) -> Option<&'static str> {
+                match id {
+                    #(#get_tag_name)*
+                    _ => None
+                }
+            }
+
+            fn get_tag_id_by_name(name: &str) -> Option<u64> {
+                match name {
+                    #(#get_tag_id_by_name)*
+                    _ => None
+                }
+            }
+
+            fn get_default(id: u64) -> Option<#ty> {
+                match id {
+                    #(#get_default)*
+                    _ => None
+                }
+            }
+
+            fn get_children_with_defaults(id: u64) -> &'static [u64] {
+                match id {
+                    #(#get_children_with_defaults)*
+                    _ => &[]
+                }
+            }
+
+            fn get_unsigned_int_range(id: u64) -> Option<#spanned_value_range<u64>> {
+                match id {
+                    #(#get_unsigned_int_range)*
+                    _ => None
+                }
+            }
+
+            fn get_signed_int_range(id: u64) -> Option<#spanned_value_range<i64>> {
+                match id {
+                    #(#get_signed_int_range)*
+                    _ => None
+                }
+            }
+
+            fn get_float_range(id: u64) -> Option<#spanned_value_range<f64>> {
+                match id {
+                    #(#get_float_range)*
+                    _ => None
+                }
+            }
+
+            fn get_length_range(id: u64) -> Option<#spanned_value_range<u64>> {
+                match id {
+                    #(#get_length_range)*
+                    _ => None
+                }
+            }
+
+            fn get_occurs(id: u64) -> Option<#spanned_occurs> {
+                match id {
+                    #(#get_occurs)*
+                    _ => None
+                }
+            }
+
+            fn get_mandatory_children(id: u64) -> &'static [u64] {
+                match id {
+                    #(#get_mandatory_children)*
+                    _ => &[]
+                }
+            }
+
+            fn get_tag_ids() -> &'static [u64] {
+                &[#(#tag_ids),*]
+            }
+
+            fn get_parent_id(id: u64) -> Option<u64> {
+                match id {
+                    #(#get_parent_id)*
+                    _ => None
+                }
+            }
+
+            fn get_global_levels(id: u64) -> Option<#spanned_global_levels> {
+                match id {
+                    #(#get_global_levels)*
+                    _ => None
+                }
+            }
         }
 
         impl #impl_generics #ebml_tag_trait <#ty> for #ty #ty_generics #where_clause {
@@ -354,6 +609,20 @@ fn get_impl(input: Enum) -> Result<TokenStream> {
                 }
             }
 
+            fn as_date(&self) -> Option<&#spanned_date> {
+                match self {
+                    #(#as_date)*
+                    _ => None,
+                }
+            }
+
+            fn as_string(&self) -> Option<&str> {
+                match self {
+                    #(#as_string)*
+                    _ => None,
+                }
+            }
+
             fn is_child(&self, id: u64) -> bool {
                 match self {
                     #(#is_child)*
@@ -383,6 +652,51 @@ fn spanned_master_enum(input: &ItemEnum) -> TokenStream {
     quote!(#path #r#enum)
 }
 
+fn spanned_date(input: &ItemEnum) -> TokenStream {
+    let path = spanned_ebml_iterable_specs(input);
+    let last_span = input.ident.span();
+    let date = quote_spanned!(last_span=> Date);
+    quote!(#path #date)
+}
+
+fn spanned_value_range(input: &ItemEnum) -> TokenStream {
+    let path = spanned_ebml_iterable_specs(input);
+    let last_span = input.ident.span();
+    let range = quote_spanned!(last_span=> ValueRange);
+    quote!(#path #range)
+}
+
+fn spanned_occurs(input: &ItemEnum) -> TokenStream {
+    let path = spanned_ebml_iterable_specs(input);
+    let last_span = input.ident.span();
+    let occurs = quote_spanned!(last_span=> Occurs);
+    quote!(#path #occurs)
+}
+
+fn spanned_global_levels(input: &ItemEnum) -> TokenStream {
+    let path = spanned_ebml_iterable_specs(input);
+    let last_span = input.ident.span();
+    let levels = quote_spanned!(last_span=> GlobalLevels);
+    quote!(#path #levels)
+}
+
+// Void and CRC-32 are global in every EBML document, so they don't need a #[global] attribute unless they are declared with a parent
+fn global_levels(var: &crate::ast::Variant) -> Option<GlobalLevels> {
+    match &var.global_attr {
+        Some((args, _)) => Some(GlobalLevels::new(args.min, args.max)),
+        None if var.parent_attr.is_none() => GlobalLevels::standard(var.id_attr.0),
+        None => None,
+    }
+}
+
+fn bound_tokens(bound: &Bound<Expr>) -> TokenStream {
+    match bound {
+        Bound::Included(value) => quote!( ::std::ops::Bound::Included(#value) ),
+        Bound::Excluded(value) => quote!( ::std::ops::Bound::Excluded(#value) ),
+        Bound::Unbounded => quote!( ::std::ops::Bound::Unbounded ),
+    }
+}
+
 fn spanned_ebml_specification_trait(input: &ItemEnum) -> TokenStream {
     let path = spanned_ebml_iterable_specs(input);
     let last_span = input.ident.span();
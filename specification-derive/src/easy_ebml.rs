@@ -51,6 +51,7 @@ impl EasyEBML {
 }
 
 pub struct EasyEBMLVariant {
+    attrs: Vec<Attribute>,
     path: Punctuated<Ident, Token![/]>,
     ty: Ident,
     id: LitInt
@@ -58,9 +59,9 @@ pub struct EasyEBMLVariant {
 
 impl EasyEBMLVariant {
     pub fn into_variant(self) -> Result<Variant> {
-        let EasyEBMLVariant { mut path, ty, id } = self;
+        let EasyEBMLVariant { attrs: variant_attrs, mut path, ty, id } = self;
         let ident = path.pop().ok_or_else(|| Error::new(path.span(), "easy_ebml enum variant must be at least: `Name: Type = id`"))?.into_value();
-        let mut attrs = vec![];
+        let mut attrs = variant_attrs;
         attrs.push(Attribute {
             pound_token: Default::default(),
             style: AttrStyle::Outer,
@@ -98,12 +99,14 @@ impl EasyEBMLVariant {
 
 impl Parse for EasyEBMLVariant {
     fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
         let path = Punctuated::parse_separated_nonempty(input)?;
         input.parse::<Token![:]>()?;
         let ty: Ident = input.parse()?;
         input.parse::<Token![=]>()?;
         let id: LitInt = input.parse()?;
         Ok(Self {
+            attrs,
             path,
             ty,
             id
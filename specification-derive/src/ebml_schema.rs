@@ -0,0 +1,241 @@
+use std::ops::Bound;
+use std::path::PathBuf;
+
+use proc_macro2::{Literal, TokenStream};
+use syn::{Attribute, Ident, LitStr, parse::Parse, Token, Visibility};
+use syn::parse::ParseStream;
+use syn::Result;
+use syn::Error;
+use quote::{quote, ToTokens};
+
+use ebml_iterable_specification::{GlobalLevels, TagDataType, ValueRange};
+use ebml_iterable_specification::schema::{DynamicValue, EbmlSchema, SchemaElement};
+
+///
+/// Input to `ebml_schema!`, either just the schema file or an enum declaration followed by `= "file"`.
+///
+pub struct EbmlSchemaInput {
+    attrs: Vec<Attribute>,
+    visibility: Option<Visibility>,
+    ident: Option<Ident>,
+    file: LitStr,
+}
+
+impl Parse for EbmlSchemaInput {
+    fn parse(input: ParseStream) -> Result<Self> {
+        if input.peek(LitStr) {
+            let file = input.parse()?;
+            return Ok(Self { attrs: Vec::new(), visibility: None, ident: None, file });
+        }
+
+        let attrs = input.call(Attribute::parse_outer)?;
+        let visibility: Visibility = input.parse()?;
+        input.parse::<Token![enum]>()?;
+        let ident = input.parse::<Ident>()?;
+        input.parse::<Token![=]>()?;
+        let file = input.parse()?;
+        if input.peek(Token![;]) {
+            input.parse::<Token![;]>()?;
+        }
+        Ok(Self {
+            attrs,
+            visibility: Some(visibility),
+            ident: Some(ident),
+            file,
+        })
+    }
+}
+
+///
+/// Converts a schema name into an upper camel case identifier, e.g. `EBMLMaxIDLength` becomes `EbmlMaxIdLength` and `CRC-32` becomes `Crc32`.
+///
+fn to_variant_name(name: &str) -> String {
+    let mut result = String::with_capacity(name.len());
+    for word in name.split(|c: char| !c.is_ascii_alphanumeric()).filter(|word| !word.is_empty()) {
+        let chars: Vec<char> = word.chars().collect();
+        for (i, c) in chars.iter().enumerate() {
+            let upper = if i == 0 {
+                true
+            } else if c.is_ascii_uppercase() {
+                // Only the last capital of an acronym stays uppercase, and only when it starts a new word
+                !chars[i - 1].is_ascii_uppercase() || chars.get(i + 1).is_some_and(|next| next.is_ascii_lowercase())
+            } else {
+                false
+            };
+            result.push(if upper { c.to_ascii_uppercase() } else { c.to_ascii_lowercase() });
+        }
+    }
+    result
+}
+
+fn variant_ident(element: &SchemaElement, span: proc_macro2::Span) -> Result<Ident> {
+    let name = to_variant_name(&element.name);
+    if name.is_empty() || !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return Err(Error::new(span, format!("cannot derive a variant name from schema element '{}'", element.name)));
+    }
+    Ok(Ident::new(&name, span))
+}
+
+fn data_type_ident(data_type: TagDataType) -> Ident {
+    let name = match data_type {
+        TagDataType::Master => "Master",
+        TagDataType::UnsignedInt => "UnsignedInt",
+        TagDataType::Integer => "Integer",
+        TagDataType::Utf8 => "Utf8",
+        TagDataType::Binary => "Binary",
+        TagDataType::Float => "Float",
+        TagDataType::Date => "Date",
+        TagDataType::String => "String",
+    };
+    Ident::new(name, proc_macro2::Span::call_site())
+}
+
+fn unsigned_tokens(value: u64) -> TokenStream {
+    Literal::u64_unsuffixed(value).into_token_stream()
+}
+
+fn signed_tokens(value: i64) -> TokenStream {
+    let magnitude = Literal::u64_unsuffixed(value.unsigned_abs());
+    if value < 0 { quote!(-#magnitude) } else { quote!(#magnitude) }
+}
+
+fn float_tokens(value: f64, span: proc_macro2::Span) -> Result<TokenStream> {
+    if !value.is_finite() {
+        return Err(Error::new(span, format!("cannot use non-finite float value {} in a specification", value)));
+    }
+    let magnitude = Literal::f64_unsuffixed(value.abs());
+    Ok(if value.is_sign_negative() { quote!(-#magnitude) } else { quote!(#magnitude) })
+}
+
+fn range_tokens<T, F>(range: &ValueRange<T>, to_tokens: F) -> Result<TokenStream>
+    where F: Fn(&T) -> Result<TokenStream>
+{
+    let min = match &range.min {
+        Bound::Included(min) => Some({ let min = to_tokens(min)?; quote!(>= #min) }),
+        Bound::Excluded(min) => Some({ let min = to_tokens(min)?; quote!(> #min) }),
+        Bound::Unbounded => None,
+    };
+    let max = match &range.max {
+        Bound::Included(max) => Some({ let max = to_tokens(max)?; quote!(<= #max) }),
+        Bound::Excluded(max) => Some({ let max = to_tokens(max)?; quote!(< #max) }),
+        Bound::Unbounded => None,
+    };
+    let parts = min.into_iter().chain(max);
+    Ok(quote!(#(#parts),*))
+}
+
+fn element_attributes(element: &SchemaElement, parent: Option<Ident>, span: proc_macro2::Span) -> Result<TokenStream> {
+    let schema_error = |err| Error::new(span, err);
+    let id = Literal::u64_unsuffixed(element.id);
+    let data_type = data_type_ident(element.data_type);
+    let mut attrs = quote! {
+        #[id(#id)]
+        #[data_type(TagDataType::#data_type)]
+    };
+
+    if let Some(documentation) = &element.documentation {
+        attrs.extend(quote!(#[doc = #documentation]));
+    }
+    if let Some(parent) = parent {
+        attrs.extend(quote!(#[parent(#parent)]));
+    }
+    if let Some(levels) = element.global {
+        let min = Literal::u64_unsuffixed(levels.min);
+        attrs.extend(match levels.max {
+            _ if levels == GlobalLevels::ANY => quote!(#[global]),
+            Some(max) => {
+                let max = Literal::u64_unsuffixed(max);
+                quote!(#[global(#min..=#max)])
+            },
+            None => quote!(#[global(#min..)]),
+        });
+    }
+    if let Some(default) = element.default_value().map_err(schema_error)? {
+        let value = match default {
+            DynamicValue::UnsignedInt(value) => unsigned_tokens(value),
+            DynamicValue::Integer(value) => signed_tokens(value),
+            DynamicValue::Float(value) => float_tokens(value, span)?,
+            DynamicValue::Date(value) => signed_tokens(value.nanos()),
+            DynamicValue::Utf8(value) | DynamicValue::String(value) => quote!(#value),
+            DynamicValue::Binary(_) | DynamicValue::Master(_) => unreachable!("schemas can't declare binary or master defaults"),
+        };
+        attrs.extend(quote!(#[default(#value)]));
+    }
+    let range = if let Some(range) = element.unsigned_int_range().map_err(schema_error)? {
+        Some(range_tokens(&range, |value| Ok(unsigned_tokens(*value)))?)
+    } else if let Some(range) = element.signed_int_range().map_err(schema_error)? {
+        Some(range_tokens(&range, |value| Ok(signed_tokens(*value)))?)
+    } else if let Some(range) = element.float_range().map_err(schema_error)? {
+        Some(range_tokens(&range, |value| float_tokens(*value, span))?)
+    } else {
+        None
+    };
+    if let Some(range) = range {
+        attrs.extend(quote!(#[range(#range)]));
+    }
+    if let Some(length) = element.length_range().map_err(schema_error)? {
+        let length = range_tokens(&length, |value| Ok(unsigned_tokens(*value)))?;
+        attrs.extend(quote!(#[length(#length)]));
+    }
+    if element.occurs != Default::default() {
+        let min = Literal::u64_unsuffixed(element.occurs.min);
+        let max = match element.occurs.max {
+            Some(max) => Literal::u64_unsuffixed(max).into_token_stream(),
+            None => quote!(_),
+        };
+        attrs.extend(quote!(#[occurs(#min, #max)]));
+    }
+    Ok(attrs)
+}
+
+impl EbmlSchemaInput {
+
+    pub fn implement(self) -> Result<TokenStream> {
+        let EbmlSchemaInput { attrs, visibility, ident, file } = self;
+        let span = file.span();
+
+        let mut path = PathBuf::from(file.value());
+        if path.is_relative() {
+            if let Ok(manifest_dir) = std::env::var("CARGO_MANIFEST_DIR") {
+                path = PathBuf::from(manifest_dir).join(path);
+            }
+        }
+        let schema = EbmlSchema::load(&path).map_err(|err| Error::new(span, format!("could not load schema '{}': {}", path.display(), err)))?;
+
+        let ident = match ident {
+            Some(ident) => ident,
+            None => Ident::new(&format!("{}Spec", to_variant_name(&schema.doc_type)), span),
+        };
+        let attrs = if attrs.is_empty() && visibility.is_none() {
+            quote!(#[derive(Clone, Debug, PartialEq)])
+        } else {
+            quote!(#(#attrs)*)
+        };
+        let visibility = visibility.map_or_else(|| quote!(pub), |visibility| visibility.into_token_stream());
+
+        let variants = schema.elements.iter().map(|element| {
+            let name = variant_ident(element, span)?;
+            let parent = match element.parent_id.and_then(|parent_id| schema.element(parent_id)) {
+                Some(parent) => Some(variant_ident(parent, span)?),
+                None => None,
+            };
+            let attrs = element_attributes(element, parent, span)?;
+            Ok(quote! {
+                #attrs
+                #name
+            })
+        }).collect::<Result<Vec<_>>>()?;
+
+        // Referencing the file makes the compiler rebuild the spec whenever the schema changes
+        let path = path.to_string_lossy().into_owned();
+        Ok(quote!(
+            const _: &str = include_str!(#path);
+
+            #[ebml_iterable::specs::ebml_specification]
+            #attrs
+            #visibility enum #ident {
+                #(#variants),*
+            }
+        ))
+    }
+}
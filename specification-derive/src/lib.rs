@@ -3,10 +3,14 @@ extern crate proc_macro;
 mod ast;
 mod attr;
 mod easy_ebml;
+#[cfg(feature = "schema")]
+mod ebml_schema;
 
 use proc_macro::TokenStream;
 use syn::{ItemEnum, Error};
 use crate::easy_ebml::EasyEBML;
+#[cfg(feature = "schema")]
+use crate::ebml_schema::EbmlSchemaInput;
 
 ///
 /// Attribute that derives implementations of EbmlSpecification and EbmlTag for an enum.
@@ -17,6 +21,14 @@ use crate::easy_ebml::EasyEBML;
 ///   * __#[id(`u64`)]__ - This attribute specifies the "id" of the tag. e.g. `0x1a45dfa3`
 ///   * __#[data_type(`TagDataType`)]__ - This attribute specifies the type of data contained in the tag. e.g. `TagDataType::UnsignedInt`
 ///
+/// The following attributes are optional:
+///   * __#[parent(`Variant`)]__ - This attribute specifies the "Master" variant that contains the tag. e.g. `Ebml`
+///   * __#[default(`value`)]__ - This attribute specifies the default value of a non-master tag, which is returned by `EbmlSpecification::get_default()`.  The value is written as it would be for the variant's data, except that "Utf8" and "String" tags take a string literal, "Binary" tags take a byte array or byte string, and "Date" tags take a number of nanoseconds. e.g. `1000000`
+///   * __#[range(`range`)]__ - This attribute restricts the values of an "UnsignedInt", "Integer" or "Float" tag.  The range is written as a rust range, a comparison, a lower and an upper comparison separated by a comma, or a single value. e.g. `1..`, `0..=5`, `> 0.0`, `> 0.0, <= 1.0`
+///   * __#[length(`range`)]__ - This attribute restricts the length in bytes of a "Utf8", "String" or "Binary" tag, using the same syntax as `#[range()]`. e.g. `16`
+///   * __#[occurs(`min`, `max`)]__ - This attribute specifies how many times the tag may occur within its parent.  A `max` of `_` allows any number of occurrences. e.g. `1, 1` for a mandatory tag that can't be repeated
+///   * __#[global]__ or __#[global(`range`)]__ - This attribute marks the tag as global, meaning it may occur within any "Master" tag rather than a single parent.  The optional range limits the levels the tag may occur at, where top level tags are at level 0.  Variants with the ids of "Void" (`0xec`) or "CRC-32" (`0xbf`) and no `#[parent()]` are global by default. e.g. `1..`
+///
 /// # Note
 ///
 /// This attribute modifies the variants in the enumeration by adding fields to them.  It also will add a `RawTag(u64, Vec<u8>)` variant to the enumeration.
@@ -49,9 +61,46 @@ pub fn easy_ebml(input: TokenStream) -> TokenStream {
                 Crc32: Binary = 0xbf,\
                 Ebml: Master = 0x1a45dfa3,\
                 Ebml/EbmlVersion: UnsignedInt = 0x4286,\
+                #[range(1..)]\
+                Ebml/EbmlReadVersion: UnsignedInt = 0x42f7,\
             }").to_compile_error())
         },
     };
 
     input.implement().unwrap_or_else(|err| err.to_compile_error()).into()
 }
+
+///
+/// Macro that generates an [`#[ebml_specification]`](macro@ebml_specification) enum from an [EBML Schema](https://datatracker.ietf.org/doc/rfc8794/) XML file at compile time.
+///
+/// The schema file is resolved relative to the directory containing the `Cargo.toml` of the crate being compiled.  Every element in the schema becomes a variant named after the element (converted to upper camel case, e.g. `EBMLMaxIDLength` becomes `EbmlMaxIdLength` and `CRC-32` becomes `Crc32`), with the `#[id]`, `#[data_type]`, `#[parent]`, `#[default]`, `#[range]`, `#[length]`, `#[occurs]` and `#[global]` attributes taken from the schema.  Parents and global levels are derived from the `path` of each element, and element documentation is kept as doc comments on the variants.  `TagDataType` must be in scope where the macro is used.
+///
+/// This macro is only available with the `schema` feature, which adds a dependency on `roxmltree`.
+///
+/// The enum can be declared explicitly:
+///
+/// ```ignore
+/// ebml_schema! {
+///     #[derive(Clone, Debug, PartialEq)]
+///     pub enum MatroskaSpec = "schemas/matroska.xml";
+/// }
+/// ```
+///
+/// or only the file can be given, in which case a public enum deriving `Clone`, `Debug` and `PartialEq` is named after the `docType` of the schema (e.g. `MatroskaSpec` for "matroska"):
+///
+/// ```ignore
+/// ebml_schema!("schemas/matroska.xml");
+/// ```
+///
+#[cfg(feature = "schema")]
+#[proc_macro]
+pub fn ebml_schema(input: TokenStream) -> TokenStream {
+    let input = match syn::parse::<EbmlSchemaInput>(input) {
+        Ok(syntax_tree) => syntax_tree,
+        Err(err) => {
+            return TokenStream::from(Error::new(err.span(), "ebml_schema! content must be of format: \"path/to/schema.xml\" or: enum Name = \"path/to/schema.xml\"").to_compile_error())
+        },
+    };
+
+    input.implement().unwrap_or_else(|err| err.to_compile_error()).into()
+}
@@ -0,0 +1,123 @@
+use std::convert::TryFrom;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+#[cfg(feature = "chrono")]
+const NANOS_PER_SECOND: i64 = 1_000_000_000;
+
+///
+/// The value of a [`TagDataType::Date`][`crate::TagDataType::Date`] element.
+///
+/// Per [RFC 8794](https://datatracker.ietf.org/doc/rfc8794/), dates are stored as a signed number of nanoseconds relative to the EBML epoch, 2001-01-01T00:00:00 UTC.  Conversions to [`SystemTime`] are always available, and conversions to `chrono::DateTime<Utc>` are available when the `"chrono"` feature is enabled.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use ebml_iterable_specification::Date;
+///
+/// let date = Date::from_nanos(1_000_000_000);
+/// assert_eq!(UNIX_EPOCH + Duration::from_secs(Date::EPOCH_UNIX_SECONDS + 1), date.to_system_time());
+/// assert_eq!(Some(date), Date::from_system_time(date.to_system_time()));
+/// ```
+///
+#[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug, Default)]
+pub struct Date {
+    nanos: i64,
+}
+
+impl Date {
+
+    ///
+    /// The EBML epoch (2001-01-01T00:00:00 UTC), measured in seconds since the Unix epoch.
+    ///
+    pub const EPOCH_UNIX_SECONDS: u64 = 978_307_200;
+
+    ///
+    /// Creates a date from a number of nanoseconds relative to 2001-01-01T00:00:00 UTC.
+    ///
+    pub fn from_nanos(nanos: i64) -> Self {
+        Date { nanos }
+    }
+
+    ///
+    /// Returns the number of nanoseconds relative to 2001-01-01T00:00:00 UTC.
+    ///
+    pub fn nanos(&self) -> i64 {
+        self.nanos
+    }
+
+    ///
+    /// Converts the date into a [`SystemTime`].
+    ///
+    pub fn to_system_time(&self) -> SystemTime {
+        let epoch = UNIX_EPOCH + Duration::from_secs(Self::EPOCH_UNIX_SECONDS);
+        let offset = Duration::from_nanos(self.nanos.unsigned_abs());
+        if self.nanos < 0 {
+            epoch - offset
+        } else {
+            epoch + offset
+        }
+    }
+
+    ///
+    /// Creates a date from a [`SystemTime`].
+    ///
+    /// Returns `None` if the time is too far from 2001-01-01T00:00:00 UTC to be represented (roughly 292 years in either direction).
+    ///
+    pub fn from_system_time(time: SystemTime) -> Option<Self> {
+        let epoch = UNIX_EPOCH + Duration::from_secs(Self::EPOCH_UNIX_SECONDS);
+        let nanos = match time.duration_since(epoch) {
+            Ok(offset) => i64::try_from(offset.as_nanos()).ok()?,
+            Err(before) => i64::try_from(before.duration().as_nanos()).ok()?.checked_neg()?,
+        };
+        Some(Date { nanos })
+    }
+
+    ///
+    /// Converts the date into a `chrono::DateTime<Utc>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{TimeZone, Utc};
+    /// use ebml_iterable_specification::Date;
+    ///
+    /// let time = Utc.with_ymd_and_hms(2000, 12, 31, 23, 59, 58).unwrap() + chrono::Duration::milliseconds(500);
+    /// assert_eq!(time, Date::from_nanos(-1_500_000_000).to_chrono());
+    /// assert_eq!(Some(Date::from_nanos(-1_500_000_000)), Date::from_chrono(&time));
+    /// ```
+    ///
+    #[cfg(feature = "chrono")]
+    pub fn to_chrono(&self) -> chrono::DateTime<chrono::Utc> {
+        use chrono::TimeZone;
+
+        let seconds = self.nanos.div_euclid(NANOS_PER_SECOND) + Self::EPOCH_UNIX_SECONDS as i64;
+        let subsec_nanos = self.nanos.rem_euclid(NANOS_PER_SECOND) as u32;
+        chrono::Utc.timestamp_opt(seconds, subsec_nanos).single().expect("every Date is within the range of chrono::DateTime")
+    }
+
+    ///
+    /// Creates a date from a `chrono::DateTime<Utc>`.
+    ///
+    /// Returns `None` if the time is too far from 2001-01-01T00:00:00 UTC to be represented (roughly 292 years in either direction).
+    ///
+    #[cfg(feature = "chrono")]
+    pub fn from_chrono(time: &chrono::DateTime<chrono::Utc>) -> Option<Self> {
+        let seconds = time.timestamp().checked_sub(Self::EPOCH_UNIX_SECONDS as i64)?;
+        let nanos = seconds.checked_mul(NANOS_PER_SECOND)?.checked_add(i64::from(time.timestamp_subsec_nanos()))?;
+        Some(Date { nanos })
+    }
+}
+
+impl From<Date> for SystemTime {
+    fn from(date: Date) -> Self {
+        date.to_system_time()
+    }
+}
+
+#[cfg(feature = "chrono")]
+impl From<Date> for chrono::DateTime<chrono::Utc> {
+    fn from(date: Date) -> Self {
+        date.to_chrono()
+    }
+}
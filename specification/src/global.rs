@@ -0,0 +1,70 @@
+///
+/// The id of the "Void" element, which is global in every EBML document.
+///
+/// "Void" elements are used to reserve space or to void data that should be ignored, and may occur at any level.
+///
+pub const VOID_ID: u64 = 0xEC;
+
+///
+/// The id of the "CRC-32" element, which is global in every EBML document.
+///
+/// "CRC-32" elements contain a checksum of the other children of their parent, so they may occur at any level except the top level.
+///
+pub const CRC32_ID: u64 = 0xBF;
+
+///
+/// The levels at which a global tag may occur.
+///
+/// Global tags are not tied to a parent - they may occur within any "Master" tag whose children are at an allowed level.  The level of a tag is the number of tags enclosing it, so top level tags are at level 0.  This mirrors the global placeholder (e.g. `(1-\)`) in the path of an [EBML Schema](https://datatracker.ietf.org/doc/rfc8794/) element.  A `max` of `None` allows any level.
+///
+/// # Examples
+///
+/// ```
+/// use ebml_iterable_specification::{GlobalLevels, CRC32_ID};
+///
+/// let levels = GlobalLevels::standard(CRC32_ID).unwrap();
+/// assert!(!levels.contains(0));
+/// assert!(levels.contains(1));
+/// assert!(levels.contains(5));
+/// ```
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct GlobalLevels {
+    pub min: u64,
+    pub max: Option<u64>,
+}
+
+impl GlobalLevels {
+
+    ///
+    /// Allows a tag at any level, including the top level.
+    ///
+    pub const ANY: GlobalLevels = GlobalLevels { min: 0, max: None };
+
+    ///
+    /// Creates a level constraint from the minimum and (optional) maximum level.
+    ///
+    pub fn new(min: u64, max: Option<u64>) -> Self {
+        GlobalLevels { min, max }
+    }
+
+    ///
+    /// Returns the levels of the global elements that are defined for every EBML document: "Void" ([`VOID_ID`]) at any level, and "CRC-32" ([`CRC32_ID`]) at any level except the top level.
+    ///
+    /// Returns `None` for any other id.
+    ///
+    pub fn standard(id: u64) -> Option<Self> {
+        match id {
+            VOID_ID => Some(GlobalLevels::ANY),
+            CRC32_ID => Some(GlobalLevels::new(1, None)),
+            _ => None,
+        }
+    }
+
+    ///
+    /// Returns `true` if a tag may occur at `level`.
+    ///
+    pub fn contains(&self, level: u64) -> bool {
+        level >= self.min && self.max.map_or(true, |max| level <= max)
+    }
+}
@@ -0,0 +1,337 @@
+use std::fmt;
+use std::marker::PhantomData;
+use std::rc::Rc;
+use std::sync::Arc;
+
+use super::{Date, EbmlSpecification, EbmlTag, GlobalLevels, Master, Occurs, TagDataType, ValueRange};
+
+///
+/// An instance-based counterpart of [`EbmlSpecification`].
+///
+/// [`EbmlSpecification`] only has associated functions, so a specification is fixed at compile time by the tag type.  This trait has the same methods taking `&self`, which allows specifications that carry state - such as a spec loaded at runtime, or one of several specs chosen by the DocType of a file - and can be used as a trait object (e.g. `Box<dyn EbmlSpecificationInstance<MyTag>>`).
+///
+/// Every [`EbmlSpecification`] can be used through the zero-sized [`StaticSpecification`] adapter, which is what the readers and writers in `ebml-iterable` use unless a spec instance is provided.  The requirements on each method are the same as for the matching [`EbmlSpecification`] method.
+///
+pub trait EbmlSpecificationInstance<T: EbmlTag<T> + Clone> {
+
+    ///
+    /// Pulls the data type for a tag from the spec, based on the tag id.  See [`EbmlSpecification::get_tag_data_type()`].
+    ///
+    fn get_tag_data_type(&self, id: u64) -> TagDataType;
+
+    ///
+    /// Creates an unsigned integer type tag from the spec.  See [`EbmlSpecification::get_unsigned_int_tag()`].
+    ///
+    fn get_unsigned_int_tag(&self, id: u64, data: u64) -> Option<T>;
+
+    ///
+    /// Creates a signed integer type tag from the spec.  See [`EbmlSpecification::get_signed_int_tag()`].
+    ///
+    fn get_signed_int_tag(&self, id: u64, data: i64) -> Option<T>;
+
+    ///
+    /// Creates a utf8 type tag from the spec.  See [`EbmlSpecification::get_utf8_tag()`].
+    ///
+    fn get_utf8_tag(&self, id: u64, data: String) -> Option<T>;
+
+    ///
+    /// Creates a binary type tag from the spec.  See [`EbmlSpecification::get_binary_tag()`].
+    ///
+    fn get_binary_tag(&self, id: u64, data: &[u8]) -> Option<T>;
+
+    ///
+    /// Creates a float type tag from the spec.  See [`EbmlSpecification::get_float_tag()`].
+    ///
+    fn get_float_tag(&self, id: u64, data: f64) -> Option<T>;
+
+    ///
+    /// Creates a master type tag from the spec.  See [`EbmlSpecification::get_master_tag()`].
+    ///
+    fn get_master_tag(&self, id: u64, data: Master<T>) -> Option<T>;
+
+    ///
+    /// Creates a date type tag from the spec.  See [`EbmlSpecification::get_date_tag()`].
+    ///
+    fn get_date_tag(&self, _id: u64, _data: Date) -> Option<T> {
+        None
+    }
+
+    ///
+    /// Creates an ASCII string type tag from the spec.  See [`EbmlSpecification::get_string_tag()`].
+    ///
+    fn get_string_tag(&self, _id: u64, _data: String) -> Option<T> {
+        None
+    }
+
+    ///
+    /// Creates a tag that does not conform to the spec.  See [`EbmlSpecification::get_raw_tag()`].
+    ///
+    fn get_raw_tag(&self, id: u64, data: &[u8]) -> T;
+
+    ///
+    /// Gets the name of a tag from the spec.  See [`EbmlSpecification::get_tag_name()`].
+    ///
+    fn get_tag_name(&self, _id: u64) -> Option<&str> {
+        None
+    }
+
+    ///
+    /// Gets the id of a tag from the spec, based on the tag name.  See [`EbmlSpecification::get_tag_id_by_name()`].
+    ///
+    fn get_tag_id_by_name(&self, _name: &str) -> Option<u64> {
+        None
+    }
+
+    ///
+    /// Creates a tag containing the default value that the spec declares for the input id.  See [`EbmlSpecification::get_default()`].
+    ///
+    fn get_default(&self, _id: u64) -> Option<T> {
+        None
+    }
+
+    ///
+    /// Gets the ids of all direct children of the input "Master" id that have a default value.  See [`EbmlSpecification::get_children_with_defaults()`].
+    ///
+    fn get_children_with_defaults(&self, _id: u64) -> &[u64] {
+        &[]
+    }
+
+    ///
+    /// Gets the range of values allowed for an "UnsignedInt" tag.  See [`EbmlSpecification::get_unsigned_int_range()`].
+    ///
+    fn get_unsigned_int_range(&self, _id: u64) -> Option<ValueRange<u64>> {
+        None
+    }
+
+    ///
+    /// Gets the range of values allowed for an "Integer" tag.  See [`EbmlSpecification::get_signed_int_range()`].
+    ///
+    fn get_signed_int_range(&self, _id: u64) -> Option<ValueRange<i64>> {
+        None
+    }
+
+    ///
+    /// Gets the range of values allowed for a "Float" tag.  See [`EbmlSpecification::get_float_range()`].
+    ///
+    fn get_float_range(&self, _id: u64) -> Option<ValueRange<f64>> {
+        None
+    }
+
+    ///
+    /// Gets the range of data lengths allowed for a "Utf8", "String" or "Binary" tag.  See [`EbmlSpecification::get_length_range()`].
+    ///
+    fn get_length_range(&self, _id: u64) -> Option<ValueRange<u64>> {
+        None
+    }
+
+    ///
+    /// Gets the number of times a tag may occur within its parent.  See [`EbmlSpecification::get_occurs()`].
+    ///
+    fn get_occurs(&self, _id: u64) -> Option<Occurs> {
+        None
+    }
+
+    ///
+    /// Gets the ids of all mandatory direct children of the input "Master" id.  See [`EbmlSpecification::get_mandatory_children()`].
+    ///
+    fn get_mandatory_children(&self, _id: u64) -> &[u64] {
+        &[]
+    }
+
+    ///
+    /// Gets the ids of all tags in the specification.  See [`EbmlSpecification::get_tag_ids()`].
+    ///
+    fn get_tag_ids(&self) -> &[u64] {
+        &[]
+    }
+
+    ///
+    /// Gets the id of the "Master" tag that contains the input id.  See [`EbmlSpecification::get_parent_id()`].
+    ///
+    fn get_parent_id(&self, _id: u64) -> Option<u64> {
+        None
+    }
+
+    ///
+    /// Gets the levels at which a global tag may occur.  See [`EbmlSpecification::get_global_levels()`].
+    ///
+    fn get_global_levels(&self, id: u64) -> Option<GlobalLevels> {
+        GlobalLevels::standard(id)
+    }
+
+    ///
+    /// Tests if `id` is a child of the `parent` tag.  See [`EbmlTag::is_child()`].
+    ///
+    /// The default implementation asks the parent tag itself.
+    ///
+    fn is_child(&self, parent: &T, id: u64) -> bool {
+        parent.is_child(id)
+    }
+}
+
+///
+/// Uses a compile-time [`EbmlSpecification`] as an [`EbmlSpecificationInstance`].
+///
+/// This is a zero-sized adapter that forwards every method to the associated functions of `T`.
+///
+pub struct StaticSpecification<T> {
+    _spec: PhantomData<fn() -> T>,
+}
+
+impl<T> StaticSpecification<T> {
+
+    ///
+    /// Creates the adapter.
+    ///
+    pub fn new() -> Self {
+        StaticSpecification { _spec: PhantomData }
+    }
+}
+
+impl<T> Default for StaticSpecification<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T> Clone for StaticSpecification<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for StaticSpecification<T> {}
+
+impl<T> fmt::Debug for StaticSpecification<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "StaticSpecification<{}>", std::any::type_name::<T>())
+    }
+}
+
+impl<T: EbmlSpecification<T> + EbmlTag<T> + Clone> EbmlSpecificationInstance<T> for StaticSpecification<T> {
+    fn get_tag_data_type(&self, id: u64) -> TagDataType {
+        T::get_tag_data_type(id)
+    }
+
+    fn get_unsigned_int_tag(&self, id: u64, data: u64) -> Option<T> {
+        T::get_unsigned_int_tag(id, data)
+    }
+
+    fn get_signed_int_tag(&self, id: u64, data: i64) -> Option<T> {
+        T::get_signed_int_tag(id, data)
+    }
+
+    fn get_utf8_tag(&self, id: u64, data: String) -> Option<T> {
+        T::get_utf8_tag(id, data)
+    }
+
+    fn get_binary_tag(&self, id: u64, data: &[u8]) -> Option<T> {
+        T::get_binary_tag(id, data)
+    }
+
+    fn get_float_tag(&self, id: u64, data: f64) -> Option<T> {
+        T::get_float_tag(id, data)
+    }
+
+    fn get_master_tag(&self, id: u64, data: Master<T>) -> Option<T> {
+        T::get_master_tag(id, data)
+    }
+
+    fn get_date_tag(&self, id: u64, data: Date) -> Option<T> {
+        T::get_date_tag(id, data)
+    }
+
+    fn get_string_tag(&self, id: u64, data: String) -> Option<T> {
+        T::get_string_tag(id, data)
+    }
+
+    fn get_raw_tag(&self, id: u64, data: &[u8]) -> T {
+        T::get_raw_tag(id, data)
+    }
+
+    fn get_tag_name(&self, id: u64) -> Option<&str> {
+        T::get_tag_name(id)
+    }
+
+    fn get_tag_id_by_name(&self, name: &str) -> Option<u64> {
+        T::get_tag_id_by_name(name)
+    }
+
+    fn get_default(&self, id: u64) -> Option<T> {
+        T::get_default(id)
+    }
+
+    fn get_children_with_defaults(&self, id: u64) -> &[u64] {
+        T::get_children_with_defaults(id)
+    }
+
+    fn get_unsigned_int_range(&self, id: u64) -> Option<ValueRange<u64>> {
+        T::get_unsigned_int_range(id)
+    }
+
+    fn get_signed_int_range(&self, id: u64) -> Option<ValueRange<i64>> {
+        T::get_signed_int_range(id)
+    }
+
+    fn get_float_range(&self, id: u64) -> Option<ValueRange<f64>> {
+        T::get_float_range(id)
+    }
+
+    fn get_length_range(&self, id: u64) -> Option<ValueRange<u64>> {
+        T::get_length_range(id)
+    }
+
+    fn get_occurs(&self, id: u64) -> Option<Occurs> {
+        T::get_occurs(id)
+    }
+
+    fn get_mandatory_children(&self, id: u64) -> &[u64] {
+        T::get_mandatory_children(id)
+    }
+
+    fn get_tag_ids(&self) -> &[u64] {
+        T::get_tag_ids()
+    }
+
+    fn get_parent_id(&self, id: u64) -> Option<u64> {
+        T::get_parent_id(id)
+    }
+
+    fn get_global_levels(&self, id: u64) -> Option<GlobalLevels> {
+        T::get_global_levels(id)
+    }
+}
+
+// Forwards every method through a pointer type, so that specs can be shared (`Arc`), borrowed or boxed as trait objects
+macro_rules! forward_instance {
+    ($($ptr:ty),*) => {$(
+        impl<T: EbmlTag<T> + Clone, S: EbmlSpecificationInstance<T> + ?Sized> EbmlSpecificationInstance<T> for $ptr {
+            fn get_tag_data_type(&self, id: u64) -> TagDataType { (**self).get_tag_data_type(id) }
+            fn get_unsigned_int_tag(&self, id: u64, data: u64) -> Option<T> { (**self).get_unsigned_int_tag(id, data) }
+            fn get_signed_int_tag(&self, id: u64, data: i64) -> Option<T> { (**self).get_signed_int_tag(id, data) }
+            fn get_utf8_tag(&self, id: u64, data: String) -> Option<T> { (**self).get_utf8_tag(id, data) }
+            fn get_binary_tag(&self, id: u64, data: &[u8]) -> Option<T> { (**self).get_binary_tag(id, data) }
+            fn get_float_tag(&self, id: u64, data: f64) -> Option<T> { (**self).get_float_tag(id, data) }
+            fn get_master_tag(&self, id: u64, data: Master<T>) -> Option<T> { (**self).get_master_tag(id, data) }
+            fn get_date_tag(&self, id: u64, data: Date) -> Option<T> { (**self).get_date_tag(id, data) }
+            fn get_string_tag(&self, id: u64, data: String) -> Option<T> { (**self).get_string_tag(id, data) }
+            fn get_raw_tag(&self, id: u64, data: &[u8]) -> T { (**self).get_raw_tag(id, data) }
+            fn get_tag_name(&self, id: u64) -> Option<&str> { (**self).get_tag_name(id) }
+            fn get_tag_id_by_name(&self, name: &str) -> Option<u64> { (**self).get_tag_id_by_name(name) }
+            fn get_default(&self, id: u64) -> Option<T> { (**self).get_default(id) }
+            fn get_children_with_defaults(&self, id: u64) -> &[u64] { (**self).get_children_with_defaults(id) }
+            fn get_unsigned_int_range(&self, id: u64) -> Option<ValueRange<u64>> { (**self).get_unsigned_int_range(id) }
+            fn get_signed_int_range(&self, id: u64) -> Option<ValueRange<i64>> { (**self).get_signed_int_range(id) }
+            fn get_float_range(&self, id: u64) -> Option<ValueRange<f64>> { (**self).get_float_range(id) }
+            fn get_length_range(&self, id: u64) -> Option<ValueRange<u64>> { (**self).get_length_range(id) }
+            fn get_occurs(&self, id: u64) -> Option<Occurs> { (**self).get_occurs(id) }
+            fn get_mandatory_children(&self, id: u64) -> &[u64] { (**self).get_mandatory_children(id) }
+            fn get_tag_ids(&self) -> &[u64] { (**self).get_tag_ids() }
+            fn get_parent_id(&self, id: u64) -> Option<u64> { (**self).get_parent_id(id) }
+            fn get_global_levels(&self, id: u64) -> Option<GlobalLevels> { (**self).get_global_levels(id) }
+            fn is_child(&self, parent: &T, id: u64) -> bool { (**self).is_child(parent, id) }
+        }
+    )*};
+}
+
+forward_instance!(&S, Box<S>, Rc<S>, Arc<S>);
@@ -8,15 +8,33 @@
 ///
 pub mod empty_spec;
 
+mod date;
+pub use date::Date;
+
+mod range;
+pub use range::ValueRange;
+
+mod occurs;
+pub use occurs::Occurs;
+
+mod global;
+pub use global::{GlobalLevels, CRC32_ID, VOID_ID};
+
+mod instance;
+pub use instance::{EbmlSpecificationInstance, StaticSpecification};
+
+#[cfg(feature = "schema")]
+pub mod schema;
+
 ///
 /// Different data types defined in the EBML specification.
 ///
 /// # Notes
 ///
-/// This library made a concious decision to not work with "Date" elements from EBML due to lack of built-in support for dates in Rust. Specification implementations should treat Date elements as Binary so that consumers have the option of parsing the unaltered data using their library of choice, if needed.
+/// "Date" elements are decoded into a [`Date`], which stores the signed number of nanoseconds since 2001-01-01T00:00:00 UTC.  Conversion to `chrono` types is available through the optional `"chrono"` feature.
+///
+/// "String" elements may only contain printable ascii characters (0x20 to 0x7E), while "Utf8" elements may contain any unicode text.
 ///
-
-// Possible future feature flag to enable Date functionality by having `chrono` as an optional dependency?
 #[derive(Copy, Clone, Eq, PartialEq, Ord, PartialOrd, Hash, Debug)]
 pub enum TagDataType {
     Master,
@@ -25,6 +43,8 @@ pub enum TagDataType {
     Utf8,
     Binary,
     Float,
+    Date,
+    String,
 }
 
 ///
@@ -94,6 +114,24 @@ pub trait EbmlSpecification<T: EbmlSpecification<T> + EbmlTag<T> + Clone> {
     ///
     fn get_master_tag(id: u64, data: Master<T>) -> Option<T>;
 
+    ///
+    /// Creates a date type tag from the spec.
+    ///
+    /// This function *must* return `None` if the input id is not in the specification or if the input id data type is not [`TagDataType::Date`].  The default implementation returns `None` for every id, which is only correct for specifications without "Date" elements.
+    ///
+    fn get_date_tag(_id: u64, _data: Date) -> Option<T> {
+        None
+    }
+
+    ///
+    /// Creates an ASCII string type tag from the spec.
+    ///
+    /// This function *must* return `None` if the input id is not in the specification or if the input id data type is not [`TagDataType::String`].  The default implementation returns `None` for every id, which is only correct for specifications without "String" elements.
+    ///
+    fn get_string_tag(_id: u64, _data: String) -> Option<T> {
+        None
+    }
+
     ///
     /// Creates a tag that does not conform to the spec.
     ///
@@ -101,6 +139,123 @@ pub trait EbmlSpecification<T: EbmlSpecification<T> + EbmlTag<T> + Clone> {
     ///
     fn get_raw_tag(id: u64, data: &[u8]) -> T;
 
+    ///
+    /// Gets the name of a tag from the spec, based on the tag id.
+    ///
+    /// This function should return `None` if the input id is not in the specification.  Names are used to resolve human readable paths to tags.  The default implementation returns `None` for every id.
+    ///
+    fn get_tag_name(_id: u64) -> Option<&'static str> {
+        None
+    }
+
+    ///
+    /// Gets the id of a tag from the spec, based on the tag name.
+    ///
+    /// This function should return `None` if no tag with the input name is in the specification.  The default implementation returns `None` for every name.
+    ///
+    fn get_tag_id_by_name(_name: &str) -> Option<u64> {
+        None
+    }
+
+    ///
+    /// Creates a tag containing the default value that the spec declares for the input id.
+    ///
+    /// This function should return `None` if the input id is not in the specification or if the specification doesn't declare a default value for it.  The default implementation returns `None` for every id.
+    ///
+    fn get_default(_id: u64) -> Option<T> {
+        None
+    }
+
+    ///
+    /// Gets the ids of all direct children of the input "Master" id that have a default value.
+    ///
+    /// Every id returned by this function must produce a tag from [`Self::get_default()`].  The default implementation returns an empty slice for every id.
+    ///
+    fn get_children_with_defaults(_id: u64) -> &'static [u64] {
+        &[]
+    }
+
+    ///
+    /// Gets the range of values allowed for an "UnsignedInt" tag.
+    ///
+    /// This function should return `None` if the input id is not in the specification or if the spec doesn't restrict its values.  The default implementation returns `None` for every id.
+    ///
+    fn get_unsigned_int_range(_id: u64) -> Option<ValueRange<u64>> {
+        None
+    }
+
+    ///
+    /// Gets the range of values allowed for an "Integer" tag.
+    ///
+    /// This function should return `None` if the input id is not in the specification or if the spec doesn't restrict its values.  The default implementation returns `None` for every id.
+    ///
+    fn get_signed_int_range(_id: u64) -> Option<ValueRange<i64>> {
+        None
+    }
+
+    ///
+    /// Gets the range of values allowed for a "Float" tag.
+    ///
+    /// This function should return `None` if the input id is not in the specification or if the spec doesn't restrict its values.  The default implementation returns `None` for every id.
+    ///
+    fn get_float_range(_id: u64) -> Option<ValueRange<f64>> {
+        None
+    }
+
+    ///
+    /// Gets the range of data lengths (in bytes) allowed for a "Utf8", "String" or "Binary" tag.
+    ///
+    /// This function should return `None` if the input id is not in the specification or if the spec doesn't restrict its length.  The default implementation returns `None` for every id.
+    ///
+    fn get_length_range(_id: u64) -> Option<ValueRange<u64>> {
+        None
+    }
+
+    ///
+    /// Gets the number of times a tag may occur within its parent.
+    ///
+    /// This function should return `None` if the input id is not in the specification or if the spec doesn't restrict its occurrences.  The default implementation returns `None` for every id.
+    ///
+    fn get_occurs(_id: u64) -> Option<Occurs> {
+        None
+    }
+
+    ///
+    /// Gets the ids of all direct children of the input "Master" id that are mandatory (i.e. whose [`Occurs::min`] is at least 1).
+    ///
+    /// The default implementation returns an empty slice for every id.
+    ///
+    fn get_mandatory_children(_id: u64) -> &'static [u64] {
+        &[]
+    }
+
+    ///
+    /// Gets the ids of all tags in the specification, in the order they are declared.
+    ///
+    /// This is used to describe the specification as a whole, such as when exporting it as an EBML Schema.  The default implementation returns an empty slice.
+    ///
+    fn get_tag_ids() -> &'static [u64] {
+        &[]
+    }
+
+    ///
+    /// Gets the id of the "Master" tag that contains the input id.
+    ///
+    /// This function should return `None` if the input id is not in the specification or is a top level (or global) tag.  The default implementation returns `None` for every id.
+    ///
+    fn get_parent_id(_id: u64) -> Option<u64> {
+        None
+    }
+
+    ///
+    /// Gets the levels at which a global tag may occur.
+    ///
+    /// This function should return `None` if the input id is not a global tag.  Global tags may occur within any "Master" tag whose children are at one of the returned levels, regardless of [`EbmlTag::is_child()`].  The default implementation returns [`GlobalLevels::standard()`], which covers the "Void" and "CRC-32" elements that are global in every EBML document.
+    ///
+    fn get_global_levels(id: u64) -> Option<GlobalLevels> {
+        GlobalLevels::standard(id)
+    }
+
 }
 
 ///
@@ -161,6 +316,24 @@ pub trait EbmlTag<T: Clone> {
     ///
     fn as_master(&self) -> Option<&Master<T>>;
 
+    ///
+    /// Gets a reference to the date contained in `self`.
+    ///
+    /// This function *must* return `None` if the associated data type of `self` is not [`TagDataType::Date`].  The default implementation always returns `None`, which is only correct for specifications without "Date" elements.
+    ///
+    fn as_date(&self) -> Option<&Date> {
+        None
+    }
+
+    ///
+    /// Gets a reference to the ASCII string contained in `self`.
+    ///
+    /// This function *must* return `None` if the associated data type of `self` is not [`TagDataType::String`].  The default implementation always returns `None`, which is only correct for specifications without "String" elements.
+    ///
+    fn as_string(&self) -> Option<&str> {
+        None
+    }
+
     ///
     /// Tests if [id] is a child of self
     ///
@@ -0,0 +1,45 @@
+///
+/// The number of times a tag may occur within its parent.
+///
+/// This mirrors the `minOccurs` and `maxOccurs` attributes of an [EBML Schema](https://datatracker.ietf.org/doc/rfc8794/).  A `min` of 1 or more marks a mandatory tag, and a `max` of 1 marks a tag that may not be repeated.  A `max` of `None` allows any number of occurrences.
+///
+/// # Examples
+///
+/// ```
+/// use ebml_iterable_specification::Occurs;
+///
+/// let occurs = Occurs::new(1, Some(1));
+/// assert!(occurs.is_mandatory());
+/// assert!(occurs.allows(1));
+/// assert!(!occurs.allows(2));
+/// ```
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Default)]
+pub struct Occurs {
+    pub min: u64,
+    pub max: Option<u64>,
+}
+
+impl Occurs {
+
+    ///
+    /// Creates an occurrence constraint from the minimum and (optional) maximum number of occurrences.
+    ///
+    pub fn new(min: u64, max: Option<u64>) -> Self {
+        Occurs { min, max }
+    }
+
+    ///
+    /// Returns `true` if the tag must occur at least once.
+    ///
+    pub fn is_mandatory(&self) -> bool {
+        self.min > 0
+    }
+
+    ///
+    /// Returns `true` if `count` occurrences are within the constraint.
+    ///
+    pub fn allows(&self, count: u64) -> bool {
+        count >= self.min && self.max.map_or(true, |max| count <= max)
+    }
+}
@@ -0,0 +1,86 @@
+use std::fmt;
+use std::ops::Bound;
+
+///
+/// A range of values that a tag is allowed to contain.
+///
+/// This mirrors the `range` and `length` attributes of an [EBML Schema](https://datatracker.ietf.org/doc/rfc8794/).  Ranges apply to the value of "UnsignedInt", "Integer" and "Float" elements, while lengths are ranges over the size in bytes of "Utf8", "String" and "Binary" elements.
+///
+/// # Examples
+///
+/// ```
+/// use std::ops::Bound;
+/// use ebml_iterable_specification::ValueRange;
+///
+/// let range = ValueRange::new(Bound::Excluded(0.0), Bound::Unbounded);
+/// assert!(range.contains(&1.5));
+/// assert!(!range.contains(&0.0));
+/// assert_eq!(">0", range.to_string());
+/// ```
+///
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct ValueRange<T> {
+    pub min: Bound<T>,
+    pub max: Bound<T>,
+}
+
+impl<T: PartialOrd> ValueRange<T> {
+
+    ///
+    /// Creates a range from its lower and upper bounds.
+    ///
+    pub fn new(min: Bound<T>, max: Bound<T>) -> Self {
+        ValueRange { min, max }
+    }
+
+    ///
+    /// Returns `true` if `value` is within the range.
+    ///
+    pub fn contains(&self, value: &T) -> bool {
+        let above_min = match &self.min {
+            Bound::Included(min) => value >= min,
+            Bound::Excluded(min) => value > min,
+            Bound::Unbounded => true,
+        };
+        let below_max = match &self.max {
+            Bound::Included(max) => value <= max,
+            Bound::Excluded(max) => value < max,
+            Bound::Unbounded => true,
+        };
+        above_min && below_max
+    }
+}
+
+///
+/// Formats the range using the syntax of the `range` attribute in an EBML Schema (e.g. `"1-"`, `"0-5"` or `">0,<=1"`).
+///
+/// Intervals with a negative lower bound are written as comparisons (e.g. `">=-5,<=5"`), since `"-5-5"` would be ambiguous.
+///
+impl<T: fmt::Display + PartialEq> fmt::Display for ValueRange<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match (&self.min, &self.max) {
+            (Bound::Included(min), Bound::Included(max)) if min == max => write!(f, "{}", min),
+            (Bound::Included(min), Bound::Included(max)) if !is_negative(min) => write!(f, "{}-{}", min, max),
+            (Bound::Included(min), Bound::Unbounded) if !is_negative(min) => write!(f, "{}-", min),
+            (Bound::Unbounded, Bound::Unbounded) => Ok(()),
+            (min, max) => {
+                let min = match min {
+                    Bound::Included(min) => Some(format!(">={}", min)),
+                    Bound::Excluded(min) => Some(format!(">{}", min)),
+                    Bound::Unbounded => None,
+                };
+                let max = match max {
+                    Bound::Included(max) => Some(format!("<={}", max)),
+                    Bound::Excluded(max) => Some(format!("<{}", max)),
+                    Bound::Unbounded => None,
+                };
+                let parts: Vec<String> = min.into_iter().chain(max).collect();
+                write!(f, "{}", parts.join(","))
+            },
+        }
+    }
+}
+
+fn is_negative<T: fmt::Display>(value: &T) -> bool {
+    value.to_string().starts_with('-')
+}
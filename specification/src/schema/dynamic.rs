@@ -0,0 +1,321 @@
+use std::collections::HashMap;
+
+use super::{EbmlSchema, SchemaElement, SchemaError};
+use crate::{Date, EbmlSpecificationInstance, EbmlTag, GlobalLevels, Master, Occurs, TagDataType, ValueRange};
+
+///
+/// The value of a [`DynamicTag`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum DynamicValue {
+    Master(Master<DynamicTag>),
+    UnsignedInt(u64),
+    Integer(i64),
+    Utf8(String),
+    String(String),
+    Binary(Vec<u8>),
+    Float(f64),
+    Date(Date),
+}
+
+///
+/// A generic tag for specifications that are loaded at runtime.
+///
+/// Tags don't know anything about the schema they belong to - everything is looked up through the [`DynamicSpec`] that is passed to readers and writers as an [`EbmlSpecificationInstance`].  Tags that aren't declared in the spec are read as [`DynamicValue::Binary`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct DynamicTag {
+    pub id: u64,
+    pub value: DynamicValue,
+}
+
+impl DynamicTag {
+
+    ///
+    /// Creates a tag from its id and value.
+    ///
+    pub fn new(id: u64, value: DynamicValue) -> Self {
+        DynamicTag { id, value }
+    }
+}
+
+#[derive(Clone, Debug)]
+struct ElementInfo {
+    index: usize,
+    default: Option<DynamicValue>,
+    unsigned_int_range: Option<ValueRange<u64>>,
+    signed_int_range: Option<ValueRange<i64>>,
+    float_range: Option<ValueRange<f64>>,
+    length_range: Option<ValueRange<u64>>,
+}
+
+///
+/// A specification built from an [`EbmlSchema`] at runtime.
+///
+/// Construction resolves the `default`, `range` and `length` attributes of every element so that invalid schemas are reported up front.  The spec is passed to readers and writers as an [`EbmlSpecificationInstance`] (by reference, or shared through an `Rc` or `Arc`), so any number of specs can be used side by side.
+///
+#[derive(Clone, Debug)]
+pub struct DynamicSpec {
+    schema: EbmlSchema,
+    elements: HashMap<u64, ElementInfo>,
+    ids_by_name: HashMap<String, u64>,
+    tag_ids: Vec<u64>,
+    children_with_defaults: HashMap<u64, Vec<u64>>,
+    mandatory_children: HashMap<u64, Vec<u64>>,
+}
+
+impl DynamicSpec {
+
+    ///
+    /// Builds a spec from a parsed schema.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns an error if the `default`, `range` or `length` attribute of an element can't be interpreted.
+    ///
+    pub fn new(schema: EbmlSchema) -> Result<Self, SchemaError> {
+        let mut elements = HashMap::new();
+        let mut ids_by_name = HashMap::new();
+        let mut children_with_defaults: HashMap<u64, Vec<u64>> = HashMap::new();
+        let mut mandatory_children: HashMap<u64, Vec<u64>> = HashMap::new();
+
+        for (index, element) in schema.elements.iter().enumerate() {
+            let info = ElementInfo {
+                index,
+                default: element.default_value()?,
+                unsigned_int_range: element.unsigned_int_range()?,
+                signed_int_range: element.signed_int_range()?,
+                float_range: element.float_range()?,
+                length_range: element.length_range()?,
+            };
+            if let Some(parent_id) = element.parent_id {
+                if info.default.is_some() {
+                    children_with_defaults.entry(parent_id).or_default().push(element.id);
+                }
+                if element.occurs.is_mandatory() {
+                    mandatory_children.entry(parent_id).or_default().push(element.id);
+                }
+            }
+            ids_by_name.insert(element.name.clone(), element.id);
+            elements.insert(element.id, info);
+        }
+
+        let tag_ids = schema.elements.iter().map(|element| element.id).collect();
+        Ok(DynamicSpec { schema, elements, ids_by_name, tag_ids, children_with_defaults, mandatory_children })
+    }
+
+    ///
+    /// Returns the schema that the spec was built from.
+    ///
+    pub fn schema(&self) -> &EbmlSchema {
+        &self.schema
+    }
+
+    ///
+    /// Finds the declaration of an element by id.
+    ///
+    pub fn element(&self, id: u64) -> Option<&SchemaElement> {
+        self.elements.get(&id).map(|info| &self.schema.elements[info.index])
+    }
+
+    fn data_type(&self, id: u64) -> Option<TagDataType> {
+        self.element(id).map(|element| element.data_type)
+    }
+
+    fn typed_tag(&self, id: u64, data_type: TagDataType, value: DynamicValue) -> Option<DynamicTag> {
+        match self.data_type(id) {
+            Some(declared) if declared == data_type => Some(DynamicTag::new(id, value)),
+            _ => None,
+        }
+    }
+
+    fn is_child_id(&self, parent_id: u64, id: u64) -> bool {
+        let (element, parent) = match (self.element(id), self.element(parent_id)) {
+            (Some(element), Some(parent)) => (element, parent),
+            _ => return true,
+        };
+        if element.global.is_some() {
+            return true;
+        }
+        if id == parent_id {
+            return element.recursive;
+        }
+        if element.parent_id.is_none() || element.parent_id == parent.parent_id {
+            return false;
+        }
+
+        let mut ancestor = parent.parent_id;
+        while let Some(ancestor_id) = ancestor {
+            if ancestor_id == id {
+                return false;
+            }
+            ancestor = self.element(ancestor_id).and_then(|element| element.parent_id);
+        }
+        true
+    }
+}
+
+impl EbmlSpecificationInstance<DynamicTag> for DynamicSpec {
+    fn get_tag_data_type(&self, id: u64) -> TagDataType {
+        self.data_type(id).unwrap_or(TagDataType::Binary)
+    }
+
+    fn get_unsigned_int_tag(&self, id: u64, data: u64) -> Option<DynamicTag> {
+        self.typed_tag(id, TagDataType::UnsignedInt, DynamicValue::UnsignedInt(data))
+    }
+
+    fn get_signed_int_tag(&self, id: u64, data: i64) -> Option<DynamicTag> {
+        self.typed_tag(id, TagDataType::Integer, DynamicValue::Integer(data))
+    }
+
+    fn get_utf8_tag(&self, id: u64, data: String) -> Option<DynamicTag> {
+        self.typed_tag(id, TagDataType::Utf8, DynamicValue::Utf8(data))
+    }
+
+    fn get_binary_tag(&self, id: u64, data: &[u8]) -> Option<DynamicTag> {
+        self.typed_tag(id, TagDataType::Binary, DynamicValue::Binary(data.to_vec()))
+    }
+
+    fn get_float_tag(&self, id: u64, data: f64) -> Option<DynamicTag> {
+        self.typed_tag(id, TagDataType::Float, DynamicValue::Float(data))
+    }
+
+    fn get_master_tag(&self, id: u64, data: Master<DynamicTag>) -> Option<DynamicTag> {
+        self.typed_tag(id, TagDataType::Master, DynamicValue::Master(data))
+    }
+
+    fn get_date_tag(&self, id: u64, data: Date) -> Option<DynamicTag> {
+        self.typed_tag(id, TagDataType::Date, DynamicValue::Date(data))
+    }
+
+    fn get_string_tag(&self, id: u64, data: String) -> Option<DynamicTag> {
+        self.typed_tag(id, TagDataType::String, DynamicValue::String(data))
+    }
+
+    fn get_raw_tag(&self, id: u64, data: &[u8]) -> DynamicTag {
+        DynamicTag::new(id, DynamicValue::Binary(data.to_vec()))
+    }
+
+    fn get_tag_name(&self, id: u64) -> Option<&str> {
+        self.element(id).map(|element| element.name.as_str())
+    }
+
+    fn get_tag_id_by_name(&self, name: &str) -> Option<u64> {
+        self.ids_by_name.get(name).copied()
+    }
+
+    fn get_default(&self, id: u64) -> Option<DynamicTag> {
+        self.elements.get(&id).and_then(|info| info.default.clone()).map(|value| DynamicTag::new(id, value))
+    }
+
+    fn get_children_with_defaults(&self, id: u64) -> &[u64] {
+        self.children_with_defaults.get(&id).map_or(&[], |ids| ids.as_slice())
+    }
+
+    fn get_unsigned_int_range(&self, id: u64) -> Option<ValueRange<u64>> {
+        self.elements.get(&id).and_then(|info| info.unsigned_int_range)
+    }
+
+    fn get_signed_int_range(&self, id: u64) -> Option<ValueRange<i64>> {
+        self.elements.get(&id).and_then(|info| info.signed_int_range)
+    }
+
+    fn get_float_range(&self, id: u64) -> Option<ValueRange<f64>> {
+        self.elements.get(&id).and_then(|info| info.float_range)
+    }
+
+    fn get_length_range(&self, id: u64) -> Option<ValueRange<u64>> {
+        self.elements.get(&id).and_then(|info| info.length_range)
+    }
+
+    fn get_occurs(&self, id: u64) -> Option<Occurs> {
+        self.element(id).map(|element| element.occurs)
+    }
+
+    fn get_mandatory_children(&self, id: u64) -> &[u64] {
+        self.mandatory_children.get(&id).map_or(&[], |ids| ids.as_slice())
+    }
+
+    fn get_tag_ids(&self) -> &[u64] {
+        &self.tag_ids
+    }
+
+    fn get_parent_id(&self, id: u64) -> Option<u64> {
+        self.element(id).and_then(|element| element.parent_id)
+    }
+
+    fn get_global_levels(&self, id: u64) -> Option<GlobalLevels> {
+        self.element(id).map_or_else(|| GlobalLevels::standard(id), |element| element.global)
+    }
+
+    fn is_child(&self, parent: &DynamicTag, id: u64) -> bool {
+        self.is_child_id(parent.id, id)
+    }
+}
+
+impl EbmlTag<DynamicTag> for DynamicTag {
+    fn get_id(&self) -> u64 {
+        self.id
+    }
+
+    fn as_unsigned_int(&self) -> Option<&u64> {
+        match &self.value {
+            DynamicValue::UnsignedInt(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_signed_int(&self) -> Option<&i64> {
+        match &self.value {
+            DynamicValue::Integer(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_utf8(&self) -> Option<&str> {
+        match &self.value {
+            DynamicValue::Utf8(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_binary(&self) -> Option<&[u8]> {
+        match &self.value {
+            DynamicValue::Binary(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_float(&self) -> Option<&f64> {
+        match &self.value {
+            DynamicValue::Float(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_master(&self) -> Option<&Master<DynamicTag>> {
+        match &self.value {
+            DynamicValue::Master(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_date(&self) -> Option<&Date> {
+        match &self.value {
+            DynamicValue::Date(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    fn as_string(&self) -> Option<&str> {
+        match &self.value {
+            DynamicValue::String(value) => Some(value),
+            _ => None,
+        }
+    }
+
+    // The hierarchy is only known to the spec (see `EbmlSpecificationInstance::is_child`), so treat everything as a potential child like an unknown tag
+    fn is_child(&self, _id: u64) -> bool {
+        true
+    }
+}
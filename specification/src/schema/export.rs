@@ -0,0 +1,221 @@
+use std::fmt::{self, Write};
+use std::ops::Bound;
+
+use super::{EbmlSchema, SchemaElement};
+use crate::{EbmlSpecification, EbmlTag, GlobalLevels, TagDataType, ValueRange};
+
+///
+/// Formats floats in the hexadecimal notation used by EBML Schemas (e.g. `0x1.8p+1`).
+///
+#[derive(PartialEq)]
+struct HexFloat(f64);
+
+impl fmt::Display for HexFloat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let value = self.0;
+        if !value.is_finite() {
+            return write!(f, "{}", value);
+        }
+        if value.is_sign_negative() {
+            f.write_char('-')?;
+        }
+
+        let bits = value.abs().to_bits();
+        let biased_exponent = (bits >> 52) as i32;
+        let mantissa = bits & ((1 << 52) - 1);
+        let (leading, exponent) = match (biased_exponent, mantissa) {
+            (0, 0) => return f.write_str("0x0p+0"),
+            (0, _) => (0, -1022),
+            _ => (1, biased_exponent - 1023),
+        };
+
+        write!(f, "0x{}", leading)?;
+        if mantissa != 0 {
+            let digits = format!("{:013x}", mantissa);
+            write!(f, ".{}", digits.trim_end_matches('0'))?;
+        }
+        write!(f, "p{:+}", exponent)
+    }
+}
+
+fn hex_bound(bound: Bound<f64>) -> Bound<HexFloat> {
+    match bound {
+        Bound::Included(value) => Bound::Included(HexFloat(value)),
+        Bound::Excluded(value) => Bound::Excluded(HexFloat(value)),
+        Bound::Unbounded => Bound::Unbounded,
+    }
+}
+
+fn float_range(range: ValueRange<f64>) -> String {
+    ValueRange { min: hex_bound(range.min), max: hex_bound(range.max) }.to_string()
+}
+
+fn data_type_name(data_type: TagDataType) -> &'static str {
+    match data_type {
+        TagDataType::Master => "master",
+        TagDataType::UnsignedInt => "uinteger",
+        TagDataType::Integer => "integer",
+        TagDataType::Float => "float",
+        TagDataType::String => "string",
+        TagDataType::Utf8 => "utf-8",
+        TagDataType::Binary => "binary",
+        TagDataType::Date => "date",
+    }
+}
+
+fn default_text<T>(tag: &T) -> Option<String>
+    where T: EbmlSpecification<T> + EbmlTag<T> + Clone
+{
+    if let Some(value) = tag.as_unsigned_int() {
+        Some(value.to_string())
+    } else if let Some(value) = tag.as_signed_int() {
+        Some(value.to_string())
+    } else if let Some(value) = tag.as_float() {
+        Some(HexFloat(*value).to_string())
+    } else if let Some(value) = tag.as_date() {
+        Some(value.nanos().to_string())
+    } else {
+        tag.as_utf8().or_else(|| tag.as_string()).map(String::from)
+    }
+}
+
+fn element_from_spec<T>(id: u64, depth_limit: usize) -> SchemaElement
+    where T: EbmlSpecification<T> + EbmlTag<T> + Clone
+{
+    let name_of = |id: u64| T::get_tag_name(id).map_or_else(|| format!("Unknown0x{:X}", id), String::from);
+
+    let global = T::get_global_levels(id);
+    let mut names = vec![name_of(id)];
+    // Global elements aren't tied to a parent, so their path only contains the placeholder for their levels
+    let mut ancestor = if global.is_some() { None } else { T::get_parent_id(id) };
+    while let Some(ancestor_id) = ancestor {
+        // Guard against specs with circular parents
+        if names.len() > depth_limit {
+            break;
+        }
+        names.push(name_of(ancestor_id));
+        ancestor = T::get_parent_id(ancestor_id);
+    }
+    let path: String = match global {
+        Some(levels) => format!("{}{}", global_placeholder(levels), names[0]),
+        None => names.iter().rev().map(|name| format!("\\{}", name)).collect(),
+    };
+
+    let data_type = T::get_tag_data_type(id);
+    let range = match data_type {
+        TagDataType::UnsignedInt => T::get_unsigned_int_range(id).map(|range| range.to_string()),
+        TagDataType::Integer => T::get_signed_int_range(id).map(|range| range.to_string()),
+        TagDataType::Float => T::get_float_range(id).map(float_range),
+        _ => None,
+    };
+
+    SchemaElement {
+        name: names.swap_remove(0),
+        path,
+        id,
+        data_type,
+        parent_id: if global.is_some() { None } else { T::get_parent_id(id) },
+        global,
+        recursive: false,
+        default: T::get_default(id).as_ref().and_then(default_text),
+        range,
+        length: T::get_length_range(id).map(|range| range.to_string()),
+        occurs: T::get_occurs(id).unwrap_or_default(),
+        documentation: None,
+    }
+}
+
+fn global_placeholder(levels: GlobalLevels) -> String {
+    let min = if levels.min > 0 { levels.min.to_string() } else { String::new() };
+    let max = levels.max.map_or_else(String::new, |max| max.to_string());
+    format!("\\({}-{}\\)", min, max)
+}
+
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl EbmlSchema {
+
+    ///
+    /// Describes a specification as a schema, using the tags listed by [`EbmlSpecification::get_tag_ids()`].
+    ///
+    /// Element names, ids, types, paths, defaults, ranges, lengths and occurrence constraints are taken from the specification.  "Binary" defaults are not representable in a schema and are left out.
+    ///
+    pub fn from_spec<T>(doc_type: &str, version: u64) -> Self
+        where T: EbmlSpecification<T> + EbmlTag<T> + Clone
+    {
+        let ids = T::get_tag_ids();
+        EbmlSchema {
+            doc_type: doc_type.to_string(),
+            version,
+            elements: ids.iter().map(|&id| element_from_spec::<T>(id, ids.len())).collect(),
+        }
+    }
+
+    ///
+    /// Writes the schema as an [RFC 8794](https://datatracker.ietf.org/doc/rfc8794/) XML document.
+    ///
+    /// Documents written by this method can be read back with [`EbmlSchema::parse()`].
+    ///
+    pub fn to_xml(&self) -> String {
+        let mut xml = String::new();
+        xml.push_str("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str(&format!("<EBMLSchema xmlns=\"urn:ietf:rfc:8794\" docType=\"{}\" version=\"{}\">\n", escape(&self.doc_type), self.version));
+        for element in self.elements.iter() {
+            xml.push_str(&format!("  <element name=\"{}\" path=\"{}\" id=\"0x{:X}\" type=\"{}\"", escape(&element.name), escape(&element.path), element.id, data_type_name(element.data_type)));
+            let optional = [("range", &element.range), ("length", &element.length), ("default", &element.default)];
+            for (attribute, value) in optional.iter() {
+                if let Some(value) = value {
+                    xml.push_str(&format!(" {}=\"{}\"", attribute, escape(value)));
+                }
+            }
+            if element.occurs.min > 0 {
+                xml.push_str(&format!(" minOccurs=\"{}\"", element.occurs.min));
+            }
+            if let Some(max) = element.occurs.max {
+                xml.push_str(&format!(" maxOccurs=\"{}\"", max));
+            }
+            match &element.documentation {
+                Some(documentation) => xml.push_str(&format!(">\n    <documentation lang=\"en\" purpose=\"definition\">{}</documentation>\n  </element>\n", escape(documentation))),
+                None => xml.push_str("/>\n"),
+            }
+        }
+        xml.push_str("</EBMLSchema>\n");
+        xml
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn hex_floats() {
+        assert_eq!("0x0p+0", HexFloat(0.0).to_string());
+        assert_eq!("0x1p+0", HexFloat(1.0).to_string());
+        assert_eq!("0x1.8p+1", HexFloat(3.0).to_string());
+        assert_eq!("-0x1p-3", HexFloat(-0.125).to_string());
+        assert_eq!("0x1.f4p+12", HexFloat(8000.0).to_string());
+        assert_eq!("0x0.0000000000001p-1022", HexFloat(f64::from_bits(1)).to_string());
+        assert_eq!(">0x0p+0,<=0x1p+0", float_range(ValueRange::new(std::ops::Bound::Excluded(0.0), std::ops::Bound::Included(1.0))));
+    }
+
+    #[test]
+    fn global_placeholders() {
+        assert_eq!("\\(-\\)", global_placeholder(GlobalLevels::ANY));
+        assert_eq!("\\(1-\\)", global_placeholder(GlobalLevels::new(1, None)));
+        assert_eq!("\\(2-4\\)", global_placeholder(GlobalLevels::new(2, Some(4))));
+    }
+}
@@ -0,0 +1,244 @@
+//!
+//! Loads [EBML Schemas](https://datatracker.ietf.org/doc/rfc8794/) (RFC 8794 XML documents) at runtime.
+//!
+//! [`EbmlSchema`] is a plain description of the elements declared by a schema file.  It can be turned into a [`DynamicSpec`], which resolves element paths, default values and ranges, and is passed as a value to the iterators and writers in `ebml-iterable` to read and write the generic [`DynamicTag`] type.
+//!
+//! ```
+//! use ebml_iterable_specification::schema::{DynamicSpec, DynamicTag, DynamicValue, EbmlSchema};
+//! use ebml_iterable_specification::EbmlSpecificationInstance;
+//!
+//! let schema = EbmlSchema::parse(r#"
+//!     <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="example" version="1">
+//!         <element name="Info" path="\Info" id="0x1549A966" type="master"/>
+//!         <element name="TimestampScale" path="\Info\TimestampScale" id="0x2AD7B1" type="uinteger" default="1000000" range="not 0"/>
+//!     </EBMLSchema>
+//! "#).unwrap();
+//! let spec = DynamicSpec::new(schema).unwrap();
+//!
+//! assert_eq!(Some("TimestampScale"), spec.get_tag_name(0x2ad7b1));
+//! assert_eq!(Some(DynamicTag::new(0x2ad7b1, DynamicValue::UnsignedInt(1000000))), spec.get_default(0x2ad7b1));
+//! ```
+//!
+
+mod parse;
+mod export;
+mod dynamic;
+
+use std::fmt;
+use std::path::Path;
+
+use super::{GlobalLevels, Occurs, TagDataType};
+
+pub use dynamic::{DynamicSpec, DynamicTag, DynamicValue};
+
+///
+/// Errors that can occur when loading an EBML Schema.
+///
+#[derive(Debug)]
+pub enum SchemaError {
+
+    ///
+    /// The schema file could not be read.
+    ///
+    Io(std::io::Error),
+
+    ///
+    /// The schema is not a well formed XML document.
+    ///
+    Xml(String),
+
+    ///
+    /// The root of the document is not an `EBMLSchema` element.
+    ///
+    NotASchema(String),
+
+    ///
+    /// An element declaration is missing a required attribute.
+    ///
+    MissingAttribute {
+        element: String,
+        attribute: &'static str,
+    },
+
+    ///
+    /// An attribute of an element declaration could not be understood.
+    ///
+    InvalidAttribute {
+        element: String,
+        attribute: &'static str,
+        value: String,
+    },
+
+    ///
+    /// The path of an element refers to a parent that is not declared in the schema.
+    ///
+    UnknownParent {
+        element: String,
+        path: String,
+    },
+
+    ///
+    /// Two elements were declared with the same id.
+    ///
+    DuplicateId(u64),
+}
+
+impl fmt::Display for SchemaError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SchemaError::Io(err) => write!(f, "Could not read schema: {}", err),
+            SchemaError::Xml(err) => write!(f, "Could not parse schema xml: {}", err),
+            SchemaError::NotASchema(root) => write!(f, "Expected an EBMLSchema root element, found '{}'", root),
+            SchemaError::MissingAttribute { element, attribute } => write!(f, "Element '{}' is missing the '{}' attribute", element, attribute),
+            SchemaError::InvalidAttribute { element, attribute, value } => write!(f, "Element '{}' has an invalid '{}' attribute: '{}'", element, attribute, value),
+            SchemaError::UnknownParent { element, path } => write!(f, "Element '{}' has a path ({}) with an undeclared parent", element, path),
+            SchemaError::DuplicateId(id) => write!(f, "Multiple elements are declared with id ({})", id),
+        }
+    }
+}
+
+impl std::error::Error for SchemaError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            SchemaError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for SchemaError {
+    fn from(err: std::io::Error) -> Self {
+        SchemaError::Io(err)
+    }
+}
+
+///
+/// The declaration of a single element in an EBML Schema.
+///
+/// Attribute values that depend on the element type (`default`, `range` and `length`) are kept as the text found in the schema.  They are interpreted when the schema is turned into a [`DynamicSpec`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct SchemaElement {
+
+    ///
+    /// The name of the element.
+    ///
+    pub name: String,
+
+    ///
+    /// The path of the element, as written in the schema (e.g. `\Segment\Info\TimestampScale`).
+    ///
+    pub path: String,
+
+    ///
+    /// The element id.
+    ///
+    pub id: u64,
+
+    ///
+    /// The type of data the element contains.
+    ///
+    pub data_type: TagDataType,
+
+    ///
+    /// The id of the parent element, or `None` for top level and global elements.
+    ///
+    pub parent_id: Option<u64>,
+
+    ///
+    /// The levels at which the element may occur if its path declares it as global (i.e. it may occur within any parent, such as "Void" or "CRC-32"), or `None` for other elements.
+    ///
+    pub global: Option<GlobalLevels>,
+
+    ///
+    /// Whether the element may contain itself.
+    ///
+    pub recursive: bool,
+
+    ///
+    /// The `default` attribute.
+    ///
+    pub default: Option<String>,
+
+    ///
+    /// The `range` attribute.
+    ///
+    pub range: Option<String>,
+
+    ///
+    /// The `length` attribute.
+    ///
+    pub length: Option<String>,
+
+    ///
+    /// The `minOccurs` and `maxOccurs` attributes.
+    ///
+    pub occurs: Occurs,
+
+    ///
+    /// The text of the first `documentation` child of the declaration, if any.
+    ///
+    pub documentation: Option<String>,
+}
+
+///
+/// The contents of an EBML Schema.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct EbmlSchema {
+
+    ///
+    /// The `docType` attribute of the schema.
+    ///
+    pub doc_type: String,
+
+    ///
+    /// The `version` attribute of the schema.
+    ///
+    pub version: u64,
+
+    ///
+    /// All elements declared in the schema, in document order.
+    ///
+    pub elements: Vec<SchemaElement>,
+}
+
+impl EbmlSchema {
+
+    ///
+    /// Parses a schema from the text of an XML document.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns an error if the document is not a valid EBML Schema.
+    ///
+    pub fn parse(xml: &str) -> Result<Self, SchemaError> {
+        parse::parse_schema(xml)
+    }
+
+    ///
+    /// Reads and parses a schema from a file.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns an error if the file can't be read or is not a valid EBML Schema.
+    ///
+    pub fn load<P: AsRef<Path>>(path: P) -> Result<Self, SchemaError> {
+        let xml = std::fs::read_to_string(path)?;
+        Self::parse(&xml)
+    }
+
+    ///
+    /// Finds the declaration of an element by id.
+    ///
+    pub fn element(&self, id: u64) -> Option<&SchemaElement> {
+        self.elements.iter().find(|element| element.id == id)
+    }
+
+    ///
+    /// Finds the declaration of an element by name.
+    ///
+    pub fn element_by_name(&self, name: &str) -> Option<&SchemaElement> {
+        self.elements.iter().find(|element| element.name == name)
+    }
+}
@@ -0,0 +1,416 @@
+use std::collections::{HashMap, HashSet};
+use std::convert::TryFrom;
+use std::ops::Bound;
+
+use super::{EbmlSchema, SchemaElement, SchemaError};
+use super::dynamic::DynamicValue;
+use crate::{Date, GlobalLevels, Occurs, TagDataType, ValueRange};
+
+const ROOT_ELEMENT: &str = "EBMLSchema";
+
+pub(super) fn parse_schema(xml: &str) -> Result<EbmlSchema, SchemaError> {
+    let document = roxmltree::Document::parse(xml).map_err(|err| SchemaError::Xml(err.to_string()))?;
+    let root = document.root_element();
+    if root.tag_name().name() != ROOT_ELEMENT {
+        return Err(SchemaError::NotASchema(root.tag_name().name().to_string()));
+    }
+
+    let doc_type = required(&root, ROOT_ELEMENT, "docType")?.to_string();
+    let version = required(&root, ROOT_ELEMENT, "version")?;
+    let version = parse_unsigned(version).ok_or_else(|| invalid(ROOT_ELEMENT, "version", version))?;
+
+    struct Declaration {
+        element: SchemaElement,
+        parent_path: Option<String>,
+    }
+
+    let mut declarations = Vec::new();
+    let mut ids = HashSet::new();
+    let mut ids_by_path: HashMap<String, u64> = HashMap::new();
+    for node in root.children().filter(|node| node.is_element() && node.tag_name().name() == "element") {
+        let name = required(&node, "element", "name")?;
+        let path = required(&node, name, "path")?;
+        let id = required(&node, name, "id")?;
+        let id = parse_unsigned(id).ok_or_else(|| invalid(name, "id", id))?;
+        if !ids.insert(id) {
+            return Err(SchemaError::DuplicateId(id));
+        }
+        let data_type = required(&node, name, "type")?;
+        let data_type = parse_data_type(data_type).ok_or_else(|| invalid(name, "type", data_type))?;
+        let min = match node.attribute("minOccurs") {
+            Some(min) => parse_unsigned(min).ok_or_else(|| invalid(name, "minOccurs", min))?,
+            None => 0,
+        };
+        let max = match node.attribute("maxOccurs") {
+            Some(max) => Some(parse_unsigned(max).ok_or_else(|| invalid(name, "maxOccurs", max))?),
+            None => None,
+        };
+        let documentation = node.children()
+            .find(|child| child.is_element() && child.tag_name().name() == "documentation")
+            .and_then(|child| child.text())
+            .map(|text| text.trim().to_string());
+
+        let (segments, global, recursive) = split_path(path).ok_or_else(|| invalid(name, "path", path))?;
+        if segments.last().map(|last| last.as_str()) != Some(name) {
+            return Err(invalid(name, "path", path));
+        }
+        let parent_path = if global.is_some() || segments.len() < 2 {
+            None
+        } else {
+            Some(segments[..segments.len() - 1].join("\\"))
+        };
+        ids_by_path.insert(segments.join("\\"), id);
+
+        declarations.push(Declaration {
+            element: SchemaElement {
+                name: name.to_string(),
+                path: path.to_string(),
+                id,
+                data_type,
+                parent_id: None,
+                global,
+                recursive,
+                default: node.attribute("default").map(String::from),
+                range: node.attribute("range").map(String::from),
+                length: node.attribute("length").map(String::from),
+                occurs: Occurs::new(min, max),
+                documentation,
+            },
+            parent_path,
+        });
+    }
+
+    let mut elements = Vec::with_capacity(declarations.len());
+    for mut declaration in declarations {
+        if let Some(parent_path) = declaration.parent_path {
+            match ids_by_path.get(&parent_path) {
+                Some(parent_id) => declaration.element.parent_id = Some(*parent_id),
+                None => return Err(SchemaError::UnknownParent { element: declaration.element.name, path: declaration.element.path }),
+            }
+        }
+        elements.push(declaration.element);
+    }
+
+    Ok(EbmlSchema { doc_type, version, elements })
+}
+
+fn required<'a>(node: &roxmltree::Node<'a, '_>, element: &str, attribute: &'static str) -> Result<&'a str, SchemaError> {
+    node.attribute(attribute).ok_or_else(|| SchemaError::MissingAttribute { element: element.to_string(), attribute })
+}
+
+fn invalid(element: &str, attribute: &'static str, value: &str) -> SchemaError {
+    SchemaError::InvalidAttribute { element: element.to_string(), attribute, value: value.to_string() }
+}
+
+fn parse_data_type(data_type: &str) -> Option<TagDataType> {
+    match data_type {
+        "master" => Some(TagDataType::Master),
+        "uinteger" => Some(TagDataType::UnsignedInt),
+        "integer" => Some(TagDataType::Integer),
+        "float" => Some(TagDataType::Float),
+        "string" => Some(TagDataType::String),
+        "utf-8" => Some(TagDataType::Utf8),
+        "binary" => Some(TagDataType::Binary),
+        "date" => Some(TagDataType::Date),
+        _ => None,
+    }
+}
+
+///
+/// Splits a schema path into the names of its elements, removing global placeholders (e.g. `(1-\)`) and recursion markers (`+`).
+///
+/// Returns the element names along with the levels declared by a global placeholder and whether the last element is recursive, or `None` if a global placeholder is malformed.  The levels of a global placeholder are relative to the elements preceding it, and are converted to absolute levels (so `\Segment\(1-\)Name` allows levels 2 and up).
+///
+pub(super) fn split_path(path: &str) -> Option<(Vec<String>, Option<GlobalLevels>, bool)> {
+    let mut global = None;
+    let mut cleaned = String::with_capacity(path.len());
+    let mut rest = path;
+    while let Some(start) = rest.find('(') {
+        match rest[start..].find("\\)") {
+            Some(end) => {
+                cleaned.push_str(&rest[..start]);
+                let (min, max) = rest[start + 1..start + end].split_once('-')?;
+                let parents = cleaned.split('\\').filter(|segment| !segment.is_empty()).count() as u64;
+                let min = if min.is_empty() { 0 } else { parse_unsigned(min)? };
+                let max = if max.is_empty() { None } else { Some(parse_unsigned(max)?) };
+                global = Some(GlobalLevels::new(parents + min, max.map(|max| parents + max)));
+                rest = &rest[start + end + 2..];
+            },
+            None => break,
+        }
+    }
+    cleaned.push_str(rest);
+
+    let mut recursive = false;
+    let segments = cleaned.split('\\')
+        .filter(|segment| !segment.is_empty())
+        .map(|segment| {
+            recursive = segment.starts_with('+');
+            segment.trim_start_matches('+').to_string()
+        })
+        .collect();
+    Some((segments, global, recursive))
+}
+
+fn parse_unsigned(text: &str) -> Option<u64> {
+    let text = text.trim();
+    match text.strip_prefix("0x").or_else(|| text.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => text.parse().ok(),
+    }
+}
+
+fn parse_signed(text: &str) -> Option<i64> {
+    let text = text.trim();
+    match text.strip_prefix('-') {
+        Some(magnitude) => {
+            let magnitude = parse_unsigned(magnitude)?;
+            if magnitude == 1 << 63 {
+                Some(i64::MIN)
+            } else {
+                i64::try_from(magnitude).ok().map(|value| -value)
+            }
+        },
+        None => parse_unsigned(text).and_then(|value| i64::try_from(value).ok()),
+    }
+}
+
+///
+/// Parses a decimal float or a hexadecimal float in the `0x1.8p+1` notation used by EBML Schemas.
+///
+fn parse_float(text: &str) -> Option<f64> {
+    let text = text.trim();
+    let (negative, unsigned) = match text.strip_prefix('-') {
+        Some(unsigned) => (true, unsigned),
+        None => (false, text.strip_prefix('+').unwrap_or(text)),
+    };
+    let hex = match unsigned.strip_prefix("0x").or_else(|| unsigned.strip_prefix("0X")) {
+        Some(hex) => hex,
+        None => return text.parse().ok(),
+    };
+
+    let (mantissa, exponent) = match hex.find(['p', 'P']) {
+        Some(position) => (&hex[..position], hex[position + 1..].parse::<i32>().ok()?),
+        None => (hex, 0),
+    };
+    let (whole, fraction) = match mantissa.find('.') {
+        Some(position) => (&mantissa[..position], &mantissa[position + 1..]),
+        None => (mantissa, ""),
+    };
+    if whole.is_empty() && fraction.is_empty() {
+        return None;
+    }
+
+    let mut value = 0f64;
+    for digit in whole.chars().chain(fraction.chars()) {
+        value = value * 16.0 + f64::from(digit.to_digit(16)?);
+    }
+    let fraction_len = i32::try_from(fraction.len()).ok()?;
+    let value = value * 2f64.powi(exponent - 4 * fraction_len);
+    Some(if negative { -value } else { value })
+}
+
+///
+/// Parses the `range` syntax of an EBML Schema.
+///
+/// Supported forms are a single value (`"1"`), inclusive intervals (`"1-10"`, `"1-"`), comparisons (`">0"`, `"<= 10"`) and comparisons joined by commas (`">0,<=1"`).  `not_value` handles the `"not x"` form, which can only be represented when it excludes the lower limit of the type.
+///
+fn parse_range<T, P, N>(text: &str, parse: P, not_value: N) -> Option<ValueRange<T>>
+    where T: Copy + PartialOrd, P: Fn(&str) -> Option<T>, N: Fn(T) -> Option<Bound<T>>
+{
+    let mut min = Bound::Unbounded;
+    let mut max = Bound::Unbounded;
+    for part in text.split(',').map(str::trim) {
+        let (part_min, part_max) = if let Some(value) = part.strip_prefix(">=") {
+            (Bound::Included(parse(value)?), Bound::Unbounded)
+        } else if let Some(value) = part.strip_prefix('>') {
+            (Bound::Excluded(parse(value)?), Bound::Unbounded)
+        } else if let Some(value) = part.strip_prefix("<=") {
+            (Bound::Unbounded, Bound::Included(parse(value)?))
+        } else if let Some(value) = part.strip_prefix('<') {
+            (Bound::Unbounded, Bound::Excluded(parse(value)?))
+        } else if let Some(value) = part.strip_prefix("not ") {
+            (not_value(parse(value)?)?, Bound::Unbounded)
+        } else if let Some(value) = parse(part) {
+            (Bound::Included(value), Bound::Included(value))
+        } else {
+            parse_interval(part, &parse)?
+        };
+
+        if !matches!(part_min, Bound::Unbounded) {
+            if !matches!(min, Bound::Unbounded) {
+                return None;
+            }
+            min = part_min;
+        }
+        if !matches!(part_max, Bound::Unbounded) {
+            if !matches!(max, Bound::Unbounded) {
+                return None;
+            }
+            max = part_max;
+        }
+    }
+    Some(ValueRange::new(min, max))
+}
+
+fn parse_interval<T, P>(text: &str, parse: &P) -> Option<(Bound<T>, Bound<T>)>
+    where P: Fn(&str) -> Option<T>
+{
+    // The separator is the first '-' that isn't a sign or part of an exponent
+    text.char_indices()
+        .filter(|(position, c)| *c == '-' && *position > 0 && !matches!(text.as_bytes()[position - 1], b'e' | b'E' | b'p' | b'P'))
+        .find_map(|(position, _)| {
+            let min = parse(&text[..position])?;
+            let max = text[position + 1..].trim();
+            if max.is_empty() {
+                Some((Bound::Included(min), Bound::Unbounded))
+            } else {
+                Some((Bound::Included(min), Bound::Included(parse(max)?)))
+            }
+        })
+}
+
+impl SchemaElement {
+
+    fn invalid(&self, attribute: &'static str, value: &str) -> SchemaError {
+        invalid(&self.name, attribute, value)
+    }
+
+    ///
+    /// Interprets the `range` attribute of an "UnsignedInt" element.
+    ///
+    /// Returns `Ok(None)` if the element is not an "UnsignedInt" element or doesn't declare a range.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns an error if the range can't be parsed.
+    ///
+    pub fn unsigned_int_range(&self) -> Result<Option<ValueRange<u64>>, SchemaError> {
+        match (&self.data_type, &self.range) {
+            (TagDataType::UnsignedInt, Some(range)) => parse_range(range, parse_unsigned, |value| if value == 0 { Some(Bound::Excluded(0)) } else { None })
+                .map(Some)
+                .ok_or_else(|| self.invalid("range", range)),
+            _ => Ok(None),
+        }
+    }
+
+    ///
+    /// Interprets the `range` attribute of an "Integer" element.
+    ///
+    /// Returns `Ok(None)` if the element is not an "Integer" element or doesn't declare a range.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns an error if the range can't be parsed.
+    ///
+    pub fn signed_int_range(&self) -> Result<Option<ValueRange<i64>>, SchemaError> {
+        match (&self.data_type, &self.range) {
+            (TagDataType::Integer, Some(range)) => parse_range(range, parse_signed, |value| if value == i64::MIN { Some(Bound::Excluded(value)) } else { None })
+                .map(Some)
+                .ok_or_else(|| self.invalid("range", range)),
+            _ => Ok(None),
+        }
+    }
+
+    ///
+    /// Interprets the `range` attribute of a "Float" element.
+    ///
+    /// Returns `Ok(None)` if the element is not a "Float" element or doesn't declare a range.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns an error if the range can't be parsed.
+    ///
+    pub fn float_range(&self) -> Result<Option<ValueRange<f64>>, SchemaError> {
+        match (&self.data_type, &self.range) {
+            (TagDataType::Float, Some(range)) => parse_range(range, parse_float, |_| None)
+                .map(Some)
+                .ok_or_else(|| self.invalid("range", range)),
+            _ => Ok(None),
+        }
+    }
+
+    ///
+    /// Interprets the `length` attribute of a "Utf8", "String" or "Binary" element.
+    ///
+    /// Returns `Ok(None)` if the element doesn't contain string or binary data or doesn't declare a length.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns an error if the length can't be parsed.
+    ///
+    pub fn length_range(&self) -> Result<Option<ValueRange<u64>>, SchemaError> {
+        match (&self.data_type, &self.length) {
+            (TagDataType::Utf8 | TagDataType::String | TagDataType::Binary, Some(length)) => parse_range(length, parse_unsigned, |value| if value == 0 { Some(Bound::Excluded(0)) } else { None })
+                .map(Some)
+                .ok_or_else(|| self.invalid("length", length)),
+            _ => Ok(None),
+        }
+    }
+
+    ///
+    /// Interprets the `default` attribute according to the type of the element.  "Date" defaults are read as the number of nanoseconds since 2001-01-01T00:00:00 UTC.
+    ///
+    /// Returns `Ok(None)` if the element doesn't declare a default.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns an error if the default can't be read as the type of the element.  "Master" and "Binary" elements can't declare defaults.
+    ///
+    pub fn default_value(&self) -> Result<Option<DynamicValue>, SchemaError> {
+        let default = match &self.default {
+            Some(default) => default,
+            None => return Ok(None),
+        };
+        let value = match self.data_type {
+            TagDataType::UnsignedInt => parse_unsigned(default).map(DynamicValue::UnsignedInt),
+            TagDataType::Integer => parse_signed(default).map(DynamicValue::Integer),
+            TagDataType::Float => parse_float(default).map(DynamicValue::Float),
+            TagDataType::Date => parse_signed(default).map(|nanos| DynamicValue::Date(Date::from_nanos(nanos))),
+            TagDataType::Utf8 => Some(DynamicValue::Utf8(default.clone())),
+            TagDataType::String if default.bytes().all(|b| (0x20..=0x7e).contains(&b)) => Some(DynamicValue::String(default.clone())),
+            TagDataType::String | TagDataType::Master | TagDataType::Binary => None,
+        };
+        value.map(Some).ok_or_else(|| self.invalid("default", default))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paths() {
+        assert_eq!(Some((vec![String::from("Segment"), String::from("Info")], None, false)), split_path("\\Segment\\Info"));
+        assert_eq!(Some((vec![String::from("Void")], Some(GlobalLevels::ANY), false)), split_path("\\(-\\)Void"));
+        assert_eq!(Some((vec![String::from("CRC-32")], Some(GlobalLevels::new(1, None)), false)), split_path("\\(1-\\)CRC-32"));
+        assert_eq!(Some((vec![String::from("Segment"), String::from("CRC-32")], Some(GlobalLevels::new(2, Some(4))), false)), split_path("\\Segment\\(1-3\\)CRC-32"));
+        assert_eq!(Some((vec![String::from("Tags"), String::from("Tag"), String::from("SimpleTag")], None, true)), split_path("\\Tags\\Tag\\+SimpleTag"));
+        assert_eq!(None, split_path("\\(x-\\)Void"));
+    }
+
+    #[test]
+    fn floats() {
+        assert_eq!(Some(0.0), parse_float("0x0p+0"));
+        assert_eq!(Some(1.0), parse_float("0x1p+0"));
+        assert_eq!(Some(3.0), parse_float("0x1.8p+1"));
+        assert_eq!(Some(-0.125), parse_float("-0x1p-3"));
+        assert_eq!(Some(8000.0), parse_float("0x1.f4p+12"));
+        assert_eq!(Some(2.5), parse_float("2.5"));
+        assert_eq!(None, parse_float("0xp+1"));
+    }
+
+    #[test]
+    fn ranges() {
+        let not_zero = |value| if value == 0 { Some(Bound::Excluded(0)) } else { None };
+        assert_eq!(Some(ValueRange::new(Bound::Excluded(0), Bound::Unbounded)), parse_range("not 0", parse_unsigned, not_zero));
+        assert_eq!(Some(ValueRange::new(Bound::Included(1), Bound::Unbounded)), parse_range("1-", parse_unsigned, not_zero));
+        assert_eq!(Some(ValueRange::new(Bound::Included(0), Bound::Included(3))), parse_range("0-3", parse_unsigned, not_zero));
+        assert_eq!(Some(ValueRange::new(Bound::Included(2), Bound::Included(2))), parse_range("2", parse_unsigned, not_zero));
+        assert_eq!(None, parse_range("not 1", parse_unsigned, not_zero));
+        assert_eq!(Some(ValueRange::new(Bound::Included(-128), Bound::Included(-1))), parse_range("-128--1", parse_signed, |_| None));
+        assert_eq!(Some(ValueRange::new(Bound::Excluded(0.0), Bound::Included(1.0))), parse_range("> 0x0p+0, <=0x1p+0", parse_float, |_| None));
+        assert_eq!(Some(ValueRange::new(Bound::Included(0.0), Bound::Included(0.5))), parse_range("0x0p+0-0x1p-1", parse_float, |_| None));
+        assert_eq!(None, parse_range(">1,>2", parse_unsigned, not_zero));
+    }
+}
@@ -0,0 +1,76 @@
+use std::convert::TryInto;
+use std::fmt::Display;
+
+use crate::tag_iterator_util::empty_element_default;
+
+use super::tools;
+use super::specs::{EbmlSpecificationInstance, EbmlTag, TagDataType, ValueRange};
+use super::errors::tool::ToolError;
+
+fn check_range<T: PartialOrd + Display>(value: T, range: Option<ValueRange<T>>) -> Result<(), ToolError> {
+    match range {
+        Some(range) if !range.contains(&value) => Err(ToolError::ValueOutOfRange { value: value.to_string(), range: range.to_string() }),
+        _ => Ok(()),
+    }
+}
+
+fn check_length(length: usize, range: Option<ValueRange<u64>>) -> Result<(), ToolError> {
+    let length: u64 = length.try_into().expect("couldn't convert usize to u64");
+    match range {
+        Some(range) if !range.contains(&length) => Err(ToolError::LengthOutOfRange { length, range: range.to_string() }),
+        _ => Ok(()),
+    }
+}
+
+///
+/// Checks the raw data of a non-master tag against the range and length constraints of the specification.
+///
+/// Data that can't be read as the tag's data type is not reported here, since that is detected when the tag is decoded.
+///
+pub(crate) fn check_data<TSpec, S>(spec: &S, tag_id: u64, data: &[u8]) -> Result<(), ToolError>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec> + ?Sized
+{
+    if let Some(default) = empty_element_default(spec, tag_id, data) {
+        return check_tag(spec, &default);
+    }
+
+    match spec.get_tag_data_type(tag_id) {
+        TagDataType::UnsignedInt => match tools::arr_to_u64(data) {
+            Ok(value) => check_range(value, spec.get_unsigned_int_range(tag_id)),
+            Err(_) => Ok(()),
+        },
+        TagDataType::Integer => match tools::arr_to_i64(data) {
+            Ok(value) => check_range(value, spec.get_signed_int_range(tag_id)),
+            Err(_) => Ok(()),
+        },
+        TagDataType::Float => match tools::arr_to_f64(data) {
+            Ok(value) => check_range(value, spec.get_float_range(tag_id)),
+            Err(_) => Ok(()),
+        },
+        TagDataType::Utf8 | TagDataType::String => check_length(tools::trim_null_padding(data).len(), spec.get_length_range(tag_id)),
+        TagDataType::Binary => check_length(data.len(), spec.get_length_range(tag_id)),
+        TagDataType::Master | TagDataType::Date => Ok(()),
+    }
+}
+
+///
+/// Checks a non-master tag against the range and length constraints of the specification.
+///
+pub(crate) fn check_tag<TSpec, S>(spec: &S, tag: &TSpec) -> Result<(), ToolError>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec> + ?Sized
+{
+    let tag_id = tag.get_id();
+    if let Some(value) = tag.as_unsigned_int() {
+        check_range(*value, spec.get_unsigned_int_range(tag_id))
+    } else if let Some(value) = tag.as_signed_int() {
+        check_range(*value, spec.get_signed_int_range(tag_id))
+    } else if let Some(value) = tag.as_float() {
+        check_range(*value, spec.get_float_range(tag_id))
+    } else if let Some(value) = tag.as_utf8().or_else(|| tag.as_string()) {
+        check_length(value.len(), spec.get_length_range(tag_id))
+    } else if let Some(data) = tag.as_binary() {
+        check_length(data.len(), spec.get_length_range(tag_id))
+    } else {
+        Ok(())
+    }
+}
@@ -0,0 +1,435 @@
+use std::io::{Read, Write};
+use std::mem;
+
+use crate::tag_iterator::TagIterator;
+use crate::tag_writer::TagWriter;
+use crate::query::TagQuery;
+
+use super::specs::{EbmlSpecification, EbmlTag, Master};
+use super::errors::document::DocumentError;
+use super::errors::query::QueryError;
+use super::errors::tag_iterator::TagIteratorError;
+use super::errors::tag_writer::TagWriterError;
+
+///
+/// Identifies a node within an [`EbmlDocument`].
+///
+/// Ids are only meaningful for the document that created them.  Once a node is removed, its id is never reused.
+///
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct NodeId(usize);
+
+struct Node<TSpec> {
+    tag: TSpec,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+}
+
+///
+/// An in-memory tree of EBML elements that can be navigated, modified, and written back out.
+///
+/// Every element is stored as a node in an arena and addressed using a [`NodeId`].  Nodes know their parent and children, so the tree can be walked in any direction.  "Master" nodes always hold a [`Master::Start`] variant - their content is made up of their child nodes.  When the document is written, the sizes of all "Master" tags are recomputed from their current children.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::EbmlDocument;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let data: &[u8] = &[0x42, 0x86, 0x81, 0x01];
+/// let mut document: EbmlDocument<EmptySpec> = EbmlDocument::read(data)?;
+///
+/// let first = document.roots()[0];
+/// document.insert_after(first, EmptySpec::with_data(0x42f7, &[0x02]));
+///
+/// let mut output = Vec::new();
+/// document.write(&mut output)?;
+/// assert_eq!(vec![0x42, 0x86, 0x81, 0x01, 0x42, 0xf7, 0x81, 0x02], output);
+/// # Ok(())
+/// # }
+/// ```
+///
+/// ## Panics
+///
+/// Methods taking a [`NodeId`] panic if the node has been removed from the document, similar to indexing a [`Vec`] out of bounds.
+///
+pub struct EbmlDocument<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    nodes: Vec<Option<Node<TSpec>>>,
+    roots: Vec<NodeId>,
+}
+
+impl<TSpec> Default for EbmlDocument<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TSpec> EbmlDocument<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new, empty [`EbmlDocument`].
+    ///
+    pub fn new() -> Self {
+        EbmlDocument {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+        }
+    }
+
+    ///
+    /// Reads a complete document from a source implementing [`std::io::Read`].
+    ///
+    /// ## Errors
+    ///
+    /// This method returns the first error encountered while reading the source.  The different possible error states are enumerated in [`TagIteratorError`].
+    ///
+    pub fn read<R: Read>(source: R) -> Result<Self, TagIteratorError> {
+        Self::from_tags(TagIterator::new(source, &[]))
+    }
+
+    ///
+    /// Builds a document from a sequence of tags, such as the output of a [`TagIterator`] or [`TagParser`][`crate::TagParser`].
+    ///
+    /// "Master" tags may be provided either as [`Master::Start`] and [`Master::End`] pairs or as [`Master::Full`] tags.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns the first error in `tags`.  A [`TagIteratorError::CorruptedFileData`] is returned if a [`Master::End`] does not match the currently open tag, or if a [`Master::Start`] is never closed.
+    ///
+    pub fn from_tags<I>(tags: I) -> Result<Self, TagIteratorError>
+        where I: IntoIterator<Item = Result<TSpec, TagIteratorError>>
+    {
+        let mut document = Self::new();
+        let mut open: Vec<NodeId> = Vec::new();
+        for tag in tags {
+            let tag = tag?;
+            match tag.as_master() {
+                Some(Master::Start) => {
+                    let node = document.add_subtree(open.last().copied(), tag);
+                    open.push(node);
+                },
+                Some(Master::End) => {
+                    let tag_id = tag.get_id();
+                    if open.last().map(|node| document.tag(*node).get_id()) != Some(tag_id) {
+                        return Err(TagIteratorError::CorruptedFileData(format!("Unexpected closing tag '{}'", tag_id)));
+                    }
+                    open.pop();
+                },
+                _ => {
+                    document.add_subtree(open.last().copied(), tag);
+                },
+            }
+        }
+
+        if let Some(node) = open.last() {
+            return Err(TagIteratorError::CorruptedFileData(format!("Unclosed tag '{}'", document.tag(*node).get_id())));
+        }
+        Ok(document)
+    }
+
+    ///
+    /// Returns the top level nodes of the document.
+    ///
+    pub fn roots(&self) -> &[NodeId] {
+        &self.roots
+    }
+
+    ///
+    /// Returns the tag stored in a node.
+    ///
+    pub fn tag(&self, node: NodeId) -> &TSpec {
+        &self.node(node).tag
+    }
+
+    ///
+    /// Replaces the tag stored in a node and returns the previous tag.
+    ///
+    /// The new tag must have the same data type as the tag it replaces.  "Master" nodes keep their children unless the new tag is a [`Master::Full`], in which case its children replace them.  Either way, the previous tag of a "Master" node is returned as a [`Master::Start`] - use [`Self::to_tag()`] beforehand to keep its content.  Use [`Self::append()`], [`Self::insert()`] and [`Self::remove()`] to change the content of "Master" nodes otherwise.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns a [`DocumentError::DataTypeMismatch`] if the data type of `tag` differs from the data type of the tag stored in the node.
+    ///
+    pub fn set_tag(&mut self, node: NodeId, tag: TSpec) -> Result<TSpec, DocumentError> {
+        let expected = TSpec::get_tag_data_type(self.tag(node).get_id());
+        let found = TSpec::get_tag_data_type(tag.get_id());
+        if expected != found {
+            return Err(DocumentError::DataTypeMismatch { tag_id: tag.get_id(), expected, found });
+        }
+
+        let tag = match tag.as_master() {
+            Some(Master::Full(children)) => {
+                for child in mem::take(&mut self.node_mut(node).children) {
+                    self.free_subtree(child);
+                }
+                for child in children.clone() {
+                    self.add_subtree(Some(node), child);
+                }
+                Self::master_tag(tag.get_id(), Master::Start)
+            },
+            Some(_) => Self::master_tag(tag.get_id(), Master::Start),
+            None => tag,
+        };
+        Ok(mem::replace(&mut self.node_mut(node).tag, tag))
+    }
+
+    ///
+    /// Returns the parent of a node, or `None` for top level nodes.
+    ///
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.node(node).parent
+    }
+
+    ///
+    /// Returns the children of a node in document order.
+    ///
+    pub fn children(&self, node: NodeId) -> &[NodeId] {
+        &self.node(node).children
+    }
+
+    ///
+    /// Returns the first child of `node` with the specified tag id.
+    ///
+    pub fn find_child(&self, node: NodeId, tag_id: u64) -> Option<NodeId> {
+        self.children(node).iter().copied().find(|child| self.tag(*child).get_id() == tag_id)
+    }
+
+    ///
+    /// Returns an iterator over `node` and all nodes below it in document order.
+    ///
+    pub fn descendants(&self, node: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        self.walk(vec![node])
+    }
+
+    ///
+    /// Returns an iterator over all nodes in the document in document order.
+    ///
+    pub fn iter(&self) -> impl Iterator<Item = NodeId> + '_ {
+        self.walk(self.roots.iter().rev().copied().collect())
+    }
+
+    ///
+    /// Returns all nodes matching a path query such as `"Segment/Cluster/SimpleBlock"`, in document order.
+    ///
+    /// Paths start at the top level nodes of the document.  The query syntax is described on [`TagQuery`].
+    ///
+    /// ## Errors
+    ///
+    /// This method returns a [`QueryError`] if the query cannot be parsed.
+    ///
+    pub fn select(&self, path: &str) -> Result<Vec<NodeId>, QueryError> {
+        let query = TagQuery::parse::<TSpec>(path)?;
+        let mut matches = Vec::new();
+        for root in &self.roots {
+            self.select_within(&query, &mut Vec::new(), *root, &mut matches);
+        }
+        Ok(matches)
+    }
+
+    fn select_within(&self, query: &TagQuery, path: &mut Vec<u64>, node: NodeId, matches: &mut Vec<NodeId>) {
+        path.push(self.tag(node).get_id());
+        if query.matches(path) {
+            matches.push(node);
+        }
+        for child in self.children(node) {
+            self.select_within(query, path, *child, matches);
+        }
+        path.pop();
+    }
+
+    ///
+    /// Adds a tag as the last child of `parent`, or as the last top level node if `parent` is `None`.
+    ///
+    /// [`Master::Full`] tags are expanded into a node for each child.  Returns the id of the new node.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns a [`DocumentError::NotAMaster`] if `parent` is not a "Master" tag.
+    ///
+    pub fn append(&mut self, parent: Option<NodeId>, tag: TSpec) -> Result<NodeId, DocumentError> {
+        let index = self.siblings(parent).len();
+        self.insert(parent, index, tag)
+    }
+
+    ///
+    /// Inserts a tag as a child of `parent` at position `index`, or as a top level node if `parent` is `None`.
+    ///
+    /// [`Master::Full`] tags are expanded into a node for each child.  Returns the id of the new node.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns a [`DocumentError::NotAMaster`] if `parent` is not a "Master" tag.
+    ///
+    /// ## Panics
+    ///
+    /// This method panics if `index` is greater than the number of children of `parent`.
+    ///
+    pub fn insert(&mut self, parent: Option<NodeId>, index: usize, tag: TSpec) -> Result<NodeId, DocumentError> {
+        if let Some(parent) = parent {
+            let parent_tag = self.tag(parent);
+            if parent_tag.as_master().is_none() {
+                return Err(DocumentError::NotAMaster { tag_id: parent_tag.get_id() });
+            }
+        }
+        assert!(index <= self.siblings(parent).len(), "insertion index (is {}) should be <= number of children (is {})", index, self.siblings(parent).len());
+
+        let node = self.add_subtree(parent, tag);
+        let siblings = self.siblings_mut(parent);
+        siblings.pop();
+        siblings.insert(index, node);
+        Ok(node)
+    }
+
+    ///
+    /// Inserts a tag immediately before `sibling`, sharing its parent.  Returns the id of the new node.
+    ///
+    pub fn insert_before(&mut self, sibling: NodeId, tag: TSpec) -> NodeId {
+        let (parent, index) = self.position(sibling);
+        self.insert(parent, index, tag).expect("parent of an existing node is a master")
+    }
+
+    ///
+    /// Inserts a tag immediately after `sibling`, sharing its parent.  Returns the id of the new node.
+    ///
+    pub fn insert_after(&mut self, sibling: NodeId, tag: TSpec) -> NodeId {
+        let (parent, index) = self.position(sibling);
+        self.insert(parent, index + 1, tag).expect("parent of an existing node is a master")
+    }
+
+    ///
+    /// Removes a node and everything below it from the document.
+    ///
+    /// The removed content is returned as a single tag, with "Master" tags converted to [`Master::Full`].
+    ///
+    pub fn remove(&mut self, node: NodeId) -> TSpec {
+        let (parent, index) = self.position(node);
+        self.siblings_mut(parent).remove(index);
+
+        let tag = self.to_tag(node);
+        self.free_subtree(node);
+        tag
+    }
+
+    ///
+    /// Converts a node and everything below it into a single tag, with "Master" tags converted to [`Master::Full`].
+    ///
+    pub fn to_tag(&self, node: NodeId) -> TSpec {
+        let tag = self.tag(node);
+        if tag.as_master().is_some() {
+            let children = self.children(node).iter().map(|child| self.to_tag(*child)).collect();
+            Self::master_tag(tag.get_id(), Master::Full(children))
+        } else {
+            tag.clone()
+        }
+    }
+
+    ///
+    /// Writes the document to a destination implementing [`std::io::Write`].
+    ///
+    /// ## Errors
+    ///
+    /// This method can error if there is a problem writing the tags.  The different possible error states are enumerated in [`TagWriterError`].
+    ///
+    pub fn write<W: Write>(&self, dest: W) -> Result<(), TagWriterError> {
+        let mut writer = TagWriter::new(dest);
+        for root in &self.roots {
+            self.write_node(*root, &mut writer)?;
+        }
+        Ok(())
+    }
+
+    ///
+    /// Writes a node and everything below it using an existing [`TagWriter`].
+    ///
+    /// ## Errors
+    ///
+    /// This method can error if there is a problem writing the tags.  The different possible error states are enumerated in [`TagWriterError`].
+    ///
+    pub fn write_node<W: Write>(&self, node: NodeId, writer: &mut TagWriter<W>) -> Result<(), TagWriterError> {
+        let tag = self.tag(node);
+        if tag.as_master().is_some() {
+            let tag_id = tag.get_id();
+            writer.write(&Self::master_tag(tag_id, Master::Start))?;
+            for child in self.children(node) {
+                self.write_node(*child, writer)?;
+            }
+            writer.write(&Self::master_tag(tag_id, Master::End))
+        } else {
+            writer.write(tag)
+        }
+    }
+
+    fn free_subtree(&mut self, node: NodeId) {
+        let removed: Vec<NodeId> = self.descendants(node).collect();
+        for id in removed {
+            self.nodes[id.0] = None;
+        }
+    }
+
+    fn node(&self, node: NodeId) -> &Node<TSpec> {
+        self.nodes[node.0].as_ref().expect("node was removed from the document")
+    }
+
+    fn node_mut(&mut self, node: NodeId) -> &mut Node<TSpec> {
+        self.nodes[node.0].as_mut().expect("node was removed from the document")
+    }
+
+    fn siblings(&self, parent: Option<NodeId>) -> &[NodeId] {
+        match parent {
+            Some(parent) => self.children(parent),
+            None => &self.roots,
+        }
+    }
+
+    fn siblings_mut(&mut self, parent: Option<NodeId>) -> &mut Vec<NodeId> {
+        match parent {
+            Some(parent) => &mut self.node_mut(parent).children,
+            None => &mut self.roots,
+        }
+    }
+
+    fn position(&self, node: NodeId) -> (Option<NodeId>, usize) {
+        let parent = self.parent(node);
+        let index = self.siblings(parent).iter().position(|sibling| *sibling == node).expect("node is listed by its parent");
+        (parent, index)
+    }
+
+    fn walk(&self, mut stack: Vec<NodeId>) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::from_fn(move || {
+            let node = stack.pop()?;
+            stack.extend(self.children(node).iter().rev().copied());
+            Some(node)
+        })
+    }
+
+    fn master_tag(tag_id: u64, master: Master<TSpec>) -> TSpec {
+        TSpec::get_master_tag(tag_id, master).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id))
+    }
+
+    // Adds `tag` as the last child of `parent`, expanding `Master::Full` tags into child nodes.
+    fn add_subtree(&mut self, parent: Option<NodeId>, tag: TSpec) -> NodeId {
+        let (tag, children) = match tag.as_master() {
+            Some(Master::Full(children)) => (Self::master_tag(tag.get_id(), Master::Start), children.clone()),
+            Some(Master::End) => (Self::master_tag(tag.get_id(), Master::Start), Vec::new()),
+            _ => (tag, Vec::new()),
+        };
+
+        let node = NodeId(self.nodes.len());
+        self.nodes.push(Some(Node { tag, parent, children: Vec::new() }));
+        self.siblings_mut(parent).push(node);
+        for child in children {
+            self.add_subtree(Some(node), child);
+        }
+        node
+    }
+}
@@ -14,6 +14,10 @@ pub mod tool {
         ReadU64Overflow(Vec<u8>),
         ReadI64Overflow(Vec<u8>),
         ReadF64Mismatch(Vec<u8>),
+        ReadDateMismatch(Vec<u8>),
+        InvalidAscii(Vec<u8>),
+        ValueOutOfRange { value: String, range: String },
+        LengthOutOfRange { length: u64, range: String },
         FromUtf8Error(Vec<u8>, FromUtf8Error)
     }
 
@@ -25,6 +29,10 @@ pub mod tool {
                 ToolError::ReadU64Overflow(arr) => write!(f, "Could not read unsigned int from array: {:?}", arr),
                 ToolError::ReadI64Overflow(arr) => write!(f, "Could not read int from array: {:?}", arr),
                 ToolError::ReadF64Mismatch(arr) => write!(f, "Could not read float from array: {:?}", arr),
+                ToolError::ReadDateMismatch(arr) => write!(f, "Could not read date from array: {:?}", arr),
+                ToolError::ValueOutOfRange { value, range } => write!(f, "Value {} is outside of the allowed range: {}", value, range),
+                ToolError::LengthOutOfRange { length, range } => write!(f, "Length {} is outside of the allowed range: {}", length, range),
+                ToolError::InvalidAscii(arr) => write!(f, "Could not read printable ascii string from array: {:?}", arr),
                 ToolError::FromUtf8Error(arr, _source) => write!(f, "Could not read utf8 data: {:?}", arr),
             }
         }
@@ -77,6 +85,24 @@ pub mod tag_iterator {
             problem: ToolError,
         },
 
+        ///
+        /// An error indicating that a tag value violates a constraint of the specification.
+        ///
+        /// This error only occurs when strict mode is enabled on the iterator, and indicates that the value or length of a tag is outside of the range declared by the specification.
+        ///
+        InvalidTagData {
+
+            ///
+            /// The id of the invalid tag.
+            ///
+            tag_id: u64,
+
+            ///
+            /// An error describing the violated constraint.
+            ///
+            problem: ToolError,
+        },
+
         ///
         /// An error that wraps an IO error when reading from the underlying source.
         ///
@@ -87,6 +113,105 @@ pub mod tag_iterator {
             ///
             source: io::Error,
         },
+
+        ///
+        /// An error indicating that a seek operation could not be performed.
+        ///
+        /// This error typically occurs when trying to skip a "Master" tag with an unknown size or when there is no open "Master" tag to skip.
+        ///
+        InvalidSeek(String),
+
+        ///
+        /// An error indicating that a tag is larger than the configured [`TagIteratorLimits::max_tag_size`][`crate::TagIteratorLimits::max_tag_size`].
+        ///
+        TagSizeLimitExceeded {
+
+            ///
+            /// The id of the tag that was too large.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The size of the tag data in bytes.
+            ///
+            size: usize,
+
+            ///
+            /// The configured limit.
+            ///
+            limit: usize,
+        },
+
+        ///
+        /// An error indicating that a tag that should be buffered into a [`Master::Full`][`crate::specs::Master::Full`] is larger than the configured [`TagIteratorLimits::max_buffered_size`][`crate::TagIteratorLimits::max_buffered_size`].
+        ///
+        BufferedSizeLimitExceeded {
+
+            ///
+            /// The id of the tag that was too large.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The size of the tag data in bytes.
+            ///
+            size: usize,
+
+            ///
+            /// The configured limit.
+            ///
+            limit: usize,
+        },
+
+        ///
+        /// An error indicating that "Master" tags are nested deeper than the configured [`TagIteratorLimits::max_depth`][`crate::TagIteratorLimits::max_depth`].
+        ///
+        DepthLimitExceeded {
+
+            ///
+            /// The id of the tag that exceeded the limit.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The configured limit.
+            ///
+            limit: usize,
+        },
+
+        ///
+        /// An error indicating that the source contains more data than the configured [`TagIteratorLimits::max_total_bytes`][`crate::TagIteratorLimits::max_total_bytes`].
+        ///
+        TotalSizeLimitExceeded {
+
+            ///
+            /// The configured limit.
+            ///
+            limit: usize,
+        },
+
+        ///
+        /// An error indicating that corrupted data was skipped so that iteration could continue.
+        ///
+        /// This error is only emitted when error recovery is enabled on the iterator.  The iterator can continue to be used after this error is returned.
+        ///
+        Resynchronized {
+
+            ///
+            /// The error that caused the iterator to resynchronize.
+            ///
+            problem: Box<TagIteratorError>,
+
+            ///
+            /// The offset of the first skipped byte.
+            ///
+            offset: usize,
+
+            ///
+            /// The number of bytes that were skipped to find the next valid tag.
+            ///
+            skipped_bytes: usize,
+        },
     }
     
     impl fmt::Display for TagIteratorError {
@@ -97,7 +222,32 @@ pub mod tag_iterator {
                     tag_id,
                     problem,
                 } => write!(f, "Error reading data for tag id ({}). {}", tag_id, problem),
+                TagIteratorError::InvalidTagData {
+                    tag_id,
+                    problem,
+                } => write!(f, "Invalid data for tag id ({}). {}", tag_id, problem),
                 TagIteratorError::ReadError { source: _ } => write!(f, "Error reading from source."),
+                TagIteratorError::InvalidSeek(message) => write!(f, "Could not seek.  Message: {}", message),
+                TagIteratorError::TagSizeLimitExceeded {
+                    tag_id,
+                    size,
+                    limit,
+                } => write!(f, "Tag id ({}) has a size of {} bytes, which exceeds the limit of {} bytes.", tag_id, size, limit),
+                TagIteratorError::BufferedSizeLimitExceeded {
+                    tag_id,
+                    size,
+                    limit,
+                } => write!(f, "Buffered tag id ({}) has a size of {} bytes, which exceeds the limit of {} bytes.", tag_id, size, limit),
+                TagIteratorError::DepthLimitExceeded {
+                    tag_id,
+                    limit,
+                } => write!(f, "Tag id ({}) exceeds the nesting depth limit of {}.", tag_id, limit),
+                TagIteratorError::TotalSizeLimitExceeded { limit } => write!(f, "Source exceeds the size limit of {} bytes.", limit),
+                TagIteratorError::Resynchronized {
+                    problem,
+                    offset,
+                    skipped_bytes,
+                } => write!(f, "Skipped {} bytes of corrupted data at offset {}. {}", skipped_bytes, offset, problem),
             }
         }
     }
@@ -107,7 +257,14 @@ pub mod tag_iterator {
             match self {
                 TagIteratorError::CorruptedFileData(_) => None,
                 TagIteratorError::CorruptedTagData { tag_id: _, problem } => problem.source(),
+                TagIteratorError::InvalidTagData { tag_id: _, problem } => problem.source(),
                 TagIteratorError::ReadError { source } => Some(source),
+                TagIteratorError::InvalidSeek(_) => None,
+                TagIteratorError::TagSizeLimitExceeded { .. } => None,
+                TagIteratorError::BufferedSizeLimitExceeded { .. } => None,
+                TagIteratorError::DepthLimitExceeded { .. } => None,
+                TagIteratorError::TotalSizeLimitExceeded { .. } => None,
+                TagIteratorError::Resynchronized { problem, offset: _, skipped_bytes: _ } => Some(problem.as_ref()),
             }
         }
     }
@@ -116,6 +273,7 @@ pub mod tag_iterator {
 pub mod tag_writer {
     use super::fmt;
     use super::Error;
+    use super::tool::ToolError;
     use std::io;
 
     ///
@@ -149,6 +307,24 @@ pub mod tag_writer {
             expected_id: Option<u64>,
         },
 
+        ///
+        /// An error indicating that tag data cannot be encoded as its data type or violates a constraint of the specification.
+        ///
+        /// Can occur if a "String" tag contains characters outside of the printable ascii range, or if the value or length of a tag is outside of the range declared by the specification.
+        ///
+        InvalidTagData {
+
+            ///
+            /// The id of the tag being written.
+            ///
+            tag_id: u64,
+
+            ///
+            /// An error describing why the data is invalid.
+            ///
+            problem: ToolError,
+        },
+
         ///
         /// An error that wraps an IO error when writing to the underlying destination.
         ///
@@ -165,6 +341,7 @@ pub mod tag_writer {
                     Some(expected) => write!(f, "Unexpected closing tag '{}'. Expected '{}'", tag_id, expected),
                     None => write!(f, "Unexpected closing tag '{}'", tag_id),
                 },
+                TagWriterError::InvalidTagData { tag_id, problem } => write!(f, "Invalid data for tag id ({}). {}", tag_id, problem),
                 TagWriterError::WriteError { source: _ } => write!(f, "Error writing to destination."),
             }
         }
@@ -175,8 +352,100 @@ pub mod tag_writer {
             match self {
                 TagWriterError::TagSizeError(_) => None,
                 TagWriterError::UnexpectedClosingTag { tag_id: _, expected_id: _ } => None,
+                TagWriterError::InvalidTagData { tag_id: _, problem } => problem.source(),
                 TagWriterError::WriteError { source } => Some(source),
             }
         }
     }
-}
\ No newline at end of file
+}
+
+pub mod document {
+    use super::fmt;
+    use super::Error;
+    use crate::specs::TagDataType;
+
+    ///
+    /// Errors that can occur when modifying an [`EbmlDocument`][`crate::EbmlDocument`].
+    ///
+    #[derive(Debug)]
+    pub enum DocumentError {
+
+        ///
+        /// An error indicating that children were added to a node that isn't a "Master" tag.
+        ///
+        NotAMaster {
+
+            ///
+            /// The id of the tag that children were added to.
+            ///
+            tag_id: u64,
+        },
+
+        ///
+        /// An error indicating that the tag of a node was replaced with a tag of a different data type.
+        ///
+        DataTypeMismatch {
+
+            ///
+            /// The id of the replacement tag.
+            ///
+            tag_id: u64,
+
+            ///
+            /// The data type of the tag being replaced.
+            ///
+            expected: TagDataType,
+
+            ///
+            /// The data type of the replacement tag.
+            ///
+            found: TagDataType,
+        },
+    }
+
+    impl fmt::Display for DocumentError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                DocumentError::NotAMaster { tag_id } => write!(f, "Tag id ({}) is not a master tag and cannot have children.", tag_id),
+                DocumentError::DataTypeMismatch { tag_id, expected, found } => write!(f, "Tag id ({}) has type {:?} and cannot replace a tag of type {:?}.", tag_id, found, expected),
+            }
+        }
+    }
+
+    impl Error for DocumentError {}
+}
+
+pub mod query {
+    use super::fmt;
+    use super::Error;
+
+    ///
+    /// Errors that can occur when parsing a [`TagQuery`][`crate::TagQuery`].
+    ///
+    #[derive(Debug)]
+    pub enum QueryError {
+
+        ///
+        /// An error indicating that the query is not a valid path.
+        ///
+        /// This error typically occurs if the query is empty or contains an empty segment (e.g. `"Segment//Cluster"`).
+        ///
+        InvalidPath(String),
+
+        ///
+        /// An error indicating that a segment of the query does not name a tag in the specification.
+        ///
+        UnknownTag(String),
+    }
+
+    impl fmt::Display for QueryError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                QueryError::InvalidPath(path) => write!(f, "Invalid query path '{}'", path),
+                QueryError::UnknownTag(name) => write!(f, "Unknown tag '{}' in query path", name),
+            }
+        }
+    }
+
+    impl Error for QueryError {}
+}
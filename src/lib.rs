@@ -11,10 +11,16 @@
 //!
 //! # Features
 //!
-//! There is currently only one optional feature in this crate, but that may change over time as needs arise.
+//! The following optional features are available in this crate:
 //!
 //! * **derive-spec** -
-//!     When enabled, this provides the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro to simplify implementation of the [`EbmlSpecification`][`specs::EbmlSpecification`] and [`EbmlTag`][`specs::EbmlTag`] traits.  This introduces dependencies on [`syn`](https://crates.io/crates/syn), [`quote`](https://crates.io/crates/quote), and [`proc-macro2`](https://crates.io/crates/proc-macro2), so expect compile times to increase a little.
+//!   When enabled, this provides the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro to simplify implementation of the [`EbmlSpecification`][`specs::EbmlSpecification`] and [`EbmlTag`][`specs::EbmlTag`] traits.  This introduces dependencies on [`syn`](https://crates.io/crates/syn), [`quote`](https://crates.io/crates/quote), and [`proc-macro2`](https://crates.io/crates/proc-macro2), so expect compile times to increase a little.
+//!
+//! * **chrono** -
+//!   When enabled, [`Date`][`specs::Date`] values can be converted to and from [`chrono`](https://crates.io/crates/chrono) `DateTime<Utc>` values.
+//!
+//! * **schema** -
+//!   When enabled, [EBML Schemas][rfc8794] can be loaded from XML at runtime and used as a specification through the `specs::schema` module.  Together with `"derive-spec"`, it also provides the [`ebml_schema!`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/macro.ebml_schema.html) macro, which generates a specification from an EBML Schema XML file at compile time.  This introduces a dependency on [`roxmltree`](https://crates.io/crates/roxmltree).
 //!
 //! [EBML]: http://ebml.sourceforge.net/
 //! [webm]: https://www.webmproject.org/
@@ -26,14 +32,29 @@
 mod errors;
 mod tag_iterator;
 mod tag_iterator_async;
+mod tag_iterator_slice;
+mod tag_parser;
 mod tag_writer;
 pub mod tools;
 pub mod specs;
 mod tag_iterator_util;
+mod tag_decoder;
+mod visitor;
+mod document;
+mod query;
+mod constraints;
+mod validator;
 
-pub use self::tag_iterator::TagIterator;
-pub use self::tag_iterator_async::TagIteratorAsync;
+pub use self::tag_iterator::{TagIterator, BinaryTagReader};
+pub use self::tag_iterator_async::{TagIteratorAsync, BinaryTagReaderAsync};
+pub use self::tag_iterator_slice::{TagIteratorSlice, BorrowedTag, BorrowedTagData};
+pub use self::tag_parser::TagParser;
 pub use self::tag_writer::TagWriter;
+pub use self::tag_iterator_util::{TagMetadata, TagIteratorLimits, StreamedTag};
+pub use self::visitor::EbmlVisitor;
+pub use self::document::{EbmlDocument, NodeId};
+pub use self::query::{TagQuery, Select};
+pub use self::validator::{OccursValidator, OccursViolation};
 
 pub mod error {
 
@@ -42,6 +63,8 @@ pub mod error {
     //!
     pub use super::errors::tag_iterator::TagIteratorError;
     pub use super::errors::tag_writer::TagWriterError;
+    pub use super::errors::document::DocumentError;
+    pub use super::errors::query::QueryError;
 
     ///
     /// Error details that may be included in some thrown errors
@@ -0,0 +1,209 @@
+use std::collections::VecDeque;
+use std::io::Read;
+
+use crate::tag_iterator::TagIterator;
+
+use super::specs::{EbmlSpecification, EbmlSpecificationInstance, EbmlTag, Master, StaticSpecification};
+use super::errors::query::QueryError;
+use super::errors::tag_iterator::TagIteratorError;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Step {
+    Tag(u64),
+    // `*` - matches exactly one tag with any id
+    AnyTag,
+    // `**` - matches any number of nested tags, including none
+    AnyDepth,
+}
+
+///
+/// A compiled path query that selects tags based on their position in an EBML document.
+///
+/// Queries are written as tag names separated by `/`, starting at the top level of the document (e.g. `"Segment/Cluster/SimpleBlock"`).  Each segment is either:
+///
+/// * the name of a tag, as reported by [`EbmlSpecification::get_tag_name()`],
+/// * a hexadecimal tag id such as `0x1F43B675`, for tags without names,
+/// * `*`, which matches exactly one tag of any kind, or
+/// * `**`, which matches any number of nested tags (including none), so `"**/SimpleBlock"` selects every "SimpleBlock" regardless of depth.
+///
+/// Queries can be run over a stream using [`TagIterator::select()`], against a [`Master::Full`] tree using [`Self::select()`], or against an [`EbmlDocument`][`crate::EbmlDocument`].
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::TagQuery;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let query = TagQuery::parse::<EmptySpec>("0x18538067/**/0xa3").unwrap();
+/// assert!(query.matches(&[0x18538067, 0x1f43b675, 0xa3]));
+/// assert!(!query.matches(&[0x1f43b675, 0xa3]));
+/// ```
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagQuery {
+    steps: Vec<Step>,
+}
+
+impl TagQuery {
+
+    ///
+    /// Parses a query, resolving tag names using the specification `TSpec`.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns a [`QueryError::InvalidPath`] if the query is empty or contains an empty segment, and a [`QueryError::UnknownTag`] if a segment doesn't name a tag in the specification.
+    ///
+    pub fn parse<TSpec>(path: &str) -> Result<Self, QueryError>
+        where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    {
+        Self::parse_with_spec(path, &StaticSpecification::<TSpec>::new())
+    }
+
+    ///
+    /// Parses a query, resolving tag names using the specification instance `spec`.
+    ///
+    /// This behaves exactly like [`Self::parse()`], but works with specifications that are only available at runtime (see [`EbmlSpecificationInstance`]).
+    ///
+    pub fn parse_with_spec<TSpec, S>(path: &str, spec: &S) -> Result<Self, QueryError>
+        where TSpec: EbmlTag<TSpec> + Clone,
+        S: EbmlSpecificationInstance<TSpec> + ?Sized
+    {
+        let trimmed = path.strip_prefix('/').unwrap_or(path);
+        if trimmed.is_empty() {
+            return Err(QueryError::InvalidPath(String::from(path)));
+        }
+
+        let steps = trimmed.split('/').map(|segment| match segment {
+            "" => Err(QueryError::InvalidPath(String::from(path))),
+            "*" => Ok(Step::AnyTag),
+            "**" => Ok(Step::AnyDepth),
+            _ => Self::parse_id(segment)
+                .or_else(|| spec.get_tag_id_by_name(segment))
+                .map(Step::Tag)
+                .ok_or_else(|| QueryError::UnknownTag(String::from(segment))),
+        }).collect::<Result<Vec<Step>, QueryError>>()?;
+
+        Ok(TagQuery { steps })
+    }
+
+    fn parse_id(segment: &str) -> Option<u64> {
+        let hex = segment.strip_prefix("0x").or_else(|| segment.strip_prefix("0X"))?;
+        u64::from_str_radix(hex, 16).ok()
+    }
+
+    ///
+    /// Tests whether a tag matches the query, given the ids of all tags from the top level of the document down to (and including) the tag itself.
+    ///
+    pub fn matches(&self, path: &[u64]) -> bool {
+        Self::matches_steps(&self.steps, path)
+    }
+
+    fn matches_steps(steps: &[Step], path: &[u64]) -> bool {
+        match steps.split_first() {
+            None => path.is_empty(),
+            Some((Step::AnyDepth, rest)) => Self::matches_steps(rest, path) || (!path.is_empty() && Self::matches_steps(steps, &path[1..])),
+            Some((step, rest)) => match path.split_first() {
+                Some((id, remaining)) => (*step == Step::AnyTag || *step == Step::Tag(*id)) && Self::matches_steps(rest, remaining),
+                None => false,
+            },
+        }
+    }
+
+    ///
+    /// Returns all tags within `tag` (including `tag` itself) that match the query, in document order.
+    ///
+    /// `tag` is treated as a top level tag, and the children of [`Master::Full`] tags are searched recursively.
+    ///
+    pub fn select<'a, TSpec>(&self, tag: &'a TSpec) -> Vec<&'a TSpec>
+        where TSpec: EbmlTag<TSpec> + Clone
+    {
+        let mut matches = Vec::new();
+        self.select_within(&mut Vec::new(), tag, &mut matches);
+        matches
+    }
+
+    // Collects matches within `tag`, where `path` holds the ids of the tags enclosing `tag`.
+    fn select_within<'a, TSpec>(&self, path: &mut Vec<u64>, tag: &'a TSpec, matches: &mut Vec<&'a TSpec>)
+        where TSpec: EbmlTag<TSpec> + Clone
+    {
+        path.push(tag.get_id());
+        if self.matches(path) {
+            matches.push(tag);
+        }
+        if let Some(Master::Full(children)) = tag.as_master() {
+            for child in children {
+                self.select_within(path, child, matches);
+            }
+        }
+        path.pop();
+    }
+}
+
+///
+/// An iterator over the tags of a [`TagIterator`] that match a [`TagQuery`].
+///
+/// This is created by [`TagIterator::select()`].  Matching "Master" tags are returned as they are emitted by the underlying iterator - as a [`Master::Start`] unless the tag is configured to be buffered, in which case the [`Master::Full`] tag is returned.  Matches inside buffered tags are returned as well.
+///
+pub struct Select<'a, R: Read, TSpec, S = StaticSpecification<TSpec>>
+    where
+    TSpec: EbmlTag<TSpec> + Clone,
+    S: EbmlSpecificationInstance<TSpec>
+{
+    iter: &'a mut TagIterator<R, TSpec, S>,
+    query: TagQuery,
+    pending: VecDeque<TSpec>,
+}
+
+impl<'a, R: Read, TSpec, S> Select<'a, R, TSpec, S>
+    where
+    TSpec: EbmlTag<TSpec> + Clone,
+    S: EbmlSpecificationInstance<TSpec>
+{
+    pub(crate) fn new(iter: &'a mut TagIterator<R, TSpec, S>, query: TagQuery) -> Self {
+        Select {
+            iter,
+            query,
+            pending: VecDeque::new(),
+        }
+    }
+}
+
+impl<R: Read, TSpec, S> Iterator for Select<'_, R, TSpec, S>
+    where
+    TSpec: EbmlTag<TSpec> + Clone,
+    S: EbmlSpecificationInstance<TSpec>
+{
+    type Item = Result<TSpec, TagIteratorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            if let Some(tag) = self.pending.pop_front() {
+                return Some(Ok(tag));
+            }
+
+            let tag = match self.iter.next()? {
+                Ok(tag) => tag,
+                Err(err) => return Some(Err(err)),
+            };
+            let mut path: Vec<u64> = self.iter.ancestors().map(|(tag, _)| tag.get_id()).collect();
+            match tag.as_master() {
+                // Started tags are already included in the ancestors
+                Some(Master::Start) => if self.query.matches(&path) {
+                    return Some(Ok(tag));
+                },
+                Some(Master::End) => {},
+                Some(Master::Full(_)) => {
+                    let mut matches = Vec::new();
+                    self.query.select_within(&mut path, &tag, &mut matches);
+                    self.pending.extend(matches.into_iter().cloned());
+                },
+                None => {
+                    path.push(tag.get_id());
+                    if self.query.matches(&path) {
+                        return Some(Ok(tag));
+                    }
+                },
+            }
+        }
+    }
+}
@@ -8,8 +8,20 @@
 pub use ebml_iterable_specification_derive::ebml_specification;
 #[cfg(feature = "derive-spec")]
 pub use ebml_iterable_specification_derive::easy_ebml;
+#[cfg(all(feature = "derive-spec", feature = "schema"))]
+pub use ebml_iterable_specification_derive::ebml_schema;
+#[cfg(feature = "schema")]
+pub use ebml_iterable_specification::schema;
 
 pub use ebml_iterable_specification::EbmlSpecification as EbmlSpecification;
 pub use ebml_iterable_specification::EbmlTag as EbmlTag;
+pub use ebml_iterable_specification::EbmlSpecificationInstance as EbmlSpecificationInstance;
+pub use ebml_iterable_specification::StaticSpecification as StaticSpecification;
 pub use ebml_iterable_specification::TagDataType as TagDataType;
 pub use ebml_iterable_specification::Master as Master;
+pub use ebml_iterable_specification::Date as Date;
+pub use ebml_iterable_specification::ValueRange as ValueRange;
+pub use ebml_iterable_specification::Occurs as Occurs;
+pub use ebml_iterable_specification::GlobalLevels as GlobalLevels;
+pub use ebml_iterable_specification::VOID_ID as VOID_ID;
+pub use ebml_iterable_specification::CRC32_ID as CRC32_ID;
@@ -0,0 +1,937 @@
+use std::borrow::Cow;
+use std::collections::HashSet;
+use std::mem;
+use std::ops::Range;
+
+use crate::tag_iterator_util::EBMLSize::{Known, Unknown};
+use crate::tag_iterator_util::{EBMLSize, ProcessingTag, TagIteratorLimits, TagMetadata, empty_element_default, is_known_tag_id, started_tag_count, started_tags};
+use crate::tag_iterator_util::ProcessingTag::{EndTag, NextTag};
+use crate::constraints;
+
+use super::tools;
+use super::specs::{EbmlSpecificationInstance, EbmlTag, Master, TagDataType};
+use super::errors::tag_iterator::TagIteratorError;
+use super::errors::tool::ToolError;
+
+///
+/// The result of a single [`TagDecoder::decode()`] call.
+///
+pub enum Decoded<TSpec> {
+    Tag(TSpec, TagMetadata),
+
+    // A "Binary" tag whose data should be streamed.  The decoder is positioned at the start of the tag data.
+    Binary(u64, TagMetadata),
+
+    // A non-master tag whose undecoded data is available through `TagDecoder::last_data()`.  Only returned when primitive decoding is disabled.
+    Data(u64),
+
+    // More data must be provided using `feed()` or `finish()` before decoding can continue.
+    NeedData,
+
+    // All data has been decoded.
+    Done,
+}
+
+enum DecodeError {
+    // Not enough data is buffered yet.  Any partially decoded tag is discarded and decoded again once more data arrives, except for the children of buffered tags that have already been read.
+    Incomplete,
+    Failed(TagIteratorError),
+}
+
+impl From<TagIteratorError> for DecodeError {
+    fn from(err: TagIteratorError) -> Self {
+        DecodeError::Failed(err)
+    }
+}
+
+type DecodeResult<T> = Result<T, DecodeError>;
+
+pub type TagFilter = Box<dyn Fn(u64, usize) -> bool + Send + Sync>;
+
+// A "Master" tag that is being read into a `Master::Full`.  Its `EndTag` stays on top of the tag stack while its children are read.
+struct BufferedMaster<TSpec> {
+    tag_id: u64,
+    meta: TagMetadata,
+    children: Vec<TSpec>,
+    // Tags that aren't children of the tag below them on the stack replace that tag once they are complete.
+    is_child: bool,
+    previous_data_limit: Option<usize>,
+}
+
+fn end_of_data_error() -> DecodeError {
+    DecodeError::Failed(TagIteratorError::CorruptedFileData(String::from("reached end of file but expecting more data")))
+}
+
+fn unknown_size_primitive_error() -> DecodeError {
+    DecodeError::Failed(TagIteratorError::CorruptedFileData(String::from("Unknown size for primitive not allowed")))
+}
+
+///
+/// The I/O agnostic decoding logic shared by all tag iterators.
+///
+/// Data is provided incrementally using [`Self::feed()`], and [`Self::finish()`] signals that no more data will follow.  Decoding never blocks - when a tag cannot be completed using the buffered data, [`Self::decode()`] returns [`Decoded::NeedData`] and the tag is decoded again from its start after more data has been fed.  Tags that are buffered into a [`Master::Full`] are the exception: the children that have already been read are kept, so decoding resumes with the next child.  Alternatively, a decoder created using [`Self::from_slice()`] decodes a complete byte slice without copying it.
+///
+pub struct TagDecoder<'a, TSpec, S>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec>
+{
+    spec: S,
+    tag_ids_to_buffer: HashSet<u64>,
+    // Buffered tags whose children are still being read, outermost first.
+    buffering: Vec<BufferedMaster<TSpec>>,
+
+    buffer: Cow<'a, [u8]>,
+    buffer_offset: usize,
+    position: usize,
+    end_of_data: bool,
+    // Offset past which data may not be read while decoding the children of a buffered master.
+    data_limit: Option<usize>,
+    tag_stack: Vec<ProcessingTag<TSpec>>,
+
+    binary_stream_threshold: Option<usize>,
+    // A tag whose header has been read but whose data still needs to be returned, because its unknown sized parent had to be ended first.
+    pending_body: Option<(u64, TagMetadata)>,
+    decode_primitives: bool,
+    last_data: Range<usize>,
+    pending_skip: usize,
+    resynchronizing: Option<(usize, TagIteratorError)>,
+    error_recovery: bool,
+    limits: TagIteratorLimits,
+    tag_filter: Option<TagFilter>,
+    fill_defaults: bool,
+    strict_mode: bool,
+}
+
+impl<'a, TSpec, S> TagDecoder<'a, TSpec, S>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec>
+{
+    pub fn new(spec: S, tags_to_buffer: &[TSpec], capacity: usize) -> Self {
+        TagDecoder::with_buffer(spec, tags_to_buffer, Cow::Owned(Vec::with_capacity(capacity)), false)
+    }
+
+    ///
+    /// Returns a decoder over the complete data in `data`.  The data is borrowed rather than copied, so no more data may be fed.
+    ///
+    pub fn from_slice(spec: S, tags_to_buffer: &[TSpec], data: &'a [u8]) -> Self {
+        TagDecoder::with_buffer(spec, tags_to_buffer, Cow::Borrowed(data), true)
+    }
+
+    fn with_buffer(spec: S, tags_to_buffer: &[TSpec], buffer: Cow<'a, [u8]>, end_of_data: bool) -> Self {
+        TagDecoder {
+            spec,
+            tag_ids_to_buffer: tags_to_buffer.iter().map(|tag| tag.get_id()).collect(),
+            buffering: Vec::new(),
+            buffer,
+            buffer_offset: 0,
+            position: 0,
+            end_of_data,
+            data_limit: None,
+            tag_stack: Vec::new(),
+            binary_stream_threshold: None,
+            pending_body: None,
+            decode_primitives: true,
+            last_data: 0..0,
+            pending_skip: 0,
+            resynchronizing: None,
+            error_recovery: false,
+            limits: TagIteratorLimits::default(),
+            tag_filter: None,
+            fill_defaults: false,
+            strict_mode: false,
+        }
+    }
+
+    pub fn spec(&self) -> &S {
+        &self.spec
+    }
+
+    pub fn set_binary_stream_threshold(&mut self, threshold: Option<usize>) {
+        self.binary_stream_threshold = threshold;
+    }
+
+    pub fn set_error_recovery(&mut self, enabled: bool) {
+        self.error_recovery = enabled;
+    }
+
+    pub fn set_limits(&mut self, limits: TagIteratorLimits) {
+        self.limits = limits;
+    }
+
+    ///
+    /// Replaces the tag filter, returning the previous one.
+    ///
+    pub fn set_tag_filter(&mut self, filter: Option<TagFilter>) -> Option<TagFilter> {
+        mem::replace(&mut self.tag_filter, filter)
+    }
+
+    pub fn set_fill_defaults(&mut self, enabled: bool) {
+        self.fill_defaults = enabled;
+    }
+
+    pub fn set_strict_mode(&mut self, enabled: bool) {
+        self.strict_mode = enabled;
+    }
+
+    ///
+    /// Controls whether non-master tags are decoded into `TSpec` values.  When disabled, they are returned as [`Decoded::Data`] and their data can be borrowed using [`Self::last_data()`].
+    ///
+    pub fn set_decode_primitives(&mut self, enabled: bool) -> bool {
+        mem::replace(&mut self.decode_primitives, enabled)
+    }
+
+    ///
+    /// Returns the data of the tag most recently returned as [`Decoded::Data`].
+    ///
+    pub fn last_data(&self) -> &[u8] {
+        &self.buffer[self.last_data.clone()]
+    }
+
+    ///
+    /// Returns the absolute offsets of the data of the tag most recently returned as [`Decoded::Data`].
+    ///
+    pub fn last_data_range(&self) -> Range<usize> {
+        (self.buffer_offset + self.last_data.start)..(self.buffer_offset + self.last_data.end)
+    }
+
+    pub fn ancestors(&self) -> impl Iterator<Item = (&TSpec, TagMetadata)> {
+        started_tags(self.emitted_tag_stack())
+    }
+
+    // The tag stack without the tags that are still being buffered, which haven't been emitted in any form yet.
+    fn emitted_tag_stack(&self) -> &[ProcessingTag<TSpec>] {
+        &self.tag_stack[..(self.tag_stack.len() - self.buffering.len())]
+    }
+
+    ///
+    /// Returns the absolute offset of the next byte to be decoded.
+    ///
+    pub fn offset(&self) -> usize {
+        self.buffer_offset + self.position
+    }
+
+    ///
+    /// Returns the absolute offset immediately following the last byte that was fed.
+    ///
+    pub fn buffered_end(&self) -> usize {
+        self.buffer_offset + self.buffer.len()
+    }
+
+    pub fn feed(&mut self, data: &[u8]) {
+        // Consumed data is only dropped once it makes up at least half of the buffer so that large tags arriving in small chunks don't cause repeated copies.
+        if self.position > 0 && self.position * 2 >= self.buffer.len() {
+            self.buffer.to_mut().drain(..self.position);
+            self.buffer_offset += self.position;
+            self.position = 0;
+        }
+        self.buffer.to_mut().extend_from_slice(data);
+    }
+
+    pub fn finish(&mut self) {
+        self.end_of_data = true;
+    }
+
+    ///
+    /// Copies buffered data into `buf` and returns the number of bytes copied.
+    ///
+    pub fn read_buffered(&mut self, buf: &mut [u8]) -> usize {
+        let length = self.available().min(buf.len());
+        buf[..length].copy_from_slice(&self.buffer[self.position..(self.position + length)]);
+        self.position += length;
+        length
+    }
+
+    ///
+    /// Records that `length` bytes were read directly from the source, bypassing the buffer.  This must only be used while no data is buffered.
+    ///
+    pub fn advance_unbuffered(&mut self, length: usize) {
+        debug_assert_eq!(0, self.available());
+        self.buffer_offset = self.offset() + length;
+        self.buffer.to_mut().clear();
+        self.position = 0;
+    }
+
+    ///
+    /// Skips the next `length` bytes of data before decoding the next tag.
+    ///
+    pub fn skip(&mut self, length: usize) {
+        self.pending_skip += length;
+    }
+
+    ///
+    /// Moves the decoder to the specified offset within the buffered data.  Returns `false` if the offset isn't buffered, in which case the caller should use [`Self::reset_buffer()`] after repositioning the source.
+    ///
+    pub fn reposition(&mut self, offset: usize) -> bool {
+        self.pending_skip = 0;
+        if offset >= self.buffer_offset && offset <= self.buffered_end() {
+            self.position = offset - self.buffer_offset;
+            true
+        } else {
+            false
+        }
+    }
+
+    ///
+    /// Discards all buffered data.  The next data fed is expected to start at `offset`.
+    ///
+    pub fn reset_buffer(&mut self, offset: usize) {
+        self.buffer.to_mut().clear();
+        self.buffer_offset = offset;
+        self.position = 0;
+        self.end_of_data = false;
+        self.pending_skip = 0;
+    }
+
+    ///
+    /// Forgets all open tags, e.g. after seeking to an unrelated position.
+    ///
+    pub fn reset_tags(&mut self) {
+        self.abandon_buffering();
+        self.tag_stack.clear();
+        self.pending_body = None;
+        self.resynchronizing = None;
+    }
+
+    ///
+    /// Forgets the innermost `count` open "Master" tags without emitting their end tags, in the same way that tags being buffered are discarded after an error.
+    ///
+    pub fn abandon_open_tags(&mut self, count: usize) {
+        let emitted = self.emitted_tag_stack().len();
+        self.tag_stack.truncate(emitted.saturating_sub(count));
+    }
+
+    ///
+    /// Forgets the remaining content of the innermost open "Master" tag and returns the offset where that content ends.
+    ///
+    pub fn skip_current_master(&mut self) -> Result<usize, TagIteratorError> {
+        // Queued tags have not been emitted yet, so they are part of the content being skipped.
+        let open_index = started_tag_count(self.emitted_tag_stack()).checked_sub(1)
+            .ok_or_else(|| TagIteratorError::InvalidSeek(String::from("There is no open master tag to skip.")))?;
+
+        let end = match &self.tag_stack[open_index] {
+            EndTag { size: Known(size), start, .. } => start + size,
+            EndTag { size: Unknown, .. } => return Err(TagIteratorError::InvalidSeek(String::from("Cannot skip a master tag with an unknown size."))),
+            NextTag { .. } => return Err(TagIteratorError::InvalidSeek(String::from("There is no open master tag to skip."))),
+        };
+
+        self.abandon_buffering();
+        self.tag_stack.truncate(open_index + 1);
+        self.pending_body = None;
+        Ok(end)
+    }
+
+    ///
+    /// Decodes the next tag from the buffered data.
+    ///
+    /// When `stream_binary` is set, "Binary" tags larger than the configured stream threshold are returned as [`Decoded::Binary`] rather than being read into memory.
+    ///
+    pub fn decode(&mut self, stream_binary: bool) -> Result<Decoded<TSpec>, TagIteratorError> {
+        loop {
+            match self.try_decode(stream_binary) {
+                Ok(Some(decoded)) => return Ok(decoded),
+                // Skipped data doesn't produce a tag, so keep decoding
+                Ok(None) => {},
+                Err(DecodeError::Incomplete) => return Ok(Decoded::NeedData),
+                Err(DecodeError::Failed(err)) => return Err(err),
+            }
+        }
+    }
+
+    fn try_decode(&mut self, stream_binary: bool) -> DecodeResult<Option<Decoded<TSpec>>> {
+        self.skip_pending_payload()?;
+
+        if let Some((tag_start, problem)) = self.resynchronizing.take() {
+            return Err(DecodeError::Failed(self.continue_resynchronization(tag_start, problem)?));
+        }
+
+        if let Some(outermost) = self.buffering.first() {
+            let tag_start = outermost.meta.start;
+            let result = self.read_buffered_masters().map(|(tag, meta)| Some(Decoded::Tag(tag, meta)));
+            return self.recover_if_corrupted(tag_start, result);
+        }
+
+        if let Some((tag_id, meta)) = self.pending_body.take() {
+            let size = meta.data_size.map_or(Unknown, Known);
+            if stream_binary && self.should_stream(tag_id, size) {
+                return Ok(Some(Decoded::Binary(tag_id, meta)));
+            }
+            // The options may have changed since the header was read, so the body is read according to the current ones.
+            let start = self.position;
+            let result = match self.read_body(tag_id, size, meta) {
+                Err(DecodeError::Incomplete) if self.buffering.is_empty() => {
+                    self.position = start;
+                    self.pending_body = Some((tag_id, meta));
+                    return Err(DecodeError::Incomplete);
+                },
+                result => result,
+            };
+            return self.recover_if_corrupted(meta.start, result).map(Some);
+        }
+
+        if let Some(tag) = self.pop_finished_tag() {
+            return Ok(Self::emit(tag));
+        }
+
+        if !self.has_remaining_data()? {
+            // Any tags that are still open end with the data
+            return Ok(match self.tag_stack.pop() {
+                Some(tag) => Self::emit(tag),
+                None => Some(Decoded::Done),
+            });
+        }
+
+        let start = self.position;
+        let result = match self.read_next(stream_binary) {
+            // Buffered tags keep the progress made on their children
+            Err(DecodeError::Incomplete) if !self.buffering.is_empty() => return Err(DecodeError::Incomplete),
+            Err(DecodeError::Incomplete) => {
+                self.position = start;
+                return Err(DecodeError::Incomplete);
+            },
+            result => result,
+        };
+        self.recover_if_corrupted(self.buffer_offset + start, result)
+    }
+
+    fn emit(tag: ProcessingTag<TSpec>) -> Option<Decoded<TSpec>> {
+        if matches!(tag, EndTag { hidden: true, .. }) {
+            None
+        } else {
+            let (tag, meta) = tag.into_inner();
+            Some(Decoded::Tag(tag, meta))
+        }
+    }
+
+    fn read_next(&mut self, stream_binary: bool) -> DecodeResult<Option<Decoded<TSpec>>> {
+        let (tag_id, size, meta) = self.read_tag_header()?;
+
+        // Tags that aren't children of a hidden unknown sized master end that master
+        while matches!(self.tag_stack.last(), Some(EndTag { hidden: true, .. })) && !self.is_child(tag_id) {
+            self.tag_stack.pop();
+        }
+        if self.is_filtered_out(tag_id) {
+            return self.skip_tag(tag_id, size, meta);
+        }
+
+        let streamed = stream_binary && self.should_stream(tag_id, size);
+        let raw = !self.decode_primitives && !matches!(self.spec.get_tag_data_type(tag_id), TagDataType::Master);
+        if (streamed || raw) && !self.is_child(tag_id) {
+            // The tag ends its unknown sized parent - emit the parent end before returning the tag.
+            let (parent, parent_meta) = self.tag_stack.pop().expect("tag stack cannot be empty if tag is not a child").into_inner();
+            self.pending_body = Some((tag_id, meta));
+            return Ok(Some(Decoded::Tag(parent, parent_meta)));
+        }
+
+        if streamed {
+            Ok(Some(Decoded::Binary(tag_id, meta)))
+        } else {
+            self.read_body(tag_id, size, meta).map(Some)
+        }
+    }
+
+    fn should_stream(&self, tag_id: u64, size: EBMLSize) -> bool {
+        match (self.binary_stream_threshold, size) {
+            (Some(threshold), Known(data_size)) => data_size > threshold && matches!(self.spec.get_tag_data_type(tag_id), TagDataType::Binary),
+            _ => false,
+        }
+    }
+
+    fn read_body(&mut self, tag_id: u64, size: EBMLSize, meta: TagMetadata) -> DecodeResult<Decoded<TSpec>> {
+        if !self.decode_primitives && !matches!(self.spec.get_tag_data_type(tag_id), TagDataType::Master) {
+            let size = self.check_primitive_size(tag_id, size)?;
+            self.read_tag_data(size)?;
+            self.last_data = (self.position - size)..self.position;
+            if self.strict_mode {
+                constraints::check_data(&self.spec, tag_id, self.last_data()).map_err(|e| TagIteratorError::InvalidTagData { tag_id, problem: e })?;
+            }
+            Ok(Decoded::Data(tag_id))
+        } else {
+            self.read_tag_body(tag_id, size, meta).map(|(tag, meta)| Decoded::Tag(tag, meta))
+        }
+    }
+
+    fn check_primitive_size(&self, tag_id: u64, size: EBMLSize) -> DecodeResult<usize> {
+        let size = match size {
+            Known(size) => size,
+            Unknown => return Err(unknown_size_primitive_error()),
+        };
+
+        if let Some(limit) = self.limits.max_tag_size {
+            if size > limit {
+                return Err(DecodeError::Failed(TagIteratorError::TagSizeLimitExceeded { tag_id, size, limit }));
+            }
+        }
+        Ok(size)
+    }
+
+    fn is_filtered_out(&self, tag_id: u64) -> bool {
+        // Everything inside a hidden tag is hidden as well
+        if matches!(self.tag_stack.last(), Some(EndTag { hidden: true, .. })) {
+            return true;
+        }
+        match &self.tag_filter {
+            Some(filter) => {
+                // Tags that aren't children replace their parent
+                let depth = self.open_depth() - if self.is_child(tag_id) { 0 } else { 1 };
+                !filter(tag_id, depth)
+            },
+            None => false,
+        }
+    }
+
+    fn skip_tag(&mut self, tag_id: u64, size: EBMLSize, meta: TagMetadata) -> DecodeResult<Option<Decoded<TSpec>>> {
+        let is_master = matches!(self.spec.get_tag_data_type(tag_id), TagDataType::Master);
+        if size == Unknown && !is_master {
+            return Err(unknown_size_primitive_error());
+        }
+
+        // A skipped tag still ends its unknown sized parent
+        let parent = if self.is_child(tag_id) { None } else { self.tag_stack.pop() };
+        match size {
+            Known(size) => self.pending_skip = size,
+            // The end of an unknown sized tag can only be found by reading its children, so it stays on the stack
+            Unknown => self.tag_stack.push(EndTag {
+                tag: self.spec.get_master_tag(tag_id, Master::End).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id)),
+                size,
+                start: self.offset(),
+                meta,
+                hidden: true,
+            }),
+        }
+        Ok(parent.and_then(Self::emit))
+    }
+
+    fn data_end(&self) -> usize {
+        match self.data_limit {
+            Some(limit) => (limit.max(self.buffer_offset) - self.buffer_offset).min(self.buffer.len()),
+            None => self.buffer.len(),
+        }
+    }
+
+    fn available(&self) -> usize {
+        self.data_end().saturating_sub(self.position)
+    }
+
+    fn is_data_complete(&self) -> bool {
+        self.end_of_data || self.data_limit.is_some_and(|limit| self.buffer_offset + self.data_end() >= limit)
+    }
+
+    fn has_remaining_data(&self) -> DecodeResult<bool> {
+        if self.available() > 0 {
+            Ok(true)
+        } else if self.is_data_complete() {
+            Ok(false)
+        } else {
+            Err(DecodeError::Incomplete)
+        }
+    }
+
+    fn open_depth(&self) -> usize {
+        self.tag_stack.iter().filter(|tag| matches!(tag, EndTag { .. })).count()
+    }
+
+    fn read_vint(&mut self, missing: &str) -> DecodeResult<(u64, usize)> {
+        match tools::read_vint(&self.buffer[self.position..self.data_end()]).map_err(|e| TagIteratorError::CorruptedFileData(e.to_string()))? {
+            Some((value, length)) => {
+                self.position += length;
+                Ok((value, length))
+            },
+            None if self.is_data_complete() => Err(DecodeError::Failed(TagIteratorError::CorruptedFileData(String::from(missing)))),
+            None => Err(DecodeError::Incomplete),
+        }
+    }
+
+    fn peek_tag_id(&self) -> DecodeResult<Option<u64>> {
+        match tools::read_vint(&self.buffer[self.position..self.data_end()]) {
+            Ok(Some((value, length))) => Ok(Some(value + (1 << (7 * length)))),
+            Ok(None) if !self.is_data_complete() => Err(DecodeError::Incomplete),
+            _ => Ok(None),
+        }
+    }
+
+    fn read_tag_data(&mut self, size: usize) -> DecodeResult<&[u8]> {
+        let range = self.consume_tag_data(size)?;
+        Ok(&self.buffer[range])
+    }
+
+    // Same as `read_tag_data()`, but returns the position of the data in the buffer so that the rest of `self` can still be borrowed while using it.
+    fn consume_tag_data(&mut self, size: usize) -> DecodeResult<Range<usize>> {
+        if self.available() < size {
+            return if self.is_data_complete() {
+                Err(end_of_data_error())
+            } else {
+                Err(DecodeError::Incomplete)
+            };
+        }
+
+        self.position += size;
+        Ok((self.position - size)..self.position)
+    }
+
+    fn read_tag_header(&mut self) -> DecodeResult<(u64, EBMLSize, TagMetadata)> {
+        let tag_start = self.offset();
+        let (id, id_len) = self.read_vint("Expected tag id, but reached end of source.")?;
+        let tag_id = id + (1 << (7 * id_len));
+        let (size, size_len) = self.read_vint("Expected tag size, but reached end of source.")?;
+        let size = EBMLSize::new(size, size_len);
+        let meta = TagMetadata::new(tag_start, id_len, size_len, size);
+
+        if let Some(limit) = self.limits.max_total_bytes {
+            if meta.end().unwrap_or_else(|| meta.data_start()) > limit {
+                return Err(DecodeError::Failed(TagIteratorError::TotalSizeLimitExceeded { limit }));
+            }
+        }
+
+        if self.error_recovery {
+            if let Some(tag_end) = meta.end() {
+                if !self.fits_open_masters(tag_start, tag_end) {
+                    return Err(DecodeError::Failed(TagIteratorError::CorruptedFileData(String::from("Tag size exceeds the size of its parent."))));
+                }
+            }
+        }
+
+        Ok((tag_id, size, meta))
+    }
+
+    fn fits_open_masters(&self, tag_start: usize, tag_end: usize) -> bool {
+        self.tag_stack.iter().all(|open| match open {
+            // Masters that end before the tag starts are about to be closed, so they don't constrain the tag.
+            EndTag { size: Known(size), start, .. } => start + size <= tag_start || tag_end <= start + size,
+            _ => true,
+        })
+    }
+
+    fn is_plausible_tag_start(&self) -> bool {
+        let data = &self.buffer[self.position..self.data_end()];
+        let (tag_id, id_len) = match tools::read_vint(data) {
+            Ok(Some((value, length))) => (value + (1 << (7 * length)), length),
+            _ => return false,
+        };
+        if !is_known_tag_id(&self.spec, tag_id) {
+            return false;
+        }
+
+        let (size, size_len) = match tools::read_vint(&data[id_len..]) {
+            Ok(Some((value, length))) => (EBMLSize::new(value, length), length),
+            _ => return false,
+        };
+        let tag_start = self.offset();
+        if let Known(size) = size {
+            if !self.fits_open_masters(tag_start, tag_start + id_len + size_len + size) {
+                return false;
+            }
+        }
+
+        // The innermost open master must accept the tag as a child.  Tags that aren't children of an unknown sized master end that master, so the next level up is checked instead.
+        let mut level = self.open_depth();
+        self.tag_stack.iter().rev().find_map(|open| match open {
+            EndTag { size: Known(size), start, .. } if start + size <= tag_start => {
+                level -= 1;
+                None
+            },
+            EndTag { size: Unknown, tag, .. } => {
+                let accepted = self.accepts_child(tag, tag_id, level);
+                level -= 1;
+                if accepted { Some(true) } else { None }
+            },
+            EndTag { tag, .. } => Some(self.accepts_child(tag, tag_id, level)),
+            NextTag { .. } => None,
+        }).unwrap_or_else(|| self.spec.get_global_levels(tag_id).map_or(true, |levels| levels.contains(0)))
+    }
+
+    fn continue_resynchronization(&mut self, tag_start: usize, problem: TagIteratorError) -> DecodeResult<TagIteratorError> {
+        loop {
+            // A tag header is at most 16 bytes long (8 for the id and 8 for the size)
+            if self.available() < 16 && !self.is_data_complete() {
+                self.resynchronizing = Some((tag_start, problem));
+                return Err(DecodeError::Incomplete);
+            }
+            if self.available() == 0 || self.is_plausible_tag_start() {
+                break;
+            }
+            self.position += 1;
+        }
+
+        Ok(TagIteratorError::Resynchronized {
+            problem: Box::new(problem),
+            offset: tag_start,
+            skipped_bytes: self.offset() - tag_start,
+        })
+    }
+
+    fn recover_if_corrupted<T>(&mut self, tag_start: usize, result: DecodeResult<T>) -> DecodeResult<T> {
+        match result {
+            Err(DecodeError::Failed(problem @ TagIteratorError::CorruptedFileData(_))) if self.error_recovery => {
+                // Resume scanning right after the start of the corrupted tag if those bytes are still buffered
+                let scan_start = (tag_start + 1).max(self.buffer_offset);
+                if scan_start <= self.buffered_end() {
+                    self.position = scan_start - self.buffer_offset;
+                }
+                self.pending_body = None;
+                self.pending_skip = 0;
+                Err(DecodeError::Failed(self.continue_resynchronization(tag_start, problem)?))
+            },
+            result => result,
+        }
+    }
+
+    fn is_child(&self, tag_id: u64) -> bool {
+        self.tag_stack.last().map(|it| {
+            match it {
+                NextTag {..} => true,
+                EndTag { size, tag: parent, .. } => {
+                    // The unknown check is there to still support proper parsing of badly formatted files.
+                    *size != Unknown || self.accepts_child(parent, tag_id, self.open_depth())
+                }
+            }
+        }).unwrap_or(true)
+    }
+
+    // Global tags are accepted by any parent whose children are at one of their allowed levels
+    fn accepts_child(&self, parent: &TSpec, tag_id: u64, level: usize) -> bool {
+        match self.spec.get_global_levels(tag_id) {
+            Some(levels) => levels.contains(level as u64),
+            None => self.spec.is_child(parent, tag_id),
+        }
+    }
+
+    fn read_tag_body(&mut self, tag_id: u64, size: EBMLSize, meta: TagMetadata) -> DecodeResult<(TSpec, TagMetadata)> {
+        let is_master = matches!(self.spec.get_tag_data_type(tag_id), TagDataType::Master);
+        let is_child = self.is_child(tag_id);
+        if is_master {
+            // Tags that aren't children replace their parent, so they don't increase the depth
+            self.check_depth(tag_id, self.open_depth() + if is_child { 1 } else { 0 })?;
+        }
+
+        let tag = if is_master && !self.tag_ids_to_buffer.contains(&tag_id) {
+            let end_tag = EndTag {
+                tag: self.spec.get_master_tag(tag_id, Master::End).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id)),
+                size,
+                start: self.offset(),
+                meta,
+                hidden: false,
+            };
+            let start_tag = self.spec.get_master_tag(tag_id, Master::Start).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id));
+            return if is_child {
+                self.tag_stack.push(end_tag);
+                Ok((start_tag, meta))
+            } else {
+                let tag = mem::replace(self.tag_stack.last_mut().unwrap(), end_tag).into_inner();
+                self.tag_stack.push(NextTag { tag: start_tag, meta });
+                Ok(tag)
+            };
+        } else if is_master {
+            self.start_buffered_master(tag_id, size, meta, is_child)?;
+            return self.read_buffered_masters();
+        } else {
+            self.read_primitive(tag_id, size)?
+        };
+
+        if is_child {
+            Ok((tag, meta))
+        } else {
+            Ok(mem::replace(self.tag_stack.last_mut().unwrap(), NextTag { tag, meta }).into_inner())
+        }
+    }
+
+    fn check_depth(&self, tag_id: u64, depth: usize) -> DecodeResult<()> {
+        if let Some(limit) = self.limits.max_depth {
+            if depth > limit {
+                return Err(DecodeError::Failed(TagIteratorError::DepthLimitExceeded { tag_id, limit }));
+            }
+        }
+        Ok(())
+    }
+
+    fn read_primitive(&mut self, tag_id: u64, size: EBMLSize) -> DecodeResult<TSpec> {
+        let size = self.check_primitive_size(tag_id, size)?;
+        let data_range = self.consume_tag_data(size)?;
+        let raw_data = &self.buffer[data_range];
+        if self.strict_mode {
+            constraints::check_data(&self.spec, tag_id, raw_data).map_err(|e| TagIteratorError::InvalidTagData { tag_id, problem: e })?;
+        }
+        if let Some(default) = empty_element_default(&self.spec, tag_id, raw_data) {
+            return Ok(default);
+        }
+        Ok(match self.spec.get_tag_data_type(tag_id) {
+            TagDataType::Master => { unreachable!("Master should have been handled before querying data") },
+            TagDataType::UnsignedInt => {
+                let val = tools::arr_to_u64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+                self.spec.get_unsigned_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", tag_id))
+            },
+            TagDataType::Integer => {
+                let val = tools::arr_to_i64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+                self.spec.get_signed_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", tag_id))
+            },
+            TagDataType::Utf8 => {
+                let text = tools::trim_null_padding(raw_data);
+                let val = String::from_utf8(text.to_vec()).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: ToolError::FromUtf8Error(text.to_vec(), e) })?;
+                self.spec.get_utf8_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", tag_id))
+            },
+            TagDataType::String => {
+                let val = tools::arr_to_ascii(tools::trim_null_padding(raw_data)).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+                self.spec.get_string_tag(tag_id, val.to_string()).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was string, but could not get tag!", tag_id))
+            },
+            TagDataType::Binary => {
+                self.spec.get_binary_tag(tag_id, raw_data).unwrap_or_else(|| self.spec.get_raw_tag(tag_id, raw_data))
+            },
+            TagDataType::Float => {
+                let val = tools::arr_to_f64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+                self.spec.get_float_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", tag_id))
+            },
+            TagDataType::Date => {
+                let val = tools::arr_to_date(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+                self.spec.get_date_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was date, but could not get tag!", tag_id))
+            },
+        })
+    }
+
+    fn start_buffered_master(&mut self, tag_id: u64, size: EBMLSize, meta: TagMetadata, is_child: bool) -> DecodeResult<()> {
+        if let Known(size) = size {
+            if let Some(limit) = self.limits.max_buffered_size {
+                if size > limit {
+                    return Err(DecodeError::Failed(TagIteratorError::BufferedSizeLimitExceeded { tag_id, size, limit }));
+                }
+            }
+            // Known sized tags are only decoded once all of their data is available
+            if self.available() < size {
+                return Err(if self.is_data_complete() { end_of_data_error() } else { DecodeError::Incomplete });
+            }
+        }
+
+        let end_tag = self.spec.get_master_tag(tag_id, Master::End).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id));
+
+        // The tag stays on the stack while its children are read so that nesting checks behave the same as for unbuffered tags.
+        let previous_data_limit = self.data_limit;
+        if let Known(size) = size {
+            self.data_limit = Some(self.offset() + size);
+        }
+        self.tag_stack.push(EndTag { tag: end_tag, size, start: self.offset(), meta, hidden: false });
+        self.buffering.push(BufferedMaster { tag_id, meta, children: Vec::new(), is_child, previous_data_limit });
+        Ok(())
+    }
+
+    // Reads children into the open buffered tags until the outermost one is complete.  Any failure discards all of them.
+    fn read_buffered_masters(&mut self) -> DecodeResult<(TSpec, TagMetadata)> {
+        let result = self.read_buffered_children();
+        if let Err(DecodeError::Failed(_)) = result {
+            self.abandon_buffering();
+        }
+        result
+    }
+
+    fn read_buffered_children(&mut self) -> DecodeResult<(TSpec, TagMetadata)> {
+        loop {
+            // An unknown sized tag ends when its parent ends, at the end of the data, or when a tag is found that isn't one of its children.
+            let mut ended = self.open_master_ended() || !self.has_remaining_data()?;
+            if !ended {
+                if let Some(child_id) = self.peek_tag_id()? {
+                    ended = !self.is_child(child_id);
+                }
+            }
+
+            let child = if ended {
+                let (tag, meta) = self.finish_buffered_master();
+                if self.buffering.is_empty() {
+                    return Ok((tag, meta));
+                }
+                tag
+            } else {
+                // Only the child being read is decoded again once more data arrives
+                let child_start = self.position;
+                match self.read_buffered_child() {
+                    Ok(Some(child)) => child,
+                    Ok(None) => continue,
+                    Err(DecodeError::Incomplete) => {
+                        self.position = child_start;
+                        return Err(DecodeError::Incomplete);
+                    },
+                    Err(err) => return Err(err),
+                }
+            };
+
+            let buffered = self.buffering.last_mut().expect("a buffered tag must be open");
+            buffered.children.push(child);
+            if let Some(limit) = self.limits.max_buffered_size {
+                let (tag_id, data_start) = (buffered.tag_id, buffered.meta.data_start());
+                let size = self.offset() - data_start;
+                if size > limit {
+                    return Err(DecodeError::Failed(TagIteratorError::BufferedSizeLimitExceeded { tag_id, size, limit }));
+                }
+            }
+        }
+    }
+
+    // Reads the next child of the innermost buffered tag.  "Master" children are buffered as well, so they don't produce a tag until they are complete.
+    fn read_buffered_child(&mut self) -> DecodeResult<Option<TSpec>> {
+        let (tag_id, size, meta) = self.read_tag_header()?;
+        if matches!(self.spec.get_tag_data_type(tag_id), TagDataType::Master) {
+            self.check_depth(tag_id, self.open_depth() + 1)?;
+            self.start_buffered_master(tag_id, size, meta, true)?;
+            Ok(None)
+        } else {
+            self.read_primitive(tag_id, size).map(Some)
+        }
+    }
+
+    fn finish_buffered_master(&mut self) -> (TSpec, TagMetadata) {
+        let BufferedMaster { tag_id, meta, mut children, is_child, previous_data_limit } = self.buffering.pop().expect("a buffered tag must be open");
+        self.tag_stack.pop();
+        self.data_limit = previous_data_limit;
+
+        if self.fill_defaults {
+            self.add_missing_defaults(tag_id, &mut children);
+        }
+        let tag = self.spec.get_master_tag(tag_id, Master::Full(children)).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id));
+
+        if is_child {
+            (tag, meta)
+        } else {
+            mem::replace(self.tag_stack.last_mut().unwrap(), NextTag { tag, meta }).into_inner()
+        }
+    }
+
+    fn abandon_buffering(&mut self) {
+        if let Some(outermost) = self.buffering.first() {
+            self.data_limit = outermost.previous_data_limit;
+        }
+        let emitted = self.emitted_tag_stack().len();
+        self.tag_stack.truncate(emitted);
+        self.buffering.clear();
+    }
+
+    fn add_missing_defaults(&self, tag_id: u64, children: &mut Vec<TSpec>) {
+        for &child_id in self.spec.get_children_with_defaults(tag_id) {
+            if !children.iter().any(|child| child.get_id() == child_id) {
+                children.push(self.spec.get_default(child_id).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} was listed with a default, but could not get tag!", child_id)));
+            }
+        }
+    }
+
+    fn open_master_ended(&self) -> bool {
+        let offset = self.offset();
+        self.tag_stack.iter().any(|open| matches!(open, EndTag { size: Known(size), start, .. } if start + size <= offset))
+    }
+
+    fn pop_finished_tag(&mut self) -> Option<ProcessingTag<TSpec>> {
+        let finished = match self.tag_stack.last()? {
+            EndTag { size: Known(size), start, .. } => self.offset() >= start + size,
+            // Unknown sized tags end with their parent
+            EndTag { size: Unknown, .. } => self.open_master_ended(),
+            NextTag { .. } => true,
+        };
+        if finished {
+            self.tag_stack.pop()
+        } else {
+            None
+        }
+    }
+
+    fn skip_pending_payload(&mut self) -> DecodeResult<()> {
+        let length = self.available().min(self.pending_skip);
+        self.position += length;
+        self.pending_skip -= length;
+        if self.pending_skip > 0 {
+            return Err(if self.is_data_complete() { end_of_data_error() } else { DecodeError::Incomplete });
+        }
+        Ok(())
+    }
+}
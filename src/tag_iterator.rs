@@ -1,15 +1,12 @@
-use std::io::{Cursor, Read};
-use std::convert::TryInto;
-use std::collections::HashSet;
-use std::mem;
-use crate::tag_iterator_util::EBMLSize::{Known, Unknown};
-use crate::tag_iterator_util::{DEFAULT_BUFFER_LEN, EBMLSize, ProcessingTag};
-use crate::tag_iterator_util::ProcessingTag::{EndTag, NextTag};
-
-use super::tools;
-use super::specs::{EbmlSpecification, EbmlTag, Master, TagDataType};
+use std::io::{self, Read, Seek, SeekFrom};
+use crate::tag_decoder::{Decoded, TagDecoder};
+use crate::tag_iterator_util::{DEFAULT_BUFFER_LEN, StreamedTag, TagIteratorLimits, TagMetadata};
+use crate::visitor::{self, EbmlVisitor};
+use crate::query::{Select, TagQuery};
+
+use super::specs::{EbmlSpecification, EbmlSpecificationInstance, EbmlTag, StaticSpecification};
 use super::errors::tag_iterator::TagIteratorError;
-use super::errors::tool::ToolError;
+use super::errors::query::QueryError;
 
 ///
 /// Provides an iterator over EBML files (read from a source implementing the [`std::io::Read`] trait). Can be configured to read specific "Master" tags as complete objects rather than just emitting when they start and end.
@@ -45,23 +42,21 @@ use super::errors::tool::ToolError;
 ///
 /// The iterator can panic if `<TSpec>` is an internally inconsistent specification (i.e. it claims that a specific tag id has a specific data type but fails to produce a tag variant using data of that type).  This won't happen if the specification being used was created using the [`#[ebml_specification]`](https://docs.rs/ebml-iterable-specification-derive/latest/ebml_iterable_specification_derive/attr.ebml_specification.html) attribute macro.
 ///
-
-pub struct TagIterator<R: Read, TSpec>
+/// ## Specification Instances
+///
+/// The `S` parameter is the specification instance the iterator reads tags with.  It defaults to [`StaticSpecification<TSpec>`], which uses the [`EbmlSpecification`] implementation of `TSpec`.  Iterators using any other [`EbmlSpecificationInstance`] (such as a specification loaded at runtime) are created using [`Self::with_spec()`].
+///
+pub struct TagIterator<R: Read, TSpec, S = StaticSpecification<TSpec>>
     where
-    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    TSpec: EbmlTag<TSpec> + Clone,
+    S: EbmlSpecificationInstance<TSpec>
 {
     source: R,
-    tag_ids_to_buffer: HashSet<u64>,
-    buffer_all: bool,
-
-    buffer: Box<[u8]>,
-    buffer_offset: Option<usize>,
-    buffered_byte_length: usize,
-    internal_buffer_position: usize,
-    tag_stack: Vec<ProcessingTag<TSpec>>,
+    read_buffer: Box<[u8]>,
+    decoder: TagDecoder<'static, TSpec, S>,
 }
 
-impl<'a, R: Read, TSpec> TagIterator<R, TSpec>
+impl<R: Read, TSpec> TagIterator<R, TSpec>
     where
     TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
 {
@@ -71,6 +66,8 @@ impl<'a, R: Read, TSpec> TagIterator<R, TSpec>
     ///
     /// The `source` parameter must implement [`std::io::Read`].  The second argument, `tags_to_buffer`, specifies which "Master" tags should be read as [`Master::Full`]s rather than as [`Master::Start`] and [`Master::End`]s.  Refer to the documentation on [`TagIterator`] for more explanation of how to use the returned instance.
     ///
+    /// Buffered tags may have an unknown size (as is common in live streams).  Such a tag is considered complete when its parent ends, when the source ends, or when a tag is found that the specification does not list as one of its children.
+    ///
     pub fn new(source: R, tags_to_buffer: &[TSpec]) -> Self {
         TagIterator::with_capacity(source, tags_to_buffer, DEFAULT_BUFFER_LEN)
     }
@@ -81,212 +78,374 @@ impl<'a, R: Read, TSpec> TagIterator<R, TSpec>
     /// This initializes the [`TagIterator`] with a specific byte capacity.  The iterator will still reallocate if necessary. (Reallocation occurs if the iterator comes across a tag that should be output as a [`Master::Full`] and its size in bytes is greater than the iterator's current buffer capacity.)
     ///
     pub fn with_capacity(source: R, tags_to_buffer: &[TSpec], capacity: usize) -> Self {
+        TagIterator::with_spec_and_capacity(source, StaticSpecification::new(), tags_to_buffer, capacity)
+    }
+}
+
+impl<R: Read, TSpec, S> TagIterator<R, TSpec, S>
+    where
+    TSpec: EbmlTag<TSpec> + Clone,
+    S: EbmlSpecificationInstance<TSpec>
+{
+
+    ///
+    /// Returns a new [`TagIterator`] instance that reads tags using the specification instance `spec`.
+    ///
+    /// This behaves exactly like [`TagIterator::new()`], except that tag data types, names, defaults and constraints are looked up through `spec` rather than through the [`EbmlSpecification`] implementation of `TSpec`.  This allows reading with specifications that are only known at runtime, and with several different specifications for the same tag type at the same time.
+    ///
+    pub fn with_spec(source: R, spec: S, tags_to_buffer: &[TSpec]) -> Self {
+        TagIterator::with_spec_and_capacity(source, spec, tags_to_buffer, DEFAULT_BUFFER_LEN)
+    }
+
+    ///
+    /// Returns a new [`TagIterator`] instance that reads tags using the specification instance `spec`, with the specified internal buffer capacity.
+    ///
+    /// Refer to [`TagIterator::with_capacity()`] for details on the buffer capacity.
+    ///
+    pub fn with_spec_and_capacity(source: R, spec: S, tags_to_buffer: &[TSpec], capacity: usize) -> Self {
         let buffer = vec![0;capacity];
 
         TagIterator {
             source,
-            tag_ids_to_buffer: tags_to_buffer.iter().map(|tag| tag.get_id()).collect(),
-            buffer_all: false,
-            buffer: buffer.into_boxed_slice(),
-            buffered_byte_length: 0,
-            buffer_offset: None,
-            internal_buffer_position: 0,
-            tag_stack: Vec::new(),
+            read_buffer: buffer.into_boxed_slice(),
+            decoder: TagDecoder::new(spec, tags_to_buffer, capacity),
         }
     }
 
-    fn current_offset(&self) -> usize {
-        self.buffer_offset.unwrap_or(0) + self.internal_buffer_position
+    ///
+    /// Returns the specification instance used by the iterator.
+    ///
+    pub fn spec(&self) -> &S {
+        self.decoder.spec()
+    }
+
+    ///
+    /// Configures the size above which "Binary" tags are streamed rather than buffered.
+    ///
+    /// When a threshold is set, [`Self::next_streamed()`] emits any "Binary" tag whose data is larger than `threshold` bytes as a [`StreamedTag::Binary`] handle that reads the tag data directly from the source instead of buffering it into memory.  Passing `None` (the default) disables streaming.  This setting has no effect on the regular [`Iterator`] implementation, which always buffers tag data.
+    ///
+    pub fn set_binary_stream_threshold(&mut self, threshold: Option<usize>) {
+        self.decoder.set_binary_stream_threshold(threshold);
+    }
+
+    ///
+    /// Enables or disables recovery from corrupted data.
+    ///
+    /// By default, the state of the iterator is undefined after it returns a [`TagIteratorError::CorruptedFileData`] error.  With error recovery enabled, the iterator instead scans forward byte-by-byte for the next tag id that is defined by the specification and fits in the currently open "Master" tags.  The corruption is reported as a [`TagIteratorError::Resynchronized`] error containing the original problem and the number of bytes that were skipped, and iteration can continue normally afterwards.
+    ///
+    /// Enabling error recovery also makes the iterator report tags whose size exceeds the size of their parent as corrupted, since that typically indicates a damaged tag header.
+    ///
+    pub fn set_error_recovery(&mut self, enabled: bool) {
+        self.decoder.set_error_recovery(enabled);
+    }
+
+    ///
+    /// Configures resource limits for the iterator.
+    ///
+    /// No limits are enforced by default.  Setting limits is strongly recommended when reading untrusted input, since corrupted or malicious tag sizes could otherwise cause huge allocations or unbounded nesting.  Refer to [`TagIteratorLimits`] for the available limits.
+    ///
+    pub fn set_limits(&mut self, limits: TagIteratorLimits) {
+        self.decoder.set_limits(limits);
     }
 
-    fn private_read(&mut self, internal_buffer_start: usize) -> Result<bool, TagIteratorError> {
-        let bytes_read = self.source.read(&mut self.buffer[internal_buffer_start..]).map_err(|source| TagIteratorError::ReadError { source })?;
+    ///
+    /// Enables or disables filling in default values for buffered tags.
+    ///
+    /// When enabled, every [`Master::Full`] tag produced by the iterator is checked for missing children that have a default value in the specification (see [`EbmlSpecification::get_default()`]).  Any missing children are appended to the end of the tag's children with their default values.  This is disabled by default, and has no effect on "Master" tags that are emitted as [`Master::Start`] and [`Master::End`].
+    ///
+    pub fn set_fill_defaults(&mut self, enabled: bool) {
+        self.decoder.set_fill_defaults(enabled);
+    }
+
+    ///
+    /// Enables or disables strict mode.
+    ///
+    /// When enabled, the iterator checks every non-master tag against the value ranges and lengths declared by the specification (see [`EbmlSpecification::get_unsigned_int_range()`] and related methods).  Tags that violate these constraints are reported as a [`TagIteratorError::InvalidTagData`] error, and iteration can continue with the next tag afterwards.  This is disabled by default.
+    ///
+    pub fn set_strict_mode(&mut self, enabled: bool) {
+        self.decoder.set_strict_mode(enabled);
+    }
+
+    ///
+    /// Configures a filter that decides which tags are read.
+    ///
+    /// The filter is called with the id of each tag and its depth (the number of "Master" tags enclosing it) before any tag data is read.  Tags for which the filter returns `false` are skipped without being decoded, and skipping a "Master" tag skips all of its children as well.  This means the filter must accept every ancestor of the tags of interest.  Children of tags that are read as [`Master::Full`][`crate::specs::Master::Full`]s are not filtered.
+    ///
+    /// ## Example
+    ///
+    /// ```
+    /// use std::collections::HashSet;
+    /// use ebml_iterable::TagIterator;
+    /// # use ebml_iterable_specification::empty_spec::EmptySpec;
+    ///
+    /// let data: &[u8] = &[0x1a, 0x45, 0xdf, 0xa3, 0x82, 0x01, 0x02, 0xec, 0x81, 0x00];
+    /// let ids: HashSet<u64> = [0xec].iter().copied().collect();
+    /// let mut my_iterator: TagIterator<_, EmptySpec> = TagIterator::new(data, &[]);
+    /// my_iterator.set_tag_filter(move |id, _depth| ids.contains(&id));
+    /// assert_eq!(1, my_iterator.count());
+    /// ```
+    ///
+    pub fn set_tag_filter<F>(&mut self, filter: F)
+        where F: Fn(u64, usize) -> bool + Send + Sync + 'static
+    {
+        self.decoder.set_tag_filter(Some(Box::new(filter)));
+    }
+
+    ///
+    /// Removes the filter configured using [`Self::set_tag_filter()`], so that all tags are read again.
+    ///
+    pub fn clear_tag_filter(&mut self) {
+        self.decoder.set_tag_filter(None);
+    }
+
+    ///
+    /// Returns the "Master" tags that enclose the current position of the iterator, outermost first.
+    ///
+    /// These are the tags whose [`Master::Start`] has been emitted but whose [`Master::End`] has not been emitted yet.  Each tag is returned as its [`Master::End`] variant along with its [`TagMetadata`].  This removes the need to maintain a separate stack of open tags when processing `Start` and `End` tags.
+    ///
+    pub fn ancestors(&self) -> impl Iterator<Item = (&TSpec, TagMetadata)> {
+        self.decoder.ancestors()
+    }
+
+    ///
+    /// Returns the number of "Master" tags that enclose the current position of the iterator.
+    ///
+    /// This is the same as the number of items returned by [`Self::ancestors()`].
+    ///
+    pub fn depth(&self) -> usize {
+        self.ancestors().count()
+    }
+
+    fn read_more(&mut self) -> Result<(), TagIteratorError> {
+        let bytes_read = self.source.read(&mut self.read_buffer).map_err(|source| TagIteratorError::ReadError { source })?;
         if bytes_read == 0 {
-            Ok(false)
+            self.decoder.finish();
         } else {
-            self.buffered_byte_length += bytes_read;
-            Ok(true)
+            self.decoder.feed(&self.read_buffer[..bytes_read]);
         }
+        Ok(())
     }
 
-    fn ensure_capacity(&mut self, required_capacity: usize) {
-        if required_capacity > self.buffer.len() {
-            let mut new_buffer = Vec::from(&self.buffer[..]);
-            new_buffer.resize(required_capacity, 0);
-            self.buffer = new_buffer.into_boxed_slice();
+    fn decode(&mut self, stream_binary: bool) -> Option<Result<Decoded<TSpec>, TagIteratorError>> {
+        loop {
+            match self.decoder.decode(stream_binary) {
+                Ok(Decoded::NeedData) => if let Err(err) = self.read_more() {
+                    return Some(Err(err));
+                },
+                Ok(Decoded::Done) => return None,
+                result => return Some(result),
+            }
         }
     }
 
-    fn ensure_data_read(&mut self, length: usize) -> Result<bool, TagIteratorError> {
-        if self.internal_buffer_position + length <= self.buffered_byte_length {
-            return Ok(true)
-        }
+    ///
+    /// Reads the next tag along with its [`TagMetadata`].
+    ///
+    /// This behaves exactly like [`Iterator::next()`], but additionally reports where the tag is located in the source and how its header was encoded.  This is useful for building indexes over large files.
+    ///
+    pub fn next_with_meta(&mut self) -> Option<Result<(TSpec, TagMetadata), TagIteratorError>> {
+        self.decode(false).map(|result| result.map(|decoded| match decoded {
+            Decoded::Tag(tag, meta) => (tag, meta),
+            _ => unreachable!("Binary tags are only streamed when requested"),
+        }))
+    }
 
-        if self.buffer_offset.is_none() {
-            if !self.private_read(0)? {
-                return Ok(false);
-            }
-            self.buffer_offset = Some(0);
-            self.internal_buffer_position = 0;
-        } else {
-            while self.internal_buffer_position + length > self.buffered_byte_length {
-                self.buffer.copy_within(self.internal_buffer_position..self.buffered_byte_length, 0);
-                self.buffered_byte_length -= self.internal_buffer_position;
-                self.buffer_offset = Some(self.buffer_offset.unwrap() + self.internal_buffer_position);
-                self.internal_buffer_position = 0;
-                if !self.private_read(self.buffered_byte_length)? {
-                    return Ok(false);
-                }
-            }
+    ///
+    /// Reads the next tag, streaming large "Binary" tag data rather than buffering it.
+    ///
+    /// This behaves like [`Iterator::next()`], except that "Binary" tags larger than the threshold configured using [`Self::set_binary_stream_threshold()`] are emitted as a [`StreamedTag::Binary`] handle.  The handle implements [`std::io::Read`] and reads the tag data directly from the source.  The iterator can be used again once the handle is dropped - any tag data that was not read from the handle is skipped.
+    ///
+    /// ## Errors
+    ///
+    /// This method can return the same errors as [`Iterator::next()`].  The returned handle will return an [`io::ErrorKind::UnexpectedEof`] error if the source ends before all tag data has been read.
+    ///
+    #[allow(clippy::type_complexity)]
+    pub fn next_streamed(&mut self) -> Option<Result<StreamedTag<TSpec, BinaryTagReader<'_, R, TSpec, S>>, TagIteratorError>> {
+        match self.decode(true)? {
+            Ok(Decoded::Tag(tag, _)) => Some(Ok(StreamedTag::Tag(tag))),
+            Ok(Decoded::Binary(tag_id, meta)) => Some(Ok(StreamedTag::Binary(BinaryTagReader::new(self, tag_id, meta)))),
+            Ok(_) => unreachable!("decode() never returns NeedData or Done"),
+            Err(err) => Some(Err(err)),
         }
-        Ok(true)
     }
 
-    fn read_tag_id(&mut self) -> Result<u64, TagIteratorError> {
-        self.ensure_data_read(8)?;
-        match tools::read_vint(&self.buffer[self.internal_buffer_position..]).map_err(|e| TagIteratorError::CorruptedFileData(e.to_string()))? {
-            Some((value, length)) => {
-                self.internal_buffer_position += length;
-                Ok(value + (1 << (7 * length)))
-            },
-            None => Err(TagIteratorError::CorruptedFileData(String::from("Expected tag id, but reached end of source."))),
-        }
+    ///
+    /// Walks the remaining data, passing each element to the callbacks of `visitor`.
+    ///
+    /// Unlike iterating, this does not construct `TSpec` variants for non-master elements - their data is decoded and passed to the visitor as borrowed values directly from the internal buffer.  "Master" tags configured in `tags_to_buffer` are still read as a whole, but are reported through the same start, child, and end callbacks as unbuffered tags.
+    ///
+    /// ## Errors
+    ///
+    /// This method returns the first error encountered while reading, which can be any of the errors returned by [`Iterator::next()`].  The iterator is left positioned after the failing element, so `visit()` can be called again to continue (for example, when error recovery is enabled).
+    ///
+    pub fn visit<V: EbmlVisitor + ?Sized>(&mut self, visitor: &mut V) -> Result<(), TagIteratorError> {
+        let decode_primitives = self.decoder.set_decode_primitives(false);
+        let result = self.visit_all(visitor);
+        self.decoder.set_decode_primitives(decode_primitives);
+        result
     }
 
-    fn read_tag_size(&mut self) -> Result<EBMLSize, TagIteratorError> {
-        self.ensure_data_read(8)?;
-        match tools::read_vint(&self.buffer[self.internal_buffer_position..]).map_err(|e| TagIteratorError::CorruptedFileData(e.to_string()))? {
-            Some((value, length)) => {
-                self.internal_buffer_position += length;
-                Ok(value.try_into().expect("u64 couldn't convert into usize"))
-            },
-            None => Err(TagIteratorError::CorruptedFileData(String::from("Expected tag size, but reached end of source."))),
-        }
+    ///
+    /// Returns an iterator over the remaining tags that match a path query such as `"Segment/Cluster/SimpleBlock"`.
+    ///
+    /// Tags are matched as they pass through the iterator, so only the matching tags need to be kept in memory.  The query syntax is described on [`TagQuery`].
+    ///
+    /// ## Errors
+    ///
+    /// This method returns a [`QueryError`] if the query cannot be parsed.  The items of the returned iterator can contain the same errors as [`Iterator::next()`].
+    ///
+    pub fn select(&mut self, path: &str) -> Result<Select<'_, R, TSpec, S>, QueryError> {
+        let query = TagQuery::parse_with_spec(path, self.spec())?;
+        Ok(Select::new(self, query))
     }
 
-    fn read_tag_data(&mut self, size: usize) -> Result<&[u8], TagIteratorError> {
-        self.ensure_capacity(size);
-        if !self.ensure_data_read(size)? {
-            return Err(TagIteratorError::CorruptedFileData(String::from("reached end of file but expecting more data")));
+    fn visit_all<V: EbmlVisitor + ?Sized>(&mut self, visitor: &mut V) -> Result<(), TagIteratorError> {
+        loop {
+            match self.decoder.decode(false)? {
+                Decoded::Tag(tag, _) => visitor::visit_tag(self.decoder.spec(), &tag, visitor),
+                Decoded::Data(tag_id) => visitor::visit_data(self.decoder.spec(), tag_id, self.decoder.last_data(), visitor)?,
+                Decoded::NeedData => self.read_more()?,
+                Decoded::Done => return Ok(()),
+                Decoded::Binary(..) => unreachable!("Binary tags are only streamed when requested"),
+            }
         }
-
-        self.internal_buffer_position += size;
-        Ok(&self.buffer[(self.internal_buffer_position-size)..self.internal_buffer_position])
     }
+}
 
-    fn read_tag(&mut self) -> Result<TSpec, TagIteratorError> {
-        let tag_id = self.read_tag_id()?;
-        let size: EBMLSize = self.read_tag_size()?;
+impl<R: Read + Seek, TSpec, S> TagIterator<R, TSpec, S>
+    where
+    TSpec: EbmlTag<TSpec> + Clone,
+    S: EbmlSpecificationInstance<TSpec>
+{
 
-        let spec_tag_type = <TSpec>::get_tag_data_type(tag_id);
+    ///
+    /// Moves the iterator to the specified offset and resumes parsing from there.
+    ///
+    /// The `offset` is measured the same way as the offsets in [`TagMetadata`] (from the point the iterator started reading), so offsets obtained from [`Self::next_with_meta()`] can be used directly.  The offset should point to the first byte of a tag.  Any open "Master" tags are forgotten, meaning that no [`Master::End`] will be emitted for tags that were started before the seek.
+    ///
+    /// ## Errors
+    ///
+    /// This method can return a [`TagIteratorError::ReadError`] if seeking the underlying source fails.
+    ///
+    pub fn seek_to(&mut self, offset: usize) -> Result<(), TagIteratorError> {
+        self.decoder.reset_tags();
+        self.seek_source(offset)
+    }
 
-        let is_master = matches!(spec_tag_type, TagDataType::Master);
-        let is_child = self.tag_stack.last().map(|it| {
-            match it {
-                NextTag {..} => true,
-                EndTag { size, tag: parent, .. } => {
-                    // The unknown check is there to still support proper parsing of badly formatted files.
-                    *size != Unknown || parent.is_child(tag_id)
-                }
-            }
-        }).unwrap_or(true);
-        if is_master && (size == Unknown || (!self.buffer_all && !self.tag_ids_to_buffer.contains(&tag_id))) {
-            let end_tag = EndTag {
-                tag: TSpec::get_master_tag(tag_id, Master::End).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id)),
-                size,
-                start: self.current_offset(),
-            };
-            let start_tag = TSpec::get_master_tag(tag_id, Master::Start).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id));
-            if is_child {
-                self.tag_stack.push(end_tag);
-                Ok(start_tag)
-            } else {
-                let tag = mem::replace(self.tag_stack.last_mut().unwrap(), end_tag).into_inner();
-                self.tag_stack.push(NextTag { tag: start_tag });
-                Ok(tag)
-            }
-        } else {
-            let raw_data = if let Known(size) = size {
-                self.read_tag_data(size)?
-            } else {
-                unreachable!("Unknown size for primitive or buffered tags is not allowed")
-            };
-            let tag = match spec_tag_type {
-                TagDataType::Master => {
-                    let mut src = Cursor::new(raw_data);
-                    let mut sub_iterator: TagIterator<_, TSpec> = TagIterator::new(&mut src, &[]);
-                    sub_iterator.buffer_all = true;
-                    let children: Result<Vec<TSpec>, TagIteratorError> = sub_iterator.collect();
-
-                    TSpec::get_master_tag(tag_id, Master::Full(children?)).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id))
-                },
-                TagDataType::UnsignedInt => {
-                    let val = tools::arr_to_u64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
-                    TSpec::get_unsigned_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", tag_id))
-                },
-                TagDataType::Integer => {
-                    let val = tools::arr_to_i64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
-                    TSpec::get_signed_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", tag_id))
-                },
-                TagDataType::Utf8 => {
-                    let val = String::from_utf8(raw_data.to_vec()).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: ToolError::FromUtf8Error(raw_data.to_vec(), e) })?;
-                    TSpec::get_utf8_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", tag_id))
-                },
-                TagDataType::Binary => {
-                    TSpec::get_binary_tag(tag_id, raw_data).unwrap_or_else(|| TSpec::get_raw_tag(tag_id, raw_data))
-                },
-                TagDataType::Float => {
-                    let val = tools::arr_to_f64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
-                    TSpec::get_float_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", tag_id))
-                },
-            };
-            if is_child {
-                Ok(tag)
-            } else {
-                Ok(mem::replace(self.tag_stack.last_mut().unwrap(), NextTag { tag }).into_inner())
-            }
+    ///
+    /// Skips the remaining content of the innermost open "Master" tag without reading it.
+    ///
+    /// After calling this method, the next tag emitted will be the [`Master::End`] of the skipped tag.  This is much faster than iterating over the children of large "Master" tags as the skipped content is never read from the source.
+    ///
+    /// ## Errors
+    ///
+    /// This method will return a [`TagIteratorError::InvalidSeek`] if there is no open "Master" tag or if the size of the open tag is unknown.  It can also return a [`TagIteratorError::ReadError`] if seeking the underlying source fails.
+    ///
+    pub fn skip_current_master(&mut self) -> Result<(), TagIteratorError> {
+        let end = self.decoder.skip_current_master()?;
+        self.seek_source(end)
+    }
+
+    fn seek_source(&mut self, offset: usize) -> Result<(), TagIteratorError> {
+        if self.decoder.reposition(offset) {
+            return Ok(());
         }
+
+        // The source is positioned right after the last buffered byte - seek relative to that so offsets don't depend on where the source started.
+        let source_position = self.decoder.buffered_end() as i64;
+        self.source.seek(SeekFrom::Current(offset as i64 - source_position)).map_err(|source| TagIteratorError::ReadError { source })?;
+        self.decoder.reset_buffer(offset);
+        Ok(())
     }
 }
 
-impl<R: Read, TSpec> Iterator for TagIterator<R, TSpec>
-    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+impl<R: Read, TSpec, S> Iterator for TagIterator<R, TSpec, S>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec>
 {
     type Item = Result<TSpec, TagIteratorError>;
 
     fn next(&mut self) -> Option<Self::Item> {
-        if let Some(tag) = self.tag_stack.pop() {
-            match tag {
-                EndTag { size, start, tag } => {
-                    if let Known(size) = size {
-                        if self.current_offset() >= start + size {
-                            return Some(Ok(tag));
-                        }
-                    }
-                    self.tag_stack.push(EndTag { size, start, tag });
-                },
-                NextTag { tag } => return Some(Ok(tag))
-            }
+        self.next_with_meta().map(|result| result.map(|(tag, _)| tag))
+    }
+}
+
+
+///
+/// A handle to read the data of a "Binary" tag directly from the source of a [`TagIterator`].
+///
+/// Instances are returned by [`TagIterator::next_streamed()`] for "Binary" tags larger than the configured stream threshold.  This implements [`std::io::Read`] and will return `Ok(0)` once all tag data has been read.  The handle mutably borrows the iterator, so it must be dropped before iteration can continue.  Any data that has not been read when the handle is dropped will be skipped by the iterator.
+///
+pub struct BinaryTagReader<'a, R: Read, TSpec, S = StaticSpecification<TSpec>>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec>
+{
+    iterator: &'a mut TagIterator<R, TSpec, S>,
+    id: u64,
+    meta: TagMetadata,
+    remaining: usize,
+}
+
+impl<'a, R: Read, TSpec, S> BinaryTagReader<'a, R, TSpec, S>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec>
+{
+    fn new(iterator: &'a mut TagIterator<R, TSpec, S>, id: u64, meta: TagMetadata) -> Self {
+        BinaryTagReader {
+            iterator,
+            id,
+            meta,
+            remaining: meta.data_size.expect("streamed tags must have a known size"),
         }
+    }
 
-        if self.internal_buffer_position == self.buffered_byte_length {
-            //If we've already consumed the entire internal buffer
-            //ensure there is nothing else in the data source before returning `None`
-            let read_result = self.ensure_data_read(1);
-            match read_result {
-                Err(err) => return Some(Err(err)),
-                Ok(data_remaining) => {
-                    if !data_remaining {
-                        return None;
-                    }
-                 }
-            }
+    ///
+    /// Returns the id of the tag being read.
+    ///
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    ///
+    /// Returns the [`TagMetadata`] of the tag being read.
+    ///
+    pub fn metadata(&self) -> TagMetadata {
+        self.meta
+    }
+
+    ///
+    /// Returns the number of bytes of tag data that have not been read yet.
+    ///
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, R: Read, TSpec, S> Read for BinaryTagReader<'a, R, TSpec, S>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec>
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        if self.remaining == 0 || buf.is_empty() {
+            return Ok(0);
         }
 
-        if self.internal_buffer_position > self.buffered_byte_length {
-            panic!("read position exceeded buffer length");
+        let max_length = self.remaining.min(buf.len());
+        let iterator = &mut *self.iterator;
+        let mut bytes_read = iterator.decoder.read_buffered(&mut buf[..max_length]);
+        if bytes_read == 0 {
+            // Bypass the internal buffer entirely so that large payloads never need to be held in memory
+            bytes_read = iterator.source.read(&mut buf[..max_length])?;
+            iterator.decoder.advance_unbuffered(bytes_read);
         }
+        if bytes_read == 0 {
+            return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "reached end of file but expecting more data"));
+        }
+        self.remaining -= bytes_read;
+        Ok(bytes_read)
+    }
+}
 
-        Some(self.read_tag())
+impl<'a, R: Read, TSpec, S> Drop for BinaryTagReader<'a, R, TSpec, S>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec>
+{
+    fn drop(&mut self) {
+        self.iterator.decoder.skip(self.remaining);
     }
 }
@@ -1,27 +1,25 @@
-use std::io::ErrorKind;
-use std::iter::repeat;
-use std::mem;
-use ebml_iterable_specification::{EbmlSpecification, EbmlTag, Master, TagDataType};
+use std::io;
+use std::pin::Pin;
+use std::task::{Context, Poll};
+use ebml_iterable_specification::{EbmlSpecification, EbmlSpecificationInstance, EbmlTag, StaticSpecification};
 use futures::{AsyncRead, AsyncReadExt, Stream};
-use crate::error::{TagIteratorError, ToolError};
-use crate::tag_iterator_util::{EBMLSize, ProcessingTag};
-use crate::tag_iterator_util::EBMLSize::{Known, Unknown};
-use crate::tag_iterator_util::ProcessingTag::{EndTag, NextTag};
-use crate::tools;
+use crate::error::TagIteratorError;
+use crate::tag_decoder::{Decoded, TagDecoder};
+use crate::tag_iterator_util::{DEFAULT_BUFFER_LEN, StreamedTag, TagIteratorLimits, TagMetadata};
 
 ///
 /// This Can be transformed into a [`Stream`] using [`into_stream`], or consumed directly by calling [`.next().await`] in a loop.
 ///
 /// The struct can be created with the [`new()`] function on any source that implements the [`futures::AsyncRead`] trait.
 ///
-pub struct TagIteratorAsync<R: AsyncRead + Unpin, TSpec>
+pub struct TagIteratorAsync<R: AsyncRead + Unpin, TSpec, S = StaticSpecification<TSpec>>
     where
-        TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+        TSpec: EbmlTag<TSpec> + Clone,
+        S: EbmlSpecificationInstance<TSpec>
 {
     read: R,
-    buf: Vec<u8>,
-    offset: usize,
-    tag_stack: Vec<ProcessingTag<TSpec>>
+    read_buffer: Box<[u8]>,
+    decoder: TagDecoder<'static, TSpec, S>,
 }
 
 impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
@@ -29,175 +27,195 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
         TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
 {
 
-    pub fn new(read: R) -> Self {
-        Self {
+    ///
+    /// Returns a new [`TagIteratorAsync<TSpec>`] instance.
+    ///
+    /// The `read` parameter must implement [`futures::AsyncRead`].  The second argument, `tags_to_buffer`, specifies which "Master" tags should be read as [`Master::Full`][`crate::specs::Master::Full`]s rather than as [`Master::Start`][`crate::specs::Master::Start`] and [`Master::End`][`crate::specs::Master::End`]s.  All options behave exactly like they do for [`TagIterator`][`crate::TagIterator`].
+    ///
+    pub fn new(read: R, tags_to_buffer: &[TSpec]) -> Self {
+        TagIteratorAsync::with_capacity(read, tags_to_buffer, DEFAULT_BUFFER_LEN)
+    }
+
+    ///
+    /// Returns a new [`TagIteratorAsync<TSpec>`] instance with the specified internal buffer capacity.
+    ///
+    /// This is the asynchronous equivalent of [`TagIterator::with_capacity()`][`crate::TagIterator::with_capacity`].
+    ///
+    pub fn with_capacity(read: R, tags_to_buffer: &[TSpec], capacity: usize) -> Self {
+        TagIteratorAsync::with_spec_and_capacity(read, StaticSpecification::new(), tags_to_buffer, capacity)
+    }
+}
+
+impl<R: AsyncRead + Unpin, TSpec, S> TagIteratorAsync<R, TSpec, S>
+    where
+        TSpec: EbmlTag<TSpec> + Clone,
+        S: EbmlSpecificationInstance<TSpec>
+{
+
+    ///
+    /// Returns a new [`TagIteratorAsync`] instance that reads tags using the specification instance `spec`.
+    ///
+    /// This is the asynchronous equivalent of [`TagIterator::with_spec()`][`crate::TagIterator::with_spec`].
+    ///
+    pub fn with_spec(read: R, spec: S, tags_to_buffer: &[TSpec]) -> Self {
+        TagIteratorAsync::with_spec_and_capacity(read, spec, tags_to_buffer, DEFAULT_BUFFER_LEN)
+    }
+
+    ///
+    /// Returns a new [`TagIteratorAsync`] instance that reads tags using the specification instance `spec`, with the specified internal buffer capacity.
+    ///
+    /// This is the asynchronous equivalent of [`TagIterator::with_spec_and_capacity()`][`crate::TagIterator::with_spec_and_capacity`].
+    ///
+    pub fn with_spec_and_capacity(read: R, spec: S, tags_to_buffer: &[TSpec], capacity: usize) -> Self {
+        let buffer = vec![0;capacity];
+
+        TagIteratorAsync {
             read,
-            buf: Default::default(),
-            offset: 0,
-            tag_stack: Default::default()
+            read_buffer: buffer.into_boxed_slice(),
+            decoder: TagDecoder::new(spec, tags_to_buffer, capacity),
         }
     }
 
-    fn current_offset(&self) -> usize {
-        self.offset
+    ///
+    /// Returns the specification instance used by the iterator.
+    ///
+    pub fn spec(&self) -> &S {
+        self.decoder.spec()
     }
 
-    fn advance(&mut self, length: usize) {
-        self.offset += length;
-        self.buf.drain(0..length);
+    ///
+    /// Configures the size above which "Binary" tags are streamed rather than buffered.
+    ///
+    /// When a threshold is set, [`Self::next_streamed()`] emits any "Binary" tag whose data is larger than `threshold` bytes as a [`StreamedTag::Binary`] handle that reads the tag data directly from the source instead of buffering it into memory.  Passing `None` (the default) disables streaming.  This setting has no effect on [`Self::next()`], which always buffers tag data.
+    ///
+    pub fn set_binary_stream_threshold(&mut self, threshold: Option<usize>) {
+        self.decoder.set_binary_stream_threshold(threshold);
     }
 
-    fn advance_get(&mut self, length: usize) -> Vec<u8> {
-        self.offset += length;
-        let upper = self.buf.split_off(length);
-        mem::replace(&mut self.buf, upper)
+    ///
+    /// Enables or disables recovery from corrupted data.
+    ///
+    /// This behaves exactly like [`TagIterator::set_error_recovery()`][`crate::TagIterator::set_error_recovery`].
+    ///
+    pub fn set_error_recovery(&mut self, enabled: bool) {
+        self.decoder.set_error_recovery(enabled);
     }
 
-    async fn ensure_data_read(&mut self, len: usize) -> Result<bool, TagIteratorError> {
-        let size = self.buf.len();
-        if size < len {
-            let remaining = len - size;
-            self.buf.extend(repeat(0).take(remaining));
-            match self.read.read_exact(&mut self.buf[size..]).await {
-                Err(source) => {
-                    return match source.kind() {
-                        ErrorKind::UnexpectedEof => {
-                            Ok(false)
-                        }
-                        _ => Err(TagIteratorError::ReadError { source })?
-                    }
+    ///
+    /// Configures resource limits for the iterator.
+    ///
+    /// This behaves exactly like [`TagIterator::set_limits()`][`crate::TagIterator::set_limits`].
+    ///
+    pub fn set_limits(&mut self, limits: TagIteratorLimits) {
+        self.decoder.set_limits(limits);
+    }
 
-                }
-                _ => {}
-            }
-        }
-        Ok(true)
+    ///
+    /// Enables or disables filling in default values for buffered tags.
+    ///
+    /// This behaves exactly like [`TagIterator::set_fill_defaults()`][`crate::TagIterator::set_fill_defaults`].
+    ///
+    pub fn set_fill_defaults(&mut self, enabled: bool) {
+        self.decoder.set_fill_defaults(enabled);
     }
 
-    async fn read_tag_id(&mut self) -> Result<u64, TagIteratorError> {
-        self.ensure_data_read(8).await?;
-        match tools::read_vint(&self.buf).map_err(|e| TagIteratorError::CorruptedFileData(e.to_string()))? {
-            Some((value, length)) => {
-                self.advance(length);
-                Ok(value + (1 << (7 * length)))
-            },
-            None => Err(TagIteratorError::CorruptedFileData(String::from("Expected tag id, but reached end of source."))),
-        }
+    ///
+    /// Enables or disables strict mode.
+    ///
+    /// This behaves exactly like [`TagIterator::set_strict_mode()`][`crate::TagIterator::set_strict_mode`].
+    ///
+    pub fn set_strict_mode(&mut self, enabled: bool) {
+        self.decoder.set_strict_mode(enabled);
     }
 
-    async fn read_tag_size(&mut self) -> Result<EBMLSize, TagIteratorError> {
-        self.ensure_data_read(8).await?;
-        match tools::read_vint(&self.buf).map_err(|e| TagIteratorError::CorruptedFileData(e.to_string()))? {
-            Some((value, length)) => {
-                self.advance(length);
-                Ok(value.into())
-            },
-            None => Err(TagIteratorError::CorruptedFileData(String::from("Expected tag size, but reached end of source."))),
-        }
+    ///
+    /// Configures a filter that decides which tags are read.
+    ///
+    /// This behaves exactly like [`TagIterator::set_tag_filter()`][`crate::TagIterator::set_tag_filter`].
+    ///
+    pub fn set_tag_filter<F>(&mut self, filter: F)
+        where F: Fn(u64, usize) -> bool + Send + Sync + 'static
+    {
+        self.decoder.set_tag_filter(Some(Box::new(filter)));
     }
 
-    async fn read_tag_data(&mut self, size: usize) -> Result<Vec<u8>, TagIteratorError> {
-        if !self.ensure_data_read(size).await? {
-            return Err(TagIteratorError::CorruptedFileData(String::from("reached end of file but expecting more data")));
-        }
-        Ok(self.advance_get(size))
-    }
-
-    async fn read_tag(&mut self) -> Result<TSpec, TagIteratorError> {
-        let tag_id = self.read_tag_id().await?;
-        let spec_tag_type = TSpec::get_tag_data_type(tag_id);
-        let size = self.read_tag_size().await?;
-
-        let is_master = matches!(spec_tag_type, TagDataType::Master);
-        let is_child = self.tag_stack.last().map(|it| {
-            match it {
-                NextTag {..} => true,
-                EndTag { size, tag: parent, .. } => {
-                    // The unknown check is there to still support proper parsing of badly formatted files.
-                    *size != Unknown || parent.is_child(tag_id)
-                }
-            }
-        }).unwrap_or(true);
-        if is_master {
-            let end_tag = EndTag {
-                tag: TSpec::get_master_tag(tag_id, Master::End).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id)),
-                size,
-                start: self.current_offset(),
-            };
-            let start_tag = TSpec::get_master_tag(tag_id, Master::Start).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id));
-            if is_child {
-                self.tag_stack.push(end_tag);
-                Ok(start_tag)
-            } else {
-                let tag = mem::replace(self.tag_stack.last_mut().unwrap(), end_tag).into_inner();
-                self.tag_stack.push(NextTag { tag: start_tag });
-                Ok(tag)
-            }
+    ///
+    /// Removes the filter configured using [`Self::set_tag_filter()`], so that all tags are read again.
+    ///
+    pub fn clear_tag_filter(&mut self) {
+        self.decoder.set_tag_filter(None);
+    }
+
+    ///
+    /// Returns the "Master" tags that enclose the current position of the iterator, outermost first.
+    ///
+    /// These are the tags whose [`Master::Start`] has been emitted but whose [`Master::End`] has not been emitted yet.  Each tag is returned as its [`Master::End`] variant along with its [`TagMetadata`].  This removes the need to maintain a separate stack of open tags when processing `Start` and `End` tags.
+    ///
+    pub fn ancestors(&self) -> impl Iterator<Item = (&TSpec, TagMetadata)> {
+        self.decoder.ancestors()
+    }
+
+    ///
+    /// Returns the number of "Master" tags that enclose the current position of the iterator.
+    ///
+    /// This is the same as the number of items returned by [`Self::ancestors()`].
+    ///
+    pub fn depth(&self) -> usize {
+        self.ancestors().count()
+    }
+
+    async fn read_more(&mut self) -> Result<(), TagIteratorError> {
+        let bytes_read = self.read.read(&mut self.read_buffer).await.map_err(|source| TagIteratorError::ReadError { source })?;
+        if bytes_read == 0 {
+            self.decoder.finish();
         } else {
-            let size = if let Known(size) = size {
-                size
-            } else {
-                return Err(TagIteratorError::CorruptedFileData("Unknown size for primitive not allowed".into()));
-            };
-            let raw_data = self.read_tag_data(size).await?;
-            let tag = match spec_tag_type {
-                TagDataType::Master => { unreachable!("Master should have been handled before querying data") },
-                TagDataType::UnsignedInt => {
-                    let val = tools::arr_to_u64(&raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
-                    TSpec::get_unsigned_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", tag_id))
-                },
-                TagDataType::Integer => {
-                    let val = tools::arr_to_i64(&raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
-                    TSpec::get_signed_int_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", tag_id))
-                },
-                TagDataType::Utf8 => {
-                    let val = String::from_utf8(raw_data.to_vec()).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: ToolError::FromUtf8Error(raw_data, e) })?;
-                    TSpec::get_utf8_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", tag_id))
-                },
-                TagDataType::Binary => {
-                    TSpec::get_binary_tag(tag_id, &raw_data).unwrap_or_else(|| TSpec::get_raw_tag(tag_id, &raw_data))
-                },
-                TagDataType::Float => {
-                    let val = tools::arr_to_f64(&raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
-                    TSpec::get_float_tag(tag_id, val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", tag_id))
+            self.decoder.feed(&self.read_buffer[..bytes_read]);
+        }
+        Ok(())
+    }
+
+    async fn decode(&mut self, stream_binary: bool) -> Option<Result<Decoded<TSpec>, TagIteratorError>> {
+        loop {
+            match self.decoder.decode(stream_binary) {
+                Ok(Decoded::NeedData) => if let Err(err) = self.read_more().await {
+                    return Some(Err(err));
                 },
-            };
-            if is_child {
-                Ok(tag)
-            } else {
-                Ok(mem::replace(self.tag_stack.last_mut().unwrap(), NextTag { tag }).into_inner())
+                Ok(Decoded::Done) => return None,
+                result => return Some(result),
             }
         }
     }
 
     /// can be consumed
     pub async fn next(&mut self) -> Option<Result<TSpec, TagIteratorError>> {
-        if let Some(tag) = self.tag_stack.pop() {
-            match tag {
-                EndTag { size, start, tag } => {
-                    if let Known(size) = size {
-                        if self.current_offset() >= start + size {
-                            return Some(Ok(tag));
-                        }
-                    }
-                    self.tag_stack.push(EndTag { size, start, tag });
-                },
-                NextTag { tag } => return Some(Ok(tag))
-            }
-        }
-        match self.ensure_data_read(1).await {
-            Err(err) => return Some(Err(err)),
-            Ok(data_remaining) => {
-                if !data_remaining {
-                    return if let Some(tag) = self.tag_stack.pop() {
-                        Some(Ok(tag.into_inner()))
-                    } else {
-                        None
-                    }
-                }
-            }
+        self.next_with_meta().await.map(|result| result.map(|(tag, _)| tag))
+    }
+
+    ///
+    /// Reads the next tag along with its [`TagMetadata`].
+    ///
+    /// This behaves exactly like [`Self::next()`], but additionally reports where the tag is located in the source and how its header was encoded.
+    ///
+    pub async fn next_with_meta(&mut self) -> Option<Result<(TSpec, TagMetadata), TagIteratorError>> {
+        self.decode(false).await.map(|result| result.map(|decoded| match decoded {
+            Decoded::Tag(tag, meta) => (tag, meta),
+            _ => unreachable!("Binary tags are only streamed when requested"),
+        }))
+    }
+
+    ///
+    /// Reads the next tag, streaming large "Binary" tag data rather than buffering it.
+    ///
+    /// This behaves like [`Self::next()`], except that "Binary" tags larger than the threshold configured using [`Self::set_binary_stream_threshold()`] are emitted as a [`StreamedTag::Binary`] handle.  The handle implements [`futures::AsyncRead`] and reads the tag data directly from the source.  The iterator can be used again once the handle is dropped - any tag data that was not read from the handle is skipped.
+    ///
+    #[allow(clippy::type_complexity)]
+    pub async fn next_streamed(&mut self) -> Option<Result<StreamedTag<TSpec, BinaryTagReaderAsync<'_, R, TSpec, S>>, TagIteratorError>> {
+        match self.decode(true).await? {
+            Ok(Decoded::Tag(tag, _)) => Some(Ok(StreamedTag::Tag(tag))),
+            Ok(Decoded::Binary(tag_id, meta)) => Some(Ok(StreamedTag::Binary(BinaryTagReaderAsync::new(self, tag_id, meta)))),
+            Ok(_) => unreachable!("decode() never returns NeedData or Done"),
+            Err(err) => Some(Err(err)),
         }
-        Some(self.read_tag().await)
     }
 
     pub fn into_stream(self) -> impl Stream<Item=Result<TSpec, TagIteratorError>> {
@@ -207,3 +225,88 @@ impl<R: AsyncRead + Unpin, TSpec> TagIteratorAsync<R, TSpec>
         })
     }
 }
+
+///
+/// A handle to read the data of a "Binary" tag directly from the source of a [`TagIteratorAsync`].
+///
+/// Instances are returned by [`TagIteratorAsync::next_streamed()`] for "Binary" tags larger than the configured stream threshold.  This implements [`futures::AsyncRead`] and will return `Ok(0)` once all tag data has been read.  The handle mutably borrows the iterator, so it must be dropped before iteration can continue.  Any data that has not been read when the handle is dropped will be skipped by the iterator.
+///
+pub struct BinaryTagReaderAsync<'a, R: AsyncRead + Unpin, TSpec, S = StaticSpecification<TSpec>>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec>
+{
+    iterator: &'a mut TagIteratorAsync<R, TSpec, S>,
+    id: u64,
+    meta: TagMetadata,
+    remaining: usize,
+}
+
+impl<'a, R: AsyncRead + Unpin, TSpec, S> BinaryTagReaderAsync<'a, R, TSpec, S>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec>
+{
+    fn new(iterator: &'a mut TagIteratorAsync<R, TSpec, S>, id: u64, meta: TagMetadata) -> Self {
+        BinaryTagReaderAsync {
+            iterator,
+            id,
+            meta,
+            remaining: meta.data_size.expect("streamed tags must have a known size"),
+        }
+    }
+
+    ///
+    /// Returns the id of the tag being read.
+    ///
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    ///
+    /// Returns the [`TagMetadata`] of the tag being read.
+    ///
+    pub fn metadata(&self) -> TagMetadata {
+        self.meta
+    }
+
+    ///
+    /// Returns the number of bytes of tag data that have not been read yet.
+    ///
+    pub fn remaining(&self) -> usize {
+        self.remaining
+    }
+}
+
+impl<'a, R: AsyncRead + Unpin, TSpec, S> AsyncRead for BinaryTagReaderAsync<'a, R, TSpec, S>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec>
+{
+    fn poll_read(self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<io::Result<usize>> {
+        let this = self.get_mut();
+        if this.remaining == 0 || buf.is_empty() {
+            return Poll::Ready(Ok(0));
+        }
+
+        let max_length = this.remaining.min(buf.len());
+        let iterator = &mut *this.iterator;
+        let mut bytes_read = iterator.decoder.read_buffered(&mut buf[..max_length]);
+        if bytes_read == 0 {
+            // Bypass the internal buffer entirely so that large payloads never need to be held in memory
+            bytes_read = match Pin::new(&mut iterator.read).poll_read(cx, &mut buf[..max_length]) {
+                Poll::Ready(Ok(bytes_read)) => bytes_read,
+                other => return other,
+            };
+            iterator.decoder.advance_unbuffered(bytes_read);
+        }
+
+        if bytes_read == 0 {
+            return Poll::Ready(Err(io::Error::new(io::ErrorKind::UnexpectedEof, "reached end of file but expecting more data")));
+        }
+        this.remaining -= bytes_read;
+        Poll::Ready(Ok(bytes_read))
+    }
+}
+
+impl<'a, R: AsyncRead + Unpin, TSpec, S> Drop for BinaryTagReaderAsync<'a, R, TSpec, S>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec>
+{
+    fn drop(&mut self) {
+        self.iterator.decoder.skip(self.remaining);
+    }
+}
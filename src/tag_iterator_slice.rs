@@ -0,0 +1,358 @@
+use std::collections::HashSet;
+use std::str;
+
+use crate::tag_decoder::{Decoded, TagDecoder, TagFilter};
+use crate::tag_iterator_util::{TagIteratorLimits, TagMetadata, empty_element_default};
+
+use super::tools;
+use super::specs::{Date, EbmlSpecification, EbmlTag, Master, StaticSpecification, TagDataType};
+use super::errors::tag_iterator::TagIteratorError;
+use super::errors::tool::ToolError;
+
+///
+/// A tag read by a [`TagIteratorSlice`] whose data borrows from the input slice.
+///
+/// Unlike specification tags, creating a [`BorrowedTag`] never copies tag data.  It can be converted into a tag of any specification using [`Self::to_tag()`].
+///
+#[derive(Clone, Debug, PartialEq)]
+pub struct BorrowedTag<'a> {
+
+    ///
+    /// The id of the tag.
+    ///
+    pub id: u64,
+
+    ///
+    /// The data contained in the tag.
+    ///
+    pub data: BorrowedTagData<'a>,
+}
+
+///
+/// The data contained in a [`BorrowedTag`].
+///
+/// Each variant corresponds to a [`TagDataType`].  Binary, Utf8 and String data borrow from the slice being iterated.
+///
+#[derive(Clone, Debug, PartialEq)]
+pub enum BorrowedTagData<'a> {
+    Master(Master<BorrowedTag<'a>>),
+    UnsignedInt(u64),
+    Integer(i64),
+    Utf8(&'a str),
+    Binary(&'a [u8]),
+    Float(f64),
+    Date(Date),
+    String(&'a str),
+}
+
+impl<'a> BorrowedTag<'a> {
+
+    ///
+    /// Converts this tag into a tag of the `TSpec` specification.
+    ///
+    /// Note that this copies any Binary, Utf8 or String data into the returned tag.
+    ///
+    /// ## Panics
+    ///
+    /// This method can panic if `<TSpec>` is an internally inconsistent specification or if this tag was read using a different specification.
+    ///
+    pub fn to_tag<TSpec>(&self) -> TSpec
+        where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    {
+        let id = self.id;
+        match &self.data {
+            BorrowedTagData::Master(master) => {
+                let master = match master {
+                    Master::Start => Master::Start,
+                    Master::End => Master::End,
+                    Master::Full(children) => Master::Full(children.iter().map(|child| child.to_tag()).collect()),
+                };
+                TSpec::get_master_tag(id, master).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", id))
+            },
+            BorrowedTagData::UnsignedInt(val) => TSpec::get_unsigned_int_tag(id, *val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", id)),
+            BorrowedTagData::Integer(val) => TSpec::get_signed_int_tag(id, *val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", id)),
+            BorrowedTagData::Utf8(val) => TSpec::get_utf8_tag(id, val.to_string()).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", id)),
+            BorrowedTagData::Binary(val) => TSpec::get_binary_tag(id, val).unwrap_or_else(|| TSpec::get_raw_tag(id, val)),
+            BorrowedTagData::Float(val) => TSpec::get_float_tag(id, *val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", id)),
+            BorrowedTagData::Date(val) => TSpec::get_date_tag(id, *val).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was date, but could not get tag!", id)),
+            BorrowedTagData::String(val) => TSpec::get_string_tag(id, val.to_string()).unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was string, but could not get tag!", id)),
+        }
+    }
+
+    fn master(id: u64, master: Master<BorrowedTag<'a>>) -> Self {
+        BorrowedTag {
+            id,
+            data: BorrowedTagData::Master(master),
+        }
+    }
+}
+
+///
+/// Provides an iterator over EBML data that is already held in memory as a byte slice.
+///
+/// This works like [`TagIterator`][`crate::TagIterator`], but never copies data into an internal buffer.  Instead of `TSpec` variants, the iterator outputs [`BorrowedTag`]s whose Binary, Utf8 and String data borrow directly from the input slice.  This makes it well suited for memory-mapped files or network buffers that already contain a complete document.  The specification is only used to determine tag data types and hierarchy.
+///
+/// Tags are decoded by the same logic as [`TagIterator`][`crate::TagIterator`], so both iterators produce the same tags for the same data, including for "Master" tags with an unknown size and for tags that are still open when the data ends.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::{TagIteratorSlice, BorrowedTagData};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let data = [0x1a, 0x45, 0xdf, 0xa3, 0x82, 0x01, 0x02];
+/// let mut my_iterator: TagIteratorSlice<EmptySpec> = TagIteratorSlice::new(&data, &[]);
+/// let tag = my_iterator.next().unwrap().unwrap();
+/// assert_eq!(0x1a45dfa3, tag.id);
+/// assert_eq!(BorrowedTagData::Binary(&[0x01, 0x02]), tag.data);
+/// ```
+///
+/// ## Errors
+///
+/// The `Item` type for the associated [`Iterator`] implementation is a [`Result<BorrowedTag, TagIteratorError>`].  The different possible error states are enumerated in [`TagIteratorError`].
+///
+pub struct TagIteratorSlice<'a, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    data: &'a [u8],
+    decoder: TagDecoder<'a, TSpec, StaticSpecification<TSpec>>,
+    tag_ids_to_buffer: HashSet<u64>,
+    max_buffered_size: Option<usize>,
+
+    // "Master" tags that are being read as `Master::Full`s along with the children read so far, outermost first.
+    buffering: Vec<(u64, TagMetadata, Vec<BorrowedTag<'a>>)>,
+    // Children of buffered tags are never filtered, so the tag filter is moved out of the decoder while reading them.
+    suspended_filter: Option<TagFilter>,
+}
+
+impl<'a, TSpec> TagIteratorSlice<'a, TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new [`TagIteratorSlice<TSpec>`] instance.
+    ///
+    /// The `data` parameter is the complete EBML data to iterate over.  The second argument, `tags_to_buffer`, specifies which "Master" tags should be read as [`Master::Full`]s rather than as [`Master::Start`] and [`Master::End`]s.
+    ///
+    pub fn new(data: &'a [u8], tags_to_buffer: &[TSpec]) -> Self {
+        let mut decoder = TagDecoder::from_slice(StaticSpecification::new(), &[], data);
+        // Buffered tags are assembled from their children here so that their data can be borrowed
+        decoder.set_decode_primitives(false);
+
+        TagIteratorSlice {
+            data,
+            decoder,
+            tag_ids_to_buffer: tags_to_buffer.iter().map(|tag| tag.get_id()).collect(),
+            max_buffered_size: None,
+            buffering: Vec::new(),
+            suspended_filter: None,
+        }
+    }
+
+    ///
+    /// Returns the offset in the input slice of the next byte to be read.
+    ///
+    pub fn position(&self) -> usize {
+        self.decoder.offset()
+    }
+
+    ///
+    /// Enables or disables recovery from corrupted data.
+    ///
+    /// This behaves exactly like [`TagIterator::set_error_recovery()`][`crate::TagIterator::set_error_recovery`].
+    ///
+    pub fn set_error_recovery(&mut self, enabled: bool) {
+        self.decoder.set_error_recovery(enabled);
+    }
+
+    ///
+    /// Configures resource limits for the iterator.
+    ///
+    /// This behaves exactly like [`TagIterator::set_limits()`][`crate::TagIterator::set_limits`].
+    ///
+    pub fn set_limits(&mut self, limits: TagIteratorLimits) {
+        self.max_buffered_size = limits.max_buffered_size;
+        self.decoder.set_limits(limits);
+    }
+
+    ///
+    /// Enables or disables strict mode.
+    ///
+    /// This behaves exactly like [`TagIterator::set_strict_mode()`][`crate::TagIterator::set_strict_mode`].
+    ///
+    pub fn set_strict_mode(&mut self, enabled: bool) {
+        self.decoder.set_strict_mode(enabled);
+    }
+
+    ///
+    /// Configures a filter that decides which tags are read.
+    ///
+    /// This behaves exactly like [`TagIterator::set_tag_filter()`][`crate::TagIterator::set_tag_filter`].
+    ///
+    pub fn set_tag_filter<F>(&mut self, filter: F)
+        where F: Fn(u64, usize) -> bool + Send + Sync + 'static
+    {
+        self.replace_tag_filter(Some(Box::new(filter)));
+    }
+
+    ///
+    /// Removes the filter configured using [`Self::set_tag_filter()`], so that all tags are read again.
+    ///
+    pub fn clear_tag_filter(&mut self) {
+        self.replace_tag_filter(None);
+    }
+
+    fn replace_tag_filter(&mut self, filter: Option<TagFilter>) {
+        if self.buffering.is_empty() {
+            self.decoder.set_tag_filter(filter);
+        } else {
+            self.suspended_filter = filter;
+        }
+    }
+
+    fn read_tag(&mut self) -> Option<Result<(BorrowedTag<'a>, Option<TagMetadata>), TagIteratorError>> {
+        match self.decoder.decode(false) {
+            Ok(Decoded::Tag(tag, meta)) => {
+                let master = match tag.as_master() {
+                    Some(Master::Start) => Master::Start,
+                    Some(Master::End) => Master::End,
+                    _ => unreachable!("Only \"Master\" tags are decoded when primitive decoding is disabled"),
+                };
+                Some(Ok((BorrowedTag::master(tag.get_id(), master), Some(meta))))
+            },
+            Ok(Decoded::Data(tag_id)) => {
+                let data: &'a [u8] = self.data;
+                Some(Self::read_data(tag_id, &data[self.decoder.last_data_range()]).map(|tag| (tag, None)))
+            },
+            Ok(Decoded::Done) => None,
+            Ok(_) => unreachable!("The decoder owns all data and doesn't stream binary tags"),
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    fn read_data(tag_id: u64, raw_data: &'a [u8]) -> Result<BorrowedTag<'a>, TagIteratorError> {
+        if let Some(default) = empty_element_default(&StaticSpecification::<TSpec>::new(), tag_id, raw_data) {
+            let data = if let Some(value) = default.as_unsigned_int() {
+                BorrowedTagData::UnsignedInt(*value)
+            } else if let Some(value) = default.as_signed_int() {
+                BorrowedTagData::Integer(*value)
+            } else if let Some(value) = default.as_float() {
+                BorrowedTagData::Float(*value)
+            } else {
+                BorrowedTagData::Date(*default.as_date().expect("defaults of empty elements are numbers or dates"))
+            };
+            return Ok(BorrowedTag { id: tag_id, data });
+        }
+
+        let data = match TSpec::get_tag_data_type(tag_id) {
+            TagDataType::Master => unreachable!("Master tags are never returned as data"),
+            TagDataType::UnsignedInt => BorrowedTagData::UnsignedInt(tools::arr_to_u64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?),
+            TagDataType::Integer => BorrowedTagData::Integer(tools::arr_to_i64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?),
+            TagDataType::Utf8 => {
+                let text = tools::trim_null_padding(raw_data);
+                let val = str::from_utf8(text).map_err(|_| {
+                    let e = String::from_utf8(text.to_vec()).expect_err("data should not be valid utf8");
+                    TagIteratorError::CorruptedTagData{ tag_id, problem: ToolError::FromUtf8Error(text.to_vec(), e) }
+                })?;
+                BorrowedTagData::Utf8(val)
+            },
+            TagDataType::Binary => BorrowedTagData::Binary(raw_data),
+            TagDataType::Float => BorrowedTagData::Float(tools::arr_to_f64(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?),
+            TagDataType::Date => BorrowedTagData::Date(tools::arr_to_date(raw_data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?),
+            TagDataType::String => BorrowedTagData::String(tools::arr_to_ascii(tools::trim_null_padding(raw_data)).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?),
+        };
+        Ok(BorrowedTag { id: tag_id, data })
+    }
+
+    fn start_buffering(&mut self, tag_id: u64, meta: TagMetadata) -> Result<(), TagIteratorError> {
+        if let Some(size) = meta.data_size {
+            if let Some(limit) = self.max_buffered_size {
+                if size > limit {
+                    return Err(TagIteratorError::BufferedSizeLimitExceeded { tag_id, size, limit });
+                }
+            }
+            if meta.data_start() + size > self.data.len() {
+                return Err(TagIteratorError::CorruptedFileData(String::from("reached end of file but expecting more data")));
+            }
+        }
+
+        if self.buffering.is_empty() {
+            self.suspended_filter = self.decoder.set_tag_filter(None);
+        }
+        self.buffering.push((tag_id, meta, Vec::new()));
+        Ok(())
+    }
+
+    fn finish_buffering(&mut self) -> BorrowedTag<'a> {
+        let (tag_id, _, children) = self.buffering.pop().expect("a buffered tag must be open");
+        if self.buffering.is_empty() {
+            let filter = self.suspended_filter.take();
+            self.decoder.set_tag_filter(filter);
+        }
+        BorrowedTag::master(tag_id, Master::Full(children))
+    }
+
+    // Discards the tags being buffered after an error, along with `unbuffered` tags that were started but not yet buffered, like the decoder does for the tags it buffers itself
+    fn abandon_buffering(&mut self, unbuffered: usize) {
+        self.decoder.abandon_open_tags(self.buffering.len() + unbuffered);
+        if !self.buffering.is_empty() {
+            self.buffering.clear();
+            let filter = self.suspended_filter.take();
+            self.decoder.set_tag_filter(filter);
+        }
+    }
+
+    fn check_buffered_size(&self) -> Result<(), TagIteratorError> {
+        if let (Some(limit), Some((tag_id, meta, _))) = (self.max_buffered_size, self.buffering.first()) {
+            let size = self.decoder.offset() - meta.data_start();
+            if size > limit {
+                return Err(TagIteratorError::BufferedSizeLimitExceeded { tag_id: *tag_id, size, limit });
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a, TSpec> Iterator for TagIteratorSlice<'a, TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    type Item = Result<BorrowedTag<'a>, TagIteratorError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let (tag, meta) = match self.read_tag()? {
+                Ok(read) => read,
+                Err(err) => {
+                    self.abandon_buffering(0);
+                    return Some(Err(err));
+                },
+            };
+
+            // All "Master" tags within a buffered tag are buffered as well
+            let tag = match (&tag.data, meta) {
+                (BorrowedTagData::Master(Master::Start), Some(meta)) if !self.buffering.is_empty() || self.tag_ids_to_buffer.contains(&tag.id) => {
+                    if let Err(err) = self.start_buffering(tag.id, meta) {
+                        self.abandon_buffering(1);
+                        return Some(Err(err));
+                    }
+                    continue;
+                },
+                (BorrowedTagData::Master(Master::End), _) if !self.buffering.is_empty() => self.finish_buffering(),
+                _ => tag,
+            };
+
+            match self.buffering.last_mut() {
+                Some((_, _, children)) => {
+                    children.push(tag);
+                    if let Err(err) = self.check_buffered_size() {
+                        self.abandon_buffering(0);
+                        return Some(Err(err));
+                    }
+                },
+                None => return Some(Ok(tag)),
+            }
+        }
+    }
+}
@@ -1,4 +1,4 @@
-use ebml_iterable_specification::{EbmlSpecification, EbmlTag};
+use ebml_iterable_specification::{EbmlSpecificationInstance, EbmlTag, Master, TagDataType};
 use std::convert::TryInto;
 use crate::tag_iterator_util::EBMLSize::{Known, Unknown};
 use crate::tag_iterator_util::ProcessingTag::{EndTag, NextTag};
@@ -9,20 +9,17 @@ pub enum EBMLSize {
     Unknown
 }
 
-impl From<u64> for EBMLSize {
-    fn from(size: u64) -> Self {
-        Self::new(size)
-    }
-}
-
 impl EBMLSize {
 
-    pub fn new(size: u64) -> Self {
-        const UNKNOWN: u64 = u64::MAX >> 8;
-        if size == UNKNOWN {
-            return Unknown
+    ///
+    /// Interprets a vint value read from a tag size field.  A size is unknown if all of its value bits are set, regardless of the length of the vint.
+    ///
+    pub fn new(value: u64, length: usize) -> Self {
+        let unknown: u64 = (1 << (7 * length)) - 1;
+        if value == unknown {
+            Unknown
         } else {
-            match size.try_into() {
+            match value.try_into() {
                 Ok(value) => Known(value),
                 Err(_) => Unknown
             }
@@ -31,40 +28,209 @@ impl EBMLSize {
 
 }
 
+///
+/// Positional information about a tag read by a [`TagIterator`][`crate::TagIterator`] or [`TagIteratorAsync`][`crate::TagIteratorAsync`].
+///
+/// All offsets are absolute byte positions in the source, measured from the point the iterator started reading.  [`Master::End`][`crate::specs::Master::End`] tags carry the same metadata as the [`Master::Start`][`crate::specs::Master::Start`] tag they close.
+///
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct TagMetadata {
+
+    ///
+    /// The offset of the first byte of the element (i.e. the first byte of its id).
+    ///
+    pub start: usize,
+
+    ///
+    /// The length of the element header in bytes.  This is always `id_len + size_len`.
+    ///
+    pub header_len: usize,
+
+    ///
+    /// The length of the element id in bytes.
+    ///
+    pub id_len: usize,
+
+    ///
+    /// The length of the element's data size vint in bytes.
+    ///
+    pub size_len: usize,
+
+    ///
+    /// The size of the element data in bytes, or `None` if the element was written with an "Unknown Data Size".
+    ///
+    pub data_size: Option<usize>,
+}
+
+impl TagMetadata {
+
+    pub(crate) fn new(start: usize, id_len: usize, size_len: usize, size: EBMLSize) -> Self {
+        TagMetadata {
+            start,
+            header_len: id_len + size_len,
+            id_len,
+            size_len,
+            data_size: match size {
+                Known(size) => Some(size),
+                Unknown => None,
+            },
+        }
+    }
+
+    ///
+    /// Returns the offset of the first byte of the element data.
+    ///
+    pub fn data_start(&self) -> usize {
+        self.start + self.header_len
+    }
+
+    ///
+    /// Returns the offset immediately following the element, or `None` if the element size is unknown.
+    ///
+    pub fn end(&self) -> Option<usize> {
+        self.data_size.map(|size| self.data_start() + size)
+    }
+}
+
+///
+/// Resource limits that protect a [`TagIterator`][`crate::TagIterator`] against malicious or corrupted input.
+///
+/// Every limit is optional and defaults to `None` (unlimited).  When a limit is exceeded, the iterator returns a dedicated [`TagIteratorError`][`crate::error::TagIteratorError`] variant before allocating any memory for the offending tag.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::TagIteratorLimits;
+///
+/// let limits = TagIteratorLimits {
+///     max_tag_size: Some(16 * 1024 * 1024),
+///     max_depth: Some(32),
+///     ..Default::default()
+/// };
+/// ```
+///
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct TagIteratorLimits {
+
+    ///
+    /// The maximum size in bytes of the data of any non-"Master" tag that is read into memory.
+    ///
+    pub max_tag_size: Option<usize>,
+
+    ///
+    /// The maximum size in bytes of a "Master" tag that is buffered into a [`Master::Full`][`crate::specs::Master::Full`].
+    ///
+    pub max_buffered_size: Option<usize>,
+
+    ///
+    /// The maximum number of nested "Master" tags.
+    ///
+    pub max_depth: Option<usize>,
+
+    ///
+    /// The maximum number of bytes that will be read from the source.  Any tag extending past this offset causes an error.
+    ///
+    pub max_total_bytes: Option<usize>,
+}
+
+///
+/// An item emitted when streaming tags using [`TagIterator::next_streamed()`][`crate::TagIterator::next_streamed`] or [`TagIteratorAsync::next_streamed()`][`crate::TagIteratorAsync::next_streamed`].
+///
+pub enum StreamedTag<TSpec, B> {
+
+    ///
+    /// A complete tag.
+    ///
+    Tag(TSpec),
+
+    ///
+    /// A "Binary" tag whose data should be read from the contained handle.
+    ///
+    Binary(B),
+}
+
 pub enum ProcessingTag<TSpec>
-    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+    where TSpec: EbmlTag<TSpec> + Clone
 {
     EndTag {
         tag: TSpec,
         size: EBMLSize,
         start: usize,
+        meta: TagMetadata,
+        // Set for tags excluded by a tag filter.  Neither the tag nor its children are emitted.
+        hidden: bool,
     },
     NextTag {
         tag: TSpec,
+        meta: TagMetadata,
     }
 }
 
-impl<TSpec> ProcessingTag<TSpec> where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone {
+impl<TSpec> ProcessingTag<TSpec> where TSpec: EbmlTag<TSpec> + Clone {
 
-    pub fn get_id(&self) -> u64 {
+    pub fn into_inner(self) -> (TSpec, TagMetadata) {
         match self {
-            EndTag { tag,.. } => tag.get_id(),
-            NextTag { tag } => tag.get_id()
+            EndTag { tag, meta, .. } => (tag, meta),
+            NextTag { tag, meta } => (tag, meta)
         }
     }
+}
 
-    pub fn into_inner(self) -> TSpec {
-        match self {
-            EndTag { tag,.. } => tag,
-            NextTag { tag } => tag
-        }
+///
+/// Returns the number of entries at the bottom of the tag stack that have already been started from the consumer's point of view.
+///
+/// Queued tags have not been emitted yet.  A queued `Master::Start` additionally has its `EndTag` sitting directly below it, which has not been started yet either.  Tags hidden by a tag filter are never started.
+///
+pub fn started_tag_count<TSpec>(tag_stack: &[ProcessingTag<TSpec>]) -> usize
+    where TSpec: EbmlTag<TSpec> + Clone
+{
+    match tag_stack.last() {
+        Some(NextTag { tag, .. }) => if matches!(tag.as_master(), Some(Master::Start)) {
+            tag_stack.len() - 2
+        } else {
+            tag_stack.len() - 1
+        },
+        // Hidden tags are never started, and they always sit on top of any visible tags
+        _ => tag_stack.len() - tag_stack.iter().rev().take_while(|tag| matches!(tag, EndTag { hidden: true, .. })).count(),
     }
+}
 
-    pub fn inner(&self) -> &TSpec {
-        match self {
-            EndTag { tag,.. } => tag,
-            NextTag { tag } => tag
-        }
+///
+/// Returns the "Master" tags that have been started but not ended from the consumer's point of view, outermost first.
+///
+pub fn started_tags<TSpec>(tag_stack: &[ProcessingTag<TSpec>]) -> impl Iterator<Item = (&TSpec, TagMetadata)>
+    where TSpec: EbmlTag<TSpec> + Clone
+{
+    tag_stack[..started_tag_count(tag_stack)].iter().filter_map(|tag| match tag {
+        EndTag { tag, meta, .. } => Some((tag, *meta)),
+        NextTag { .. } => None,
+    })
+}
+
+///
+/// Checks whether a tag id is defined by the specification.
+///
+/// Specifications report the data type of unknown ids as binary, so binary ids are checked by trying to create a tag with the id.
+///
+pub fn is_known_tag_id<TSpec, S>(spec: &S, id: u64) -> bool
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec> + ?Sized
+{
+    !matches!(spec.get_tag_data_type(id), TagDataType::Binary) || spec.get_binary_tag(id, &[]).is_some()
+}
+
+///
+/// Returns the value of an empty numeric or "Date" element if the specification declares a default for it.
+///
+/// RFC 8794 requires empty elements to take their default value - they are only read as zero when there is no default.
+///
+pub fn empty_element_default<TSpec, S>(spec: &S, tag_id: u64, data: &[u8]) -> Option<TSpec>
+    where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec> + ?Sized
+{
+    let numeric = matches!(spec.get_tag_data_type(tag_id), TagDataType::UnsignedInt | TagDataType::Integer | TagDataType::Float | TagDataType::Date);
+    if data.is_empty() && numeric {
+        spec.get_default(tag_id)
+    } else {
+        None
     }
 }
 
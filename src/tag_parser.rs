@@ -0,0 +1,196 @@
+use crate::tag_decoder::{Decoded, TagDecoder};
+use crate::tag_iterator_util::{DEFAULT_BUFFER_LEN, TagIteratorLimits, TagMetadata};
+
+use super::specs::{EbmlSpecification, EbmlSpecificationInstance, EbmlTag, StaticSpecification};
+use super::errors::tag_iterator::TagIteratorError;
+
+///
+/// Provides a push-based parser for EBML data that arrives in chunks rather than through a source implementing [`std::io::Read`].
+///
+/// Data is handed to the parser using [`Self::feed()`] whenever it becomes available, and completed tags can be taken out using [`Self::next_tag()`] or [`Self::drain()`].  Elements that are split across chunks are kept in an internal buffer until the rest of their data has been fed.  Once the transport has ended, [`Self::finish()`] should be called so that any remaining open tags are closed (or reported as truncated).
+///
+/// The parser uses the same decoding logic as [`TagIterator`][`crate::TagIterator`], so all options behave identically.
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::TagParser;
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// let mut parser: TagParser<EmptySpec> = TagParser::new(&[]);
+/// parser.feed(&[0x1a, 0x45, 0xdf]);
+/// assert!(parser.next_tag().is_none());
+///
+/// parser.feed(&[0xa3, 0x82, 0x01, 0x02]);
+/// parser.finish();
+/// let tags: Vec<EmptySpec> = parser.drain().map(|tag| tag.unwrap()).collect();
+/// assert_eq!(1, tags.len());
+/// ```
+///
+/// ## Errors
+///
+/// The tags returned by the parser are wrapped in a [`Result<TSpec, TagIteratorError>`].  The different possible error states are enumerated in [`TagIteratorError`].
+///
+pub struct TagParser<TSpec, S = StaticSpecification<TSpec>>
+    where
+    TSpec: EbmlTag<TSpec> + Clone,
+    S: EbmlSpecificationInstance<TSpec>
+{
+    decoder: TagDecoder<'static, TSpec, S>,
+}
+
+impl<TSpec> TagParser<TSpec>
+    where
+    TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new [`TagParser<TSpec>`] instance.
+    ///
+    /// The `tags_to_buffer` parameter specifies which "Master" tags should be read as [`Master::Full`][`crate::specs::Master::Full`]s rather than as [`Master::Start`][`crate::specs::Master::Start`] and [`Master::End`][`crate::specs::Master::End`]s.
+    ///
+    pub fn new(tags_to_buffer: &[TSpec]) -> Self {
+        TagParser::with_spec(StaticSpecification::new(), tags_to_buffer)
+    }
+}
+
+impl<TSpec, S> TagParser<TSpec, S>
+    where
+    TSpec: EbmlTag<TSpec> + Clone,
+    S: EbmlSpecificationInstance<TSpec>
+{
+
+    ///
+    /// Returns a new [`TagParser`] instance that reads tags using the specification instance `spec`.
+    ///
+    /// This behaves exactly like [`TagIterator::with_spec()`][`crate::TagIterator::with_spec`].
+    ///
+    pub fn with_spec(spec: S, tags_to_buffer: &[TSpec]) -> Self {
+        TagParser {
+            decoder: TagDecoder::new(spec, tags_to_buffer, DEFAULT_BUFFER_LEN),
+        }
+    }
+
+    ///
+    /// Returns the specification instance used by the parser.
+    ///
+    pub fn spec(&self) -> &S {
+        self.decoder.spec()
+    }
+
+    ///
+    /// Enables or disables recovery from corrupted data.
+    ///
+    /// This behaves exactly like [`TagIterator::set_error_recovery()`][`crate::TagIterator::set_error_recovery`].
+    ///
+    pub fn set_error_recovery(&mut self, enabled: bool) {
+        self.decoder.set_error_recovery(enabled);
+    }
+
+    ///
+    /// Configures resource limits for the parser.
+    ///
+    /// This behaves exactly like [`TagIterator::set_limits()`][`crate::TagIterator::set_limits`].
+    ///
+    pub fn set_limits(&mut self, limits: TagIteratorLimits) {
+        self.decoder.set_limits(limits);
+    }
+
+    ///
+    /// Enables or disables filling in default values for buffered tags.
+    ///
+    /// This behaves exactly like [`TagIterator::set_fill_defaults()`][`crate::TagIterator::set_fill_defaults`].
+    ///
+    pub fn set_fill_defaults(&mut self, enabled: bool) {
+        self.decoder.set_fill_defaults(enabled);
+    }
+
+    ///
+    /// Enables or disables strict mode.
+    ///
+    /// This behaves exactly like [`TagIterator::set_strict_mode()`][`crate::TagIterator::set_strict_mode`].
+    ///
+    pub fn set_strict_mode(&mut self, enabled: bool) {
+        self.decoder.set_strict_mode(enabled);
+    }
+
+    ///
+    /// Configures a filter that decides which tags are read.
+    ///
+    /// This behaves exactly like [`TagIterator::set_tag_filter()`][`crate::TagIterator::set_tag_filter`].
+    ///
+    pub fn set_tag_filter<F>(&mut self, filter: F)
+        where F: Fn(u64, usize) -> bool + Send + Sync + 'static
+    {
+        self.decoder.set_tag_filter(Some(Box::new(filter)));
+    }
+
+    ///
+    /// Removes the filter configured using [`Self::set_tag_filter()`], so that all tags are read again.
+    ///
+    pub fn clear_tag_filter(&mut self) {
+        self.decoder.set_tag_filter(None);
+    }
+
+    ///
+    /// Returns the "Master" tags that enclose the current position of the parser, outermost first.
+    ///
+    /// This behaves exactly like [`TagIterator::ancestors()`][`crate::TagIterator::ancestors`].
+    ///
+    pub fn ancestors(&self) -> impl Iterator<Item = (&TSpec, TagMetadata)> {
+        self.decoder.ancestors()
+    }
+
+    ///
+    /// Returns the number of "Master" tags that enclose the current position of the parser.
+    ///
+    pub fn depth(&self) -> usize {
+        self.ancestors().count()
+    }
+
+    ///
+    /// Appends a chunk of data to the parser.
+    ///
+    /// The chunk does not need to align with element boundaries.
+    ///
+    pub fn feed(&mut self, data: &[u8]) {
+        self.decoder.feed(data);
+    }
+
+    ///
+    /// Signals that no more data will be fed to the parser.
+    ///
+    /// After calling this method, the remaining tags can be drained as usual.  Any "Master" tags that are still open will be ended, and an element that is only partially available will be reported as corrupted.
+    ///
+    pub fn finish(&mut self) {
+        self.decoder.finish();
+    }
+
+    ///
+    /// Returns the next completed tag along with its [`TagMetadata`], or `None` if more data is needed.
+    ///
+    pub fn next_with_meta(&mut self) -> Option<Result<(TSpec, TagMetadata), TagIteratorError>> {
+        match self.decoder.decode(false) {
+            Ok(Decoded::Tag(tag, meta)) => Some(Ok((tag, meta))),
+            Ok(Decoded::NeedData) | Ok(Decoded::Done) => None,
+            Ok(Decoded::Binary(..)) | Ok(Decoded::Data(..)) => unreachable!("Binary tags are only streamed when requested"),
+            Err(err) => Some(Err(err)),
+        }
+    }
+
+    ///
+    /// Returns the next completed tag, or `None` if more data is needed.
+    ///
+    pub fn next_tag(&mut self) -> Option<Result<TSpec, TagIteratorError>> {
+        self.next_with_meta().map(|result| result.map(|(tag, _)| tag))
+    }
+
+    ///
+    /// Returns an iterator over all tags that can be completed using the data fed so far.
+    ///
+    /// The iterator ends once more data is needed.  Feeding more data afterwards allows draining the parser again.
+    ///
+    pub fn drain(&mut self) -> impl Iterator<Item = Result<TSpec, TagIteratorError>> + '_ {
+        std::iter::from_fn(move || self.next_tag())
+    }
+}
@@ -1,8 +1,9 @@
 use std::io::Write;
 use std::convert::{TryInto, TryFrom};
 
-use super::tools::Vint;
-use super::specs::{EbmlSpecification, EbmlTag, TagDataType, Master};
+use super::tools::{self, Vint};
+use super::constraints;
+use super::specs::{EbmlSpecification, EbmlSpecificationInstance, EbmlTag, StaticSpecification, TagDataType, Master, Date};
 
 use super::errors::tag_writer::TagWriterError;
 
@@ -11,12 +12,12 @@ use super::errors::tag_writer::TagWriterError;
 ///
 /// Unlike the [`TagIterator`][`super::TagIterator`], this does not require a specification to write data. This writer provides the [`write_raw()`](#method.write_raw) method which can be used to write data that is outside of any specification.  The regular [`write()`](#method.write) method can be used to write any `TSpec` objects regardless of whether they came from a [`TagIterator`][`super::TagIterator`] or not.
 ///
-
 pub struct TagWriter<W: Write>
 {
     dest: W,
     open_tags: Vec<(u64, usize)>,
     working_buffer: Vec<u8>,
+    zero_length_zero_values: bool,
 }
 
 impl<W: Write> TagWriter<W>
@@ -31,9 +32,34 @@ impl<W: Write> TagWriter<W>
             dest,
             open_tags: Vec::new(),
             working_buffer: Vec::new(),
+            zero_length_zero_values: false,
         }
     }
 
+    ///
+    /// Enables or disables writing zero values as zero-length elements.
+    ///
+    /// RFC 8794 specifies that an "UnsignedInt", "Integer", "Float" or "Date" element with no data has its default value, or 0 if it has no default.  When enabled, the writer takes advantage of this and emits zero values of elements without a non-zero default with a size of 0 and no data, saving a few bytes per element.  This is disabled by default, since some older readers don't handle empty numeric elements.  Negative zero floats are always written in full.
+    ///
+    pub fn set_zero_length_zero_values(&mut self, enabled: bool) {
+        self.zero_length_zero_values = enabled;
+    }
+
+    fn empty_reads_as_zero<TSpec, S>(spec: &S, id: u64) -> bool
+        where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec> + ?Sized
+    {
+        // An empty element takes its default value when the specification declares one, so only a default of zero can be omitted
+        match spec.get_default(id) {
+            Some(default) => default.as_unsigned_int() == Some(&0) || default.as_signed_int() == Some(&0) || default.as_float() == Some(&0.0) || default.as_date().map(|d| d.nanos()) == Some(0),
+            None => true,
+        }
+    }
+
+    fn write_zero_length_tag(&mut self, id: u64) {
+        self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
+        self.working_buffer.push(0x80); // vint representation of "0"
+    }
+
     fn start_tag(&mut self, id: u64) {
         self.open_tags.push((id, self.working_buffer.len()));
     }
@@ -59,7 +85,12 @@ impl<W: Write> TagWriter<W>
         }
     }
 
-    fn write_unsigned_int_tag(&mut self, id: u64, data: &u64) -> Result<(), TagWriterError> {
+    fn write_unsigned_int_tag(&mut self, id: u64, data: &u64, zero_length: bool) -> Result<(), TagWriterError> {
+        if zero_length && *data == 0 {
+            self.write_zero_length_tag(id);
+            return Ok(());
+        }
+
         self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
         let data = *data;
         u8::try_from(data).map(|n| {
@@ -81,7 +112,12 @@ impl<W: Write> TagWriter<W>
         Ok(())
     }
 
-    fn write_signed_int_tag(&mut self, id: u64, data: &i64) -> Result<(), TagWriterError> {
+    fn write_signed_int_tag(&mut self, id: u64, data: &i64, zero_length: bool) -> Result<(), TagWriterError> {
+        if zero_length && *data == 0 {
+            self.write_zero_length_tag(id);
+            return Ok(());
+        }
+
         self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
         let data = *data;
         i8::try_from(data).map(|n| { 
@@ -115,6 +151,11 @@ impl<W: Write> TagWriter<W>
         Ok(())
     }
 
+    fn write_string_tag(&mut self, id: u64, data: &str) -> Result<(), TagWriterError> {
+        tools::arr_to_ascii(data.as_bytes()).map_err(|e| TagWriterError::InvalidTagData { tag_id: id, problem: e })?;
+        self.write_utf8_tag(id, data)
+    }
+
     fn write_binary_tag(&mut self, id: u64, data: &[u8]) -> Result<(), TagWriterError> {
         self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
 
@@ -126,13 +167,30 @@ impl<W: Write> TagWriter<W>
         Ok(())
     }
 
-    fn write_float_tag(&mut self, id: u64, data: &f64) -> Result<(), TagWriterError> {
+    fn write_float_tag(&mut self, id: u64, data: &f64, zero_length: bool) -> Result<(), TagWriterError> {
+        if zero_length && *data == 0.0 && data.is_sign_positive() {
+            self.write_zero_length_tag(id);
+            return Ok(());
+        }
+
         self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
         self.working_buffer.push(0x88); // vint representation of "8"
         self.working_buffer.extend_from_slice(&data.to_be_bytes());
         Ok(())
     }
 
+    fn write_date_tag(&mut self, id: u64, data: &Date, zero_length: bool) -> Result<(), TagWriterError> {
+        if zero_length && data.nanos() == 0 {
+            self.write_zero_length_tag(id);
+            return Ok(());
+        }
+
+        self.working_buffer.extend(id.to_be_bytes().iter().skip_while(|&v| *v == 0u8));
+        self.working_buffer.push(0x88); // vint representation of "8"
+        self.working_buffer.extend_from_slice(&data.nanos().to_be_bytes());
+        Ok(())
+    }
+
     ///
     /// Write a tag to this instance's destination.
     ///
@@ -166,27 +224,56 @@ impl<W: Write> TagWriter<W>
     /// ```
     ///
     pub fn write<TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone>(&mut self, tag: &TSpec) -> Result<(), TagWriterError> {
+        self.write_with_spec(tag, &StaticSpecification::<TSpec>::new())
+    }
+
+    ///
+    /// Write a tag to this instance's destination using the specification instance `spec`.
+    ///
+    /// This behaves exactly like [`Self::write()`], except that tag data types and constraints are looked up through `spec` rather than through the [`EbmlSpecification`] implementation of `TSpec`.  This allows writing tags of a specification that is only known at runtime.
+    ///
+    /// ## Errors
+    ///
+    /// This method can error if there is a problem writing the input tag.  The different possible error states are enumerated in [`TagWriterError`].
+    ///
+    /// ## Panics
+    ///
+    /// This method can panic if `spec` is internally inconsistent with the tag (i.e. it claims that a specific tag variant is a specific data type but it is not).
+    ///
+    pub fn write_with_spec<TSpec, S>(&mut self, tag: &TSpec, spec: &S) -> Result<(), TagWriterError>
+        where TSpec: EbmlTag<TSpec> + Clone, S: EbmlSpecificationInstance<TSpec> + ?Sized
+    {
         let tag_id = tag.get_id();
-        match TSpec::get_tag_data_type(tag_id) {
+        constraints::check_tag(spec, tag).map_err(|e| TagWriterError::InvalidTagData { tag_id, problem: e })?;
+        let zero_length = self.zero_length_zero_values && Self::empty_reads_as_zero(spec, tag_id);
+        match spec.get_tag_data_type(tag_id) {
             TagDataType::UnsignedInt => {
                 let val = tag.as_unsigned_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was unsigned int, but could not get tag!", tag_id));
-                self.write_unsigned_int_tag(tag_id, val)?
+                self.write_unsigned_int_tag(tag_id, val, zero_length)?
             },
             TagDataType::Integer => {
                 let val = tag.as_signed_int().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was integer, but could not get tag!", tag_id));
-                self.write_signed_int_tag(tag_id, val)?
+                self.write_signed_int_tag(tag_id, val, zero_length)?
             },
             TagDataType::Utf8 => {
                 let val = tag.as_utf8().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was utf8, but could not get tag!", tag_id));
                 self.write_utf8_tag(tag_id, val)?
             },
+            TagDataType::String => {
+                let val = tag.as_string().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was string, but could not get tag!", tag_id));
+                self.write_string_tag(tag_id, val)?
+            },
             TagDataType::Binary => {
                 let val = tag.as_binary().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was binary, but could not get tag!", tag_id));
                 self.write_binary_tag(tag_id, val)?
             },
             TagDataType::Float => {
                 let val = tag.as_float().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was float, but could not get tag!", tag_id));
-                self.write_float_tag(tag_id, val)?
+                self.write_float_tag(tag_id, val, zero_length)?
+            },
+            TagDataType::Date => {
+                let val = tag.as_date().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was date, but could not get tag!", tag_id));
+                self.write_date_tag(tag_id, val, zero_length)?
             },
             TagDataType::Master => {
                 let position = tag.as_master().unwrap_or_else(|| panic!("Bad specification implementation: Tag id {} type was master, but could not get tag!", tag_id));
@@ -197,7 +284,7 @@ impl<W: Write> TagWriter<W>
                     Master::Full(children) => {
                         self.start_tag(tag_id);
                         for child in children {
-                            self.write(child)?;
+                            self.write_with_spec(child, spec)?;
                         }
                         self.end_tag(tag_id)?;
                     }
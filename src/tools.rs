@@ -5,6 +5,7 @@
 use std::convert::TryInto;
 
 use super::errors::tool::ToolError;
+use super::specs::Date;
 
 ///
 /// Trait to enable easy serialization to a vint.
@@ -140,7 +141,7 @@ pub fn arr_to_u64(arr: &[u8]) -> Result<u64, ToolError> {
 ///
 /// Reads an `i64` value from any length array slice.
 /// 
-/// Rather than forcing the input to be a `[u8; 8]` like standard library methods, this can interpret an `i64` from a slice of any length < 8.  Bytes are assumed to be least significant when reading the value - i.e. an array of `[4, 0]` would return a value of `1024`.  
+/// Rather than forcing the input to be a `[u8; 8]` like standard library methods, this can interpret an `i64` from a slice of any length < 8.  Bytes are assumed to be least significant when reading the value - i.e. an array of `[4, 0]` would return a value of `1024`.  An empty slice is read as `0`, as required by RFC 8794 for zero-length "Integer" elements without a default value.  The readers in this crate use the default value declared by the specification instead when there is one.
 ///
 /// # Errors
 ///
@@ -162,7 +163,9 @@ pub fn arr_to_i64(arr: &[u8]) -> Result<i64, ToolError> {
         return Err(ToolError::ReadI64Overflow(Vec::from(arr)));
     }
 
-    if arr[0] > 127 {
+    if arr.is_empty() {
+        Ok(0)
+    } else if arr[0] > 127 {
         if arr.len() == 8 {
             Ok(i64::from_be_bytes(arr.try_into().expect("[u8;8] should be convertible to i64")))
         } else {
@@ -174,16 +177,18 @@ pub fn arr_to_i64(arr: &[u8]) -> Result<i64, ToolError> {
 }
 
 ///
-/// Reads an `f64` value from an array slice of length 4 or 8.
+/// Reads an `f64` value from an array slice of length 0, 4 or 8.
 /// 
-/// This method wraps `f32` and `f64` conversions from big endian byte arrays and casts the result as an `f64`.  
+/// This method wraps `f32` and `f64` conversions from big endian byte arrays and casts the result as an `f64`.  An empty slice is read as `0.0`, as required by RFC 8794 for zero-length "Float" elements without a default value.  The readers in this crate use the default value declared by the specification instead when there is one.
 ///
 /// # Errors
 ///
-/// This method will throw an error if the input slice length is not 4 or 8.
+/// This method will throw an error if the input slice length is not 0, 4 or 8.
 /// 
 pub fn arr_to_f64(arr: &[u8]) -> Result<f64, ToolError> {
-    if arr.len() == 4 {
+    if arr.is_empty() {
+        Ok(0.0)
+    } else if arr.len() == 4 {
         Ok(f32::from_be_bytes(arr.try_into().expect("arr should be [u8;4]")) as f64)
     } else if arr.len() == 8 {
         Ok(f64::from_be_bytes(arr.try_into().expect("arr should be [u8;8]")))
@@ -192,6 +197,61 @@ pub fn arr_to_f64(arr: &[u8]) -> Result<f64, ToolError> {
     }
 }
 
+///
+/// Removes null padding from the data of a "String" or "Utf8" element.
+///
+/// RFC 8794 allows string elements to be padded with 0x00 bytes, so the value of the element ends at the first null byte.
+///
+/// ## Example
+///
+/// ```
+/// # use ebml_iterable::tools::trim_null_padding;
+/// assert_eq!(b"webm", trim_null_padding(b"webm\0\0"));
+/// ```
+///
+pub fn trim_null_padding(arr: &[u8]) -> &[u8] {
+    match arr.iter().position(|b| *b == 0) {
+        Some(end) => &arr[..end],
+        None => arr,
+    }
+}
+
+///
+/// Reads a printable ascii string from an array slice.
+///
+/// Per RFC 8794, "String" elements may only contain characters in the range 0x20 to 0x7E.
+///
+/// # Errors
+///
+/// This method will throw an error if the input slice contains any byte outside of that range.
+///
+pub fn arr_to_ascii(arr: &[u8]) -> Result<&str, ToolError> {
+    if arr.iter().all(|b| (0x20..=0x7e).contains(b)) {
+        Ok(std::str::from_utf8(arr).expect("printable ascii should be valid utf8"))
+    } else {
+        Err(ToolError::InvalidAscii(Vec::from(arr)))
+    }
+}
+
+///
+/// Reads a [`Date`] value from an array slice of length 0 or 8.
+///
+/// Dates are stored as a big endian signed number of nanoseconds since 2001-01-01T00:00:00 UTC.  An empty slice represents that exact moment.
+///
+/// # Errors
+///
+/// This method will throw an error if the input slice length is not 0 or 8.
+///
+pub fn arr_to_date(arr: &[u8]) -> Result<Date, ToolError> {
+    if arr.is_empty() {
+        Ok(Date::from_nanos(0))
+    } else if arr.len() == 8 {
+        Ok(Date::from_nanos(i64::from_be_bytes(arr.try_into().expect("arr should be [u8;8]"))))
+    } else {
+        Err(ToolError::ReadDateMismatch(Vec::from(arr)))
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -251,6 +311,7 @@ mod tests {
     }
 
     #[test]
+    #[allow(clippy::bool_assert_comparison)]
     fn read_vint_overflow() {
         let buffer = [1, 0, 0, 0];
         let result = read_vint(&buffer).expect("Reading vint failed");
@@ -292,4 +353,18 @@ mod tests {
             assert_eq!(-expected, neg_result);
         }
     }
-}
\ No newline at end of file
+
+    #[test]
+    fn read_empty_values() {
+        assert_eq!(0, arr_to_u64(&[]).unwrap());
+        assert_eq!(0, arr_to_i64(&[]).unwrap());
+        assert_eq!(0.0, arr_to_f64(&[]).unwrap());
+    }
+
+    #[test]
+    fn trim_string_padding() {
+        assert_eq!(b"", trim_null_padding(b"\0\0"));
+        assert_eq!(b"ab", trim_null_padding(b"ab"));
+        assert_eq!(b"ab", trim_null_padding(b"ab\0"));
+    }
+}
@@ -0,0 +1,267 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::marker::PhantomData;
+
+use crate::tag_iterator_util::TagMetadata;
+
+use super::specs::{EbmlSpecification, EbmlTag, Master};
+use super::errors::tag_iterator::TagIteratorError;
+
+///
+/// A violation of the occurrence constraints declared by a specification (see [`EbmlSpecification::get_occurs()`]).
+///
+/// Offsets are the absolute positions reported in [`TagMetadata::start`], and are `None` for tags that were validated without positional information (such as the children of a [`Master::Full`] tag).
+///
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OccursViolation {
+
+    ///
+    /// A mandatory tag occurred fewer times than required within its parent.  Tags that have a default value are never reported as missing, since RFC 8794 treats an absent element with a default as present with that value.
+    ///
+    Missing {
+
+        ///
+        /// The id of the missing tag.
+        ///
+        tag_id: u64,
+
+        ///
+        /// The id of the "Master" tag that should contain the missing tag, or `None` for top level tags.
+        ///
+        parent_id: Option<u64>,
+
+        ///
+        /// The offset of the parent tag, or `None` for top level tags.
+        ///
+        parent_offset: Option<usize>,
+
+        ///
+        /// The number of times the tag occurred.
+        ///
+        found: u64,
+
+        ///
+        /// The minimum number of times the tag must occur.
+        ///
+        min: u64,
+    },
+
+    ///
+    /// A tag occurred more times than allowed within its parent.  This is reported once, at the first occurrence that exceeds the limit.
+    ///
+    TooMany {
+
+        ///
+        /// The id of the repeated tag.
+        ///
+        tag_id: u64,
+
+        ///
+        /// The id of the "Master" tag containing the repeated tag, or `None` for top level tags.
+        ///
+        parent_id: Option<u64>,
+
+        ///
+        /// The offset of the first occurrence that exceeds the limit.
+        ///
+        offset: Option<usize>,
+
+        ///
+        /// The maximum number of times the tag may occur.
+        ///
+        max: u64,
+    },
+}
+
+impl fmt::Display for OccursViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            OccursViolation::Missing { tag_id, parent_id: Some(parent_id), found, min, .. } => write!(f, "Tag id ({}) occurred {} times in parent id ({}), but must occur at least {} times.", tag_id, found, parent_id, min),
+            OccursViolation::Missing { tag_id, parent_id: None, found, min, .. } => write!(f, "Tag id ({}) occurred {} times at the top level, but must occur at least {} times.", tag_id, found, min),
+            OccursViolation::TooMany { tag_id, parent_id: Some(parent_id), max, .. } => write!(f, "Tag id ({}) occurred more than {} times in parent id ({}).", tag_id, max, parent_id),
+            OccursViolation::TooMany { tag_id, parent_id: None, max, .. } => write!(f, "Tag id ({}) occurred more than {} times at the top level.", tag_id, max),
+        }
+    }
+}
+
+struct Frame {
+    id: Option<u64>,
+    offset: Option<usize>,
+    counts: HashMap<u64, u64>,
+}
+
+impl Frame {
+    fn new(id: Option<u64>, offset: Option<usize>) -> Self {
+        Frame { id, offset, counts: HashMap::new() }
+    }
+}
+
+///
+/// Checks tags against the occurrence constraints declared by a specification.
+///
+/// Tags are fed to the validator in document order using [`Self::push()`], exactly as they are emitted by a [`TagIterator`][`crate::TagIterator`] - "Master" tags can be passed either as [`Master::Start`] and [`Master::End`] pairs or as [`Master::Full`] tags.  Repeated tags are reported as soon as they exceed their limit, missing mandatory children are reported when their parent is closed, and missing mandatory top level tags are reported by [`Self::finish()`].
+///
+/// ## Example
+///
+/// ```
+/// use std::io::Cursor;
+/// use ebml_iterable::{OccursValidator, TagIterator};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(Cursor::new(vec![0x42, 0x86, 0x81, 0x01]), &[]);
+/// let violations = OccursValidator::validate_stream(std::iter::from_fn(|| iter.next_with_meta()))?;
+/// assert!(violations.is_empty());
+/// # Ok(())
+/// # }
+/// ```
+///
+pub struct OccursValidator<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    stack: Vec<Frame>,
+    violations: Vec<OccursViolation>,
+    _spec: PhantomData<TSpec>,
+}
+
+impl<TSpec> Default for OccursValidator<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<TSpec> OccursValidator<TSpec>
+    where TSpec: EbmlSpecification<TSpec> + EbmlTag<TSpec> + Clone
+{
+
+    ///
+    /// Returns a new validator positioned at the top level of a document.
+    ///
+    pub fn new() -> Self {
+        OccursValidator {
+            stack: vec![Frame::new(None, None)],
+            violations: Vec::new(),
+            _spec: PhantomData,
+        }
+    }
+
+    ///
+    /// Validates a complete tag, including all children of a [`Master::Full`] tag.
+    ///
+    /// The tag itself is treated as a top level tag, so only the constraints on its descendants are checked.
+    ///
+    pub fn validate_tag(tag: &TSpec) -> Vec<OccursViolation> {
+        let mut validator = Self::new();
+        validator.push(tag, None);
+        validator.close_open_tags();
+        validator.violations
+    }
+
+    ///
+    /// Validates a stream of tags along with their [`TagMetadata`], such as the output of [`TagIterator::next_with_meta()`][`crate::TagIterator::next_with_meta`].
+    ///
+    /// ## Errors
+    ///
+    /// This method returns the first error produced by the stream.
+    ///
+    pub fn validate_stream<I>(tags: I) -> Result<Vec<OccursViolation>, TagIteratorError>
+        where I: IntoIterator<Item = Result<(TSpec, TagMetadata), TagIteratorError>>
+    {
+        let mut validator = Self::new();
+        for tag in tags {
+            let (tag, meta) = tag?;
+            validator.push(&tag, Some(meta.start));
+        }
+        Ok(validator.finish())
+    }
+
+    ///
+    /// Feeds the next tag of the document to the validator.
+    ///
+    /// `offset` is the position of the tag in the source, if known.  For [`Master::End`] tags the offset is ignored, since the offset of the matching [`Master::Start`] is reported instead.
+    ///
+    pub fn push(&mut self, tag: &TSpec, offset: Option<usize>) {
+        let tag_id = tag.get_id();
+        match tag.as_master() {
+            Some(Master::Start) => {
+                self.count(tag_id, offset);
+                self.stack.push(Frame::new(Some(tag_id), offset));
+            },
+            Some(Master::End) => {
+                // Tolerate unbalanced input by closing everything up to the matching start tag
+                if let Some(position) = self.stack.iter().rposition(|frame| frame.id == Some(tag_id)) {
+                    while self.stack.len() > position {
+                        self.close();
+                    }
+                }
+            },
+            Some(Master::Full(children)) => {
+                self.count(tag_id, offset);
+                self.stack.push(Frame::new(Some(tag_id), offset));
+                for child in children {
+                    self.push(child, None);
+                }
+                self.close();
+            },
+            None => self.count(tag_id, offset),
+        }
+    }
+
+    ///
+    /// Returns the violations found so far.  Tags that are still open are not checked for missing children until they are closed.
+    ///
+    pub fn violations(&self) -> &[OccursViolation] {
+        &self.violations
+    }
+
+    ///
+    /// Closes any tags that are still open, checks that every mandatory top level tag occurred, and returns all violations found.
+    ///
+    /// Top level tags are the tags listed by [`EbmlSpecification::get_tag_ids()`] that have no parent and are not global, so specifications that don't implement that method are not checked at the top level.
+    ///
+    pub fn finish(mut self) -> Vec<OccursViolation> {
+        self.close_open_tags();
+        let frame = self.stack.pop().expect("validator stack always contains the top level");
+        let root_ids = TSpec::get_tag_ids().iter().copied().filter(|&id| TSpec::get_parent_id(id).is_none() && TSpec::get_global_levels(id).is_none());
+        self.check_missing(&frame, root_ids);
+        self.violations
+    }
+
+    fn close_open_tags(&mut self) {
+        while self.stack.len() > 1 {
+            self.close();
+        }
+    }
+
+    fn count(&mut self, tag_id: u64, offset: Option<usize>) {
+        let frame = self.stack.last_mut().expect("validator stack always contains the top level");
+        let count = frame.counts.entry(tag_id).or_insert(0);
+        *count += 1;
+        if let Some(max) = TSpec::get_occurs(tag_id).and_then(|occurs| occurs.max) {
+            if *count == max + 1 {
+                self.violations.push(OccursViolation::TooMany { tag_id, parent_id: frame.id, offset, max });
+            }
+        }
+    }
+
+    fn close(&mut self) {
+        let frame = self.stack.pop().expect("validator stack always contains the top level");
+        let parent_id = frame.id.expect("the top level is never closed");
+        self.check_missing(&frame, TSpec::get_mandatory_children(parent_id).iter().copied());
+    }
+
+    fn check_missing(&mut self, frame: &Frame, tag_ids: impl Iterator<Item = u64>) {
+        for tag_id in tag_ids {
+            if TSpec::get_default(tag_id).is_some() {
+                continue;
+            }
+            let min = TSpec::get_occurs(tag_id).map_or(0, |occurs| occurs.min);
+            let found = frame.counts.get(&tag_id).copied().unwrap_or(0);
+            if found < min {
+                self.violations.push(OccursViolation::Missing { tag_id, parent_id: frame.id, parent_offset: frame.offset, found, min });
+            }
+        }
+    }
+}
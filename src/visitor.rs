@@ -0,0 +1,185 @@
+use crate::tag_iterator_util::{empty_element_default, is_known_tag_id};
+
+use super::tools;
+use super::specs::{Date, EbmlSpecificationInstance, EbmlTag, Master, TagDataType};
+use super::errors::tag_iterator::TagIteratorError;
+use super::errors::tool::ToolError;
+
+///
+/// Receives callbacks for each element while walking EBML data.
+///
+/// This is a push-based ("SAX-style") alternative to iterating over `TSpec` values.  Non-master elements are passed to the matching callback as borrowed data, so no spec enum variants are constructed for them.  Every callback has an empty default implementation, so implementors only need to override the ones they are interested in.
+///
+/// A visitor is driven by [`TagIterator::visit()`][`crate::TagIterator::visit`].  Elements whose ids are not part of the specification are reported through [`Self::unknown()`].
+///
+/// ## Example
+///
+/// ```
+/// use ebml_iterable::{EbmlVisitor, TagIterator};
+/// # use ebml_iterable_specification::empty_spec::EmptySpec;
+///
+/// struct ByteCounter(usize);
+///
+/// impl EbmlVisitor for ByteCounter {
+///     fn binary(&mut self, _id: u64, data: &[u8]) {
+///         self.0 += data.len();
+///     }
+/// }
+///
+/// let data: &[u8] = &[0xa3, 0x82, 0x01, 0x02];
+/// let mut iter: TagIterator<_, EmptySpec> = TagIterator::new(data, &[]);
+/// let mut counter = ByteCounter(0);
+/// iter.visit(&mut counter).unwrap();
+/// assert_eq!(2, counter.0);
+/// ```
+///
+pub trait EbmlVisitor {
+
+    ///
+    /// Called when a "Master" element starts.
+    ///
+    fn start_master(&mut self, _id: u64) {}
+
+    ///
+    /// Called when a "Master" element ends.
+    ///
+    fn end_master(&mut self, _id: u64) {}
+
+    ///
+    /// Called for each "UnsignedInt" element.
+    ///
+    fn unsigned(&mut self, _id: u64, _value: u64) {}
+
+    ///
+    /// Called for each "Integer" element.
+    ///
+    fn signed(&mut self, _id: u64, _value: i64) {}
+
+    ///
+    /// Called for each "Float" element.
+    ///
+    fn float(&mut self, _id: u64, _value: f64) {}
+
+    ///
+    /// Called for each "Utf8" element.
+    ///
+    fn utf8(&mut self, _id: u64, _value: &str) {}
+
+    ///
+    /// Called for each "String" (printable ascii) element.
+    ///
+    fn string(&mut self, _id: u64, _value: &str) {}
+
+    ///
+    /// Called for each "Date" element.
+    ///
+    fn date(&mut self, _id: u64, _value: Date) {}
+
+    ///
+    /// Called for each "Binary" element that is defined by the specification.
+    ///
+    fn binary(&mut self, _id: u64, _data: &[u8]) {}
+
+    ///
+    /// Called for each element whose id is not defined by the specification.
+    ///
+    fn unknown(&mut self, _id: u64, _data: &[u8]) {}
+}
+
+///
+/// Passes an already decoded tag to the visitor, including all children of [`Master::Full`] tags.
+///
+pub(crate) fn visit_tag<TSpec, S, V>(spec: &S, tag: &TSpec, visitor: &mut V)
+    where
+    TSpec: EbmlTag<TSpec> + Clone,
+    S: EbmlSpecificationInstance<TSpec> + ?Sized,
+    V: EbmlVisitor + ?Sized
+{
+    let id = tag.get_id();
+    if let Some(master) = tag.as_master() {
+        match master {
+            Master::Start => visitor.start_master(id),
+            Master::End => visitor.end_master(id),
+            Master::Full(children) => {
+                visitor.start_master(id);
+                for child in children {
+                    visit_tag(spec, child, visitor);
+                }
+                visitor.end_master(id);
+            },
+        }
+    } else if let Some(value) = tag.as_unsigned_int() {
+        visitor.unsigned(id, *value);
+    } else if let Some(value) = tag.as_signed_int() {
+        visitor.signed(id, *value);
+    } else if let Some(value) = tag.as_float() {
+        visitor.float(id, *value);
+    } else if let Some(value) = tag.as_utf8() {
+        visitor.utf8(id, value);
+    } else if let Some(value) = tag.as_string() {
+        visitor.string(id, value);
+    } else if let Some(value) = tag.as_date() {
+        visitor.date(id, *value);
+    } else if let Some(data) = tag.as_binary() {
+        if is_known_tag_id(spec, id) {
+            visitor.binary(id, data);
+        } else {
+            visitor.unknown(id, data);
+        }
+    }
+}
+
+///
+/// Decodes the raw data of a non-master element and passes it to the visitor.
+///
+pub(crate) fn visit_data<TSpec, S, V>(spec: &S, tag_id: u64, data: &[u8], visitor: &mut V) -> Result<(), TagIteratorError>
+    where
+    TSpec: EbmlTag<TSpec> + Clone,
+    S: EbmlSpecificationInstance<TSpec> + ?Sized,
+    V: EbmlVisitor + ?Sized
+{
+    if let Some(default) = empty_element_default(spec, tag_id, data) {
+        visit_tag(spec, &default, visitor);
+        return Ok(());
+    }
+
+    match spec.get_tag_data_type(tag_id) {
+        TagDataType::Master => unreachable!("Master tags are never passed as raw data"),
+        TagDataType::UnsignedInt => {
+            let val = tools::arr_to_u64(data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+            visitor.unsigned(tag_id, val);
+        },
+        TagDataType::Integer => {
+            let val = tools::arr_to_i64(data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+            visitor.signed(tag_id, val);
+        },
+        TagDataType::Utf8 => {
+            let text = tools::trim_null_padding(data);
+            let val = std::str::from_utf8(text).map_err(|_| {
+                let err = String::from_utf8(text.to_vec()).expect_err("data was already found to be invalid utf8");
+                TagIteratorError::CorruptedTagData{ tag_id, problem: ToolError::FromUtf8Error(text.to_vec(), err) }
+            })?;
+            visitor.utf8(tag_id, val);
+        },
+        TagDataType::String => {
+            let val = tools::arr_to_ascii(tools::trim_null_padding(data)).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+            visitor.string(tag_id, val);
+        },
+        TagDataType::Binary => {
+            if is_known_tag_id(spec, tag_id) {
+                visitor.binary(tag_id, data);
+            } else {
+                visitor.unknown(tag_id, data);
+            }
+        },
+        TagDataType::Float => {
+            let val = tools::arr_to_f64(data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+            visitor.float(tag_id, val);
+        },
+        TagDataType::Date => {
+            let val = tools::arr_to_date(data).map_err(|e| TagIteratorError::CorruptedTagData{ tag_id, problem: e })?;
+            visitor.date(tag_id, val);
+        },
+    }
+    Ok(())
+}
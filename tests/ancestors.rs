@@ -0,0 +1,93 @@
+#[cfg(feature = "derive-spec")]
+pub mod ancestors {
+    use ebml_iterable::specs::{ebml_specification, EbmlTag, TagDataType, Master};
+    use ebml_iterable::{TagIterator, TagIteratorAsync, TagWriter};
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cluster,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Cluster)]
+        Count,
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(0x01)]))).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(0x02)]))).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    fn expected_ids() -> Vec<Vec<u64>> {
+        vec![
+            vec![0x18538067],
+            vec![0x18538067, 0x1F43B675],
+            vec![0x18538067, 0x1F43B675],
+            vec![0x18538067],
+            vec![0x18538067, 0x1F43B675],
+            vec![0x18538067, 0x1F43B675],
+            vec![0x18538067],
+            vec![],
+        ]
+    }
+
+    #[test]
+    pub fn ancestors_follow_tags() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        assert_eq!(0, iter.depth());
+
+        let mut ids = Vec::new();
+        while let Some(tag) = iter.next() {
+            tag.unwrap();
+            ids.push(iter.ancestors().map(|(tag, _)| tag.get_id()).collect::<Vec<u64>>());
+            assert_eq!(ids.last().unwrap().len(), iter.depth());
+        }
+        assert_eq!(expected_ids(), ids);
+    }
+
+    #[test]
+    pub fn ancestors_include_metadata() {
+        let data = get_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        let (_, segment_meta) = iter.next_with_meta().unwrap().unwrap();
+        let (_, cluster_meta) = iter.next_with_meta().unwrap().unwrap();
+
+        let ancestors: Vec<(TestSpec, _)> = iter.ancestors().map(|(tag, meta)| (tag.clone(), meta)).collect();
+        assert_eq!(vec![
+            (TestSpec::Segment(Master::End), segment_meta),
+            (TestSpec::Cluster(Master::End), cluster_meta),
+        ], ancestors);
+    }
+
+    #[test]
+    pub fn ancestors_follow_tags_async() {
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(futures::io::Cursor::new(get_data()), &[]);
+        assert_eq!(0, iter.depth());
+
+        let mut ids = Vec::new();
+        futures::executor::block_on(async {
+            while let Some(tag) = iter.next().await {
+                tag.unwrap();
+                ids.push(iter.ancestors().map(|(tag, _)| tag.get_id()).collect::<Vec<u64>>());
+                assert_eq!(ids.last().unwrap().len(), iter.depth());
+            }
+        });
+        assert_eq!(expected_ids(), ids);
+    }
+}
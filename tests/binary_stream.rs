@@ -0,0 +1,114 @@
+#[cfg(feature = "derive-spec")]
+pub mod binary_stream {
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::{StreamedTag, TagIterator, TagIteratorAsync, TagWriter};
+    use futures::AsyncReadExt;
+    use std::io::{Cursor, Read};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        Count,
+
+        #[id(0xa1)]
+        #[data_type(TagDataType::Binary)]
+        Block,
+    }
+
+    fn get_payload() -> Vec<u8> {
+        (0..0x20000u32).map(|i| (i % 251) as u8).collect()
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Block(vec![0x01, 0x02])).expect("Error writing tag");
+        writer.write(&TestSpec::Block(get_payload())).expect("Error writing tag");
+        writer.write(&TestSpec::Count(0x07)).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    #[test]
+    pub fn stream_large_binary() {
+        let mut src = Cursor::new(get_data());
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(&mut src, &[], 1024);
+        iter.set_binary_stream_threshold(Some(1024));
+
+        let mut tags = Vec::new();
+        let mut payload = Vec::new();
+        while let Some(tag) = iter.next_streamed() {
+            match tag.unwrap() {
+                StreamedTag::Tag(tag) => tags.push(tag),
+                StreamedTag::Binary(mut reader) => {
+                    assert_eq!(0xa1, reader.id());
+                    assert_eq!(0x20000, reader.remaining());
+                    reader.read_to_end(&mut payload).expect("Reading payload should succeed");
+                },
+            }
+        }
+
+        assert_eq!(get_payload(), payload);
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Block(vec![0x01, 0x02]),
+            TestSpec::Count(0x07),
+            TestSpec::Segment(Master::End),
+        ], tags);
+    }
+
+    #[test]
+    pub fn drop_partially_read_stream() {
+        let mut src = Cursor::new(get_data());
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(&mut src, &[], 1024);
+        iter.set_binary_stream_threshold(Some(1024));
+
+        let mut tags = Vec::new();
+        while let Some(tag) = iter.next_streamed() {
+            match tag.unwrap() {
+                StreamedTag::Tag(tag) => tags.push(tag),
+                StreamedTag::Binary(mut reader) => {
+                    let mut start = [0u8; 4000];
+                    reader.read_exact(&mut start).expect("Reading payload should succeed");
+                    assert_eq!(&get_payload()[..4000], &start[..]);
+                },
+            }
+        }
+
+        assert_eq!(4, tags.len());
+        assert_eq!(TestSpec::Count(0x07), tags[2]);
+    }
+
+    #[test]
+    pub fn stream_large_binary_async() {
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(futures::io::Cursor::new(get_data()), &[]);
+        iter.set_binary_stream_threshold(Some(1024));
+
+        let mut tags = Vec::new();
+        let mut payload = Vec::new();
+        futures::executor::block_on(async {
+            while let Some(tag) = iter.next_streamed().await {
+                match tag.unwrap() {
+                    StreamedTag::Tag(tag) => tags.push(tag),
+                    StreamedTag::Binary(mut reader) => {
+                        let mut start = [0u8; 10];
+                        reader.read_exact(&mut start).await.expect("Reading payload should succeed");
+                        payload.extend_from_slice(&start);
+                    },
+                }
+            }
+        });
+
+        assert_eq!(&get_payload()[..10], &payload[..]);
+        assert_eq!(4, tags.len());
+        assert_eq!(TestSpec::Count(0x07), tags[2]);
+    }
+}
@@ -0,0 +1,157 @@
+#[cfg(feature = "derive-spec")]
+pub mod constraints {
+    use ebml_iterable::specs::{easy_ebml, ebml_specification, EbmlSpecification, TagDataType, Master, ValueRange};
+    use ebml_iterable::error::{TagIteratorError, TagWriterError, ToolError};
+    use ebml_iterable::{EbmlVisitor, TagIterator, TagIteratorAsync, TagWriter};
+    use std::io::Cursor;
+    use std::ops::Bound;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x1549a966)]
+        #[data_type(TagDataType::Master)]
+        Info,
+
+        #[id(0x2ad7b1)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Info)]
+        #[range(1..)]
+        TimestampScale,
+
+        #[id(0x75a2)]
+        #[data_type(TagDataType::Integer)]
+        #[parent(Info)]
+        #[range(-10..=10)]
+        Offset,
+
+        #[id(0x4489)]
+        #[data_type(TagDataType::Float)]
+        #[parent(Info)]
+        #[range(> 0.0)]
+        Duration,
+
+        #[id(0x7ba9)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Info)]
+        #[length(..4)]
+        Title,
+
+        #[id(0x73a4)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(Info)]
+        #[length(16)]
+        SegmentUid,
+    }
+
+    easy_ebml! {
+        #[derive(Clone, Debug, PartialEq)]
+        pub enum EasySpec {
+            Ebml: Master = 0x1a45dfa3,
+            #[range(1..=1)]
+            Ebml/EbmlVersion: UnsignedInt = 0x4286,
+            #[length(1..)]
+            #[default("webm")]
+            Ebml/DocType: String = 0x4282,
+        }
+    }
+
+    fn write_one(tag: &TestSpec) -> Result<Vec<u8>, TagWriterError> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(tag)?;
+        drop(writer);
+        Ok(dest.into_inner())
+    }
+
+    fn get_invalid_data() -> Vec<u8> {
+        let mut data = Vec::new();
+        data.extend_from_slice(&[0x2a, 0xd7, 0xb1, 0x81, 0x00]); // TimestampScale(0)
+        data.extend_from_slice(&[0x75, 0xa2, 0x81, 0x05]); // Offset(5)
+        data.extend_from_slice(&[0x7b, 0xa9, 0x84, 0x61, 0x62, 0x63, 0x64]); // Title("abcd")
+        data
+    }
+
+    #[test]
+    pub fn spec_ranges() {
+        assert_eq!(Some(ValueRange::new(Bound::Included(1), Bound::Unbounded)), TestSpec::get_unsigned_int_range(0x2ad7b1));
+        assert_eq!(Some(ValueRange::new(Bound::Included(-10), Bound::Included(10))), TestSpec::get_signed_int_range(0x75a2));
+        assert_eq!(Some(ValueRange::new(Bound::Excluded(0.0), Bound::Unbounded)), TestSpec::get_float_range(0x4489));
+        assert_eq!(Some(ValueRange::new(Bound::Unbounded, Bound::Excluded(4))), TestSpec::get_length_range(0x7ba9));
+        assert_eq!(Some(ValueRange::new(Bound::Included(16), Bound::Included(16))), TestSpec::get_length_range(0x73a4));
+        assert_eq!(None, TestSpec::get_unsigned_int_range(0x75a2));
+        assert_eq!(None, TestSpec::get_length_range(0x2ad7b1));
+    }
+
+    #[test]
+    pub fn easy_ebml_ranges() {
+        assert_eq!(Some(ValueRange::new(Bound::Included(1), Bound::Included(1))), EasySpec::get_unsigned_int_range(0x4286));
+        assert_eq!(Some(ValueRange::new(Bound::Included(1), Bound::Unbounded)), EasySpec::get_length_range(0x4282));
+        assert_eq!(Some(EasySpec::DocType(String::from("webm"))), EasySpec::get_default(0x4282));
+    }
+
+    #[test]
+    pub fn range_display() {
+        assert_eq!("1-", TestSpec::get_unsigned_int_range(0x2ad7b1).unwrap().to_string());
+        assert_eq!(">=-10,<=10", TestSpec::get_signed_int_range(0x75a2).unwrap().to_string());
+        assert_eq!(">=-5", ValueRange::new(Bound::Included(-5), Bound::Unbounded).to_string());
+        assert_eq!("-5", ValueRange::new(Bound::Included(-5), Bound::Included(-5)).to_string());
+        assert_eq!("<4", TestSpec::get_length_range(0x7ba9).unwrap().to_string());
+        assert_eq!("16", TestSpec::get_length_range(0x73a4).unwrap().to_string());
+        assert_eq!(">=1,<5", ValueRange::new(Bound::Included(1), Bound::Excluded(5)).to_string());
+    }
+
+    #[test]
+    pub fn writer_rejects_out_of_range_values() {
+        assert!(matches!(write_one(&TestSpec::TimestampScale(0)), Err(TagWriterError::InvalidTagData { tag_id: 0x2ad7b1, problem: ToolError::ValueOutOfRange { .. } })));
+        assert!(matches!(write_one(&TestSpec::Offset(11)), Err(TagWriterError::InvalidTagData { tag_id: 0x75a2, problem: ToolError::ValueOutOfRange { .. } })));
+        assert!(matches!(write_one(&TestSpec::Duration(0.0)), Err(TagWriterError::InvalidTagData { tag_id: 0x4489, problem: ToolError::ValueOutOfRange { .. } })));
+        assert!(matches!(write_one(&TestSpec::Title(String::from("abcd"))), Err(TagWriterError::InvalidTagData { tag_id: 0x7ba9, problem: ToolError::LengthOutOfRange { length: 4, .. } })));
+        assert!(matches!(write_one(&TestSpec::SegmentUid(vec![0; 15])), Err(TagWriterError::InvalidTagData { tag_id: 0x73a4, problem: ToolError::LengthOutOfRange { length: 15, .. } })));
+        assert!(write_one(&TestSpec::Info(Master::Full(vec![TestSpec::Offset(-10), TestSpec::Duration(0.5), TestSpec::SegmentUid(vec![0; 16])]))).is_ok());
+        assert!(write_one(&TestSpec::Info(Master::Full(vec![TestSpec::Offset(-11)]))).is_err());
+    }
+
+    #[test]
+    pub fn iterator_ignores_ranges_by_default() {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_invalid_data()), &[]);
+        assert!(iter.map(|t| t.unwrap()).eq(vec![TestSpec::TimestampScale(0), TestSpec::Offset(5), TestSpec::Title(String::from("abcd"))]));
+    }
+
+    #[test]
+    pub fn strict_mode() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_invalid_data()), &[]);
+        iter.set_strict_mode(true);
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::InvalidTagData { tag_id: 0x2ad7b1, problem: ToolError::ValueOutOfRange { .. } }))));
+        assert_eq!(TestSpec::Offset(5), iter.next().unwrap().unwrap());
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::InvalidTagData { tag_id: 0x7ba9, problem: ToolError::LengthOutOfRange { length: 4, .. } }))));
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    pub fn strict_mode_async() {
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(futures::io::Cursor::new(get_invalid_data()), &[]);
+        iter.set_strict_mode(true);
+        let results = futures::executor::block_on(async {
+            let mut results = Vec::new();
+            while let Some(tag) = iter.next().await {
+                results.push(tag);
+            }
+            results
+        });
+        assert_eq!(3, results.len());
+        assert!(matches!(results[0], Err(TagIteratorError::InvalidTagData { tag_id: 0x2ad7b1, .. })));
+        assert!(results[1].is_ok());
+        assert!(matches!(results[2], Err(TagIteratorError::InvalidTagData { tag_id: 0x7ba9, .. })));
+    }
+
+    #[test]
+    pub fn strict_mode_visitor() {
+        struct Nothing;
+        impl EbmlVisitor for Nothing {}
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_invalid_data()), &[]);
+        iter.set_strict_mode(true);
+        assert!(matches!(iter.visit(&mut Nothing), Err(TagIteratorError::InvalidTagData { tag_id: 0x2ad7b1, .. })));
+    }
+}
@@ -0,0 +1,122 @@
+#[cfg(feature = "derive-spec")]
+pub mod date {
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecification, EbmlTag, TagDataType, Master, Date};
+    use ebml_iterable::error::{TagIteratorError, ToolError};
+    use ebml_iterable::{BorrowedTagData, EbmlVisitor, TagIterator, TagIteratorAsync, TagIteratorSlice, TagWriter};
+    use std::io::Cursor;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x1549a966)]
+        #[data_type(TagDataType::Master)]
+        Info,
+
+        #[id(0x4461)]
+        #[data_type(TagDataType::Date)]
+        #[parent(Info)]
+        DateUtc,
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Info(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::DateUtc(Date::from_nanos(-1_500_000_000))).expect("Error writing tag");
+        writer.write(&TestSpec::DateUtc(Date::from_nanos(1_000))).expect("Error writing tag");
+        writer.write(&TestSpec::Info(Master::End)).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    fn expected_tags() -> Vec<TestSpec> {
+        vec![
+            TestSpec::Info(Master::Start),
+            TestSpec::DateUtc(Date::from_nanos(-1_500_000_000)),
+            TestSpec::DateUtc(Date::from_nanos(1_000)),
+            TestSpec::Info(Master::End),
+        ]
+    }
+
+    #[test]
+    pub fn derive_date_tags() {
+        assert_eq!(TagDataType::Date, TestSpec::get_tag_data_type(0x4461));
+        let tag = TestSpec::get_date_tag(0x4461, Date::from_nanos(5)).unwrap();
+        assert_eq!(Some(&Date::from_nanos(5)), tag.as_date());
+        assert_eq!(None, TestSpec::get_date_tag(0x1549a966, Date::from_nanos(5)));
+        assert_eq!(None, TestSpec::Info(Master::Start).as_date());
+    }
+
+    #[test]
+    pub fn write_date() {
+        let data = get_data();
+        assert_eq!(&[0x44, 0x61, 0x88, 0xff, 0xff, 0xff, 0xff, 0xa6, 0x97, 0xd1, 0x00], &data[5..16]);
+    }
+
+    #[test]
+    pub fn read_date() {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(expected_tags(), tags);
+    }
+
+    #[test]
+    pub fn read_date_async() {
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(futures::io::Cursor::new(get_data()), &[]);
+        let mut tags = Vec::new();
+        futures::executor::block_on(async {
+            while let Some(tag) = iter.next().await {
+                tags.push(tag.unwrap());
+            }
+        });
+        assert_eq!(expected_tags(), tags);
+    }
+
+    #[test]
+    pub fn read_date_slice() {
+        let data = get_data();
+        let iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[]);
+        let tags: Vec<_> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(BorrowedTagData::Date(Date::from_nanos(-1_500_000_000)), tags[1].data);
+        assert_eq!(expected_tags(), tags.iter().map(|tag| tag.to_tag()).collect::<Vec<TestSpec>>());
+    }
+
+    #[test]
+    pub fn visit_date() {
+        struct Dates(Vec<Date>);
+        impl EbmlVisitor for Dates {
+            fn date(&mut self, _id: u64, value: Date) {
+                self.0.push(value);
+            }
+        }
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[]);
+        let mut dates = Dates(Vec::new());
+        iter.visit(&mut dates).unwrap();
+        assert_eq!(vec![Date::from_nanos(-1_500_000_000), Date::from_nanos(1_000)], dates.0);
+    }
+
+    #[test]
+    pub fn empty_date_is_epoch() {
+        let data = vec![0x44, 0x61, 0x80];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        assert_eq!(TestSpec::DateUtc(Date::from_nanos(0)), iter.next().unwrap().unwrap());
+    }
+
+    #[test]
+    pub fn invalid_date_length() {
+        let data = vec![0x44, 0x61, 0x84, 0x00, 0x00, 0x00, 0x01];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::CorruptedTagData { tag_id: 0x4461, problem: ToolError::ReadDateMismatch(_) }))));
+    }
+
+    #[test]
+    pub fn system_time_conversion() {
+        let epoch = UNIX_EPOCH + Duration::from_secs(978_307_200);
+        assert_eq!(epoch, Date::from_nanos(0).to_system_time());
+        assert_eq!(epoch - Duration::from_millis(1_500), Date::from_nanos(-1_500_000_000).to_system_time());
+        assert_eq!(Some(Date::from_nanos(-1_500_000_000)), Date::from_system_time(epoch - Duration::from_millis(1_500)));
+        assert_eq!(None, Date::from_system_time(epoch + Duration::from_secs(300 * 365 * 24 * 60 * 60)));
+    }
+}
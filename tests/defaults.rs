@@ -0,0 +1,210 @@
+#[cfg(feature = "derive-spec")]
+pub mod defaults {
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecification, TagDataType, Master, Date};
+    use ebml_iterable::{EbmlVisitor, TagIterator, TagIteratorAsync, TagIteratorSlice, TagWriter};
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x1549a966)]
+        #[data_type(TagDataType::Master)]
+        Info,
+
+        #[id(0x2ad7b1)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Info)]
+        #[default(1000000)]
+        TimestampScale,
+
+        #[id(0x75a2)]
+        #[data_type(TagDataType::Integer)]
+        #[parent(Info)]
+        #[default(-1)]
+        Offset,
+
+        #[id(0x4489)]
+        #[data_type(TagDataType::Float)]
+        #[parent(Info)]
+        #[default(1.5)]
+        Duration,
+
+        #[id(0x7ba9)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Info)]
+        #[default("untitled")]
+        Title,
+
+        #[id(0x4282)]
+        #[data_type(TagDataType::String)]
+        #[parent(Info)]
+        #[default("webm")]
+        DocType,
+
+        #[id(0x4461)]
+        #[data_type(TagDataType::Date)]
+        #[parent(Info)]
+        #[default(5)]
+        DateUtc,
+
+        #[id(0x73a4)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(Info)]
+        #[default([0x01, 0x02])]
+        SegmentUid,
+
+        #[id(0x4d80)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Info)]
+        MuxingApp,
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Info(Master::Full(vec![
+            TestSpec::MuxingApp(String::from("app")),
+            TestSpec::TimestampScale(1),
+            TestSpec::Title(String::from("title")),
+        ]))).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    fn filled_info() -> TestSpec {
+        TestSpec::Info(Master::Full(vec![
+            TestSpec::MuxingApp(String::from("app")),
+            TestSpec::TimestampScale(1),
+            TestSpec::Title(String::from("title")),
+            TestSpec::Offset(-1),
+            TestSpec::Duration(1.5),
+            TestSpec::DocType(String::from("webm")),
+            TestSpec::DateUtc(Date::from_nanos(5)),
+            TestSpec::SegmentUid(vec![0x01, 0x02]),
+        ]))
+    }
+
+    #[test]
+    pub fn spec_defaults() {
+        assert_eq!(Some(TestSpec::TimestampScale(1000000)), TestSpec::get_default(0x2ad7b1));
+        assert_eq!(Some(TestSpec::Offset(-1)), TestSpec::get_default(0x75a2));
+        assert_eq!(Some(TestSpec::Title(String::from("untitled"))), TestSpec::get_default(0x7ba9));
+        assert_eq!(Some(TestSpec::SegmentUid(vec![0x01, 0x02])), TestSpec::get_default(0x73a4));
+        assert_eq!(None, TestSpec::get_default(0x4d80));
+        assert_eq!(None, TestSpec::get_default(0x1549a966));
+
+        assert_eq!(&[0x2ad7b1, 0x75a2, 0x4489, 0x7ba9, 0x4282, 0x4461, 0x73a4], TestSpec::get_children_with_defaults(0x1549a966));
+        assert!(TestSpec::get_children_with_defaults(0x2ad7b1).is_empty());
+    }
+
+    #[test]
+    pub fn defaults_not_filled_by_default() {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[TestSpec::Info(Master::Start)]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Info(Master::Full(vec![
+            TestSpec::MuxingApp(String::from("app")),
+            TestSpec::TimestampScale(1),
+            TestSpec::Title(String::from("title")),
+        ]))], tags);
+    }
+
+    #[test]
+    pub fn fill_defaults() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[TestSpec::Info(Master::Start)]);
+        iter.set_fill_defaults(true);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(vec![filled_info()], tags);
+    }
+
+    #[test]
+    pub fn fill_defaults_async() {
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(futures::io::Cursor::new(get_data()), &[TestSpec::Info(Master::Start)]);
+        iter.set_fill_defaults(true);
+        let mut tags = Vec::new();
+        futures::executor::block_on(async {
+            while let Some(tag) = iter.next().await {
+                tags.push(tag.unwrap());
+            }
+        });
+        assert_eq!(vec![filled_info()], tags);
+    }
+
+    #[test]
+    pub fn read_empty_numbers_as_default() {
+        let data = vec![0x2a, 0xd7, 0xb1, 0x80, 0x75, 0xa2, 0x80, 0x44, 0x89, 0x80, 0x44, 0x61, 0x80];
+        let expected = vec![
+            TestSpec::TimestampScale(1000000),
+            TestSpec::Offset(-1),
+            TestSpec::Duration(1.5),
+            TestSpec::DateUtc(Date::from_nanos(5)),
+        ];
+
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(expected, tags);
+
+        let iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap().to_tag()).collect();
+        assert_eq!(expected, tags);
+    }
+
+    #[test]
+    pub fn visit_empty_numbers_as_default() {
+        #[derive(Default)]
+        struct Numbers(Vec<f64>);
+        impl EbmlVisitor for Numbers {
+            fn unsigned(&mut self, _id: u64, value: u64) {
+                self.0.push(value as f64);
+            }
+            fn signed(&mut self, _id: u64, value: i64) {
+                self.0.push(value as f64);
+            }
+            fn float(&mut self, _id: u64, value: f64) {
+                self.0.push(value);
+            }
+        }
+
+        let data = vec![0x2a, 0xd7, 0xb1, 0x80, 0x75, 0xa2, 0x80, 0x44, 0x89, 0x80];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        let mut numbers = Numbers::default();
+        iter.visit(&mut numbers).unwrap();
+        assert_eq!(vec![1000000.0, -1.0, 1.5], numbers.0);
+    }
+
+    #[test]
+    pub fn zero_values_with_defaults_round_trip() {
+        let tags = vec![
+            TestSpec::TimestampScale(0),
+            TestSpec::Offset(0),
+            TestSpec::Duration(0.0),
+            TestSpec::DateUtc(Date::from_nanos(0)),
+        ];
+
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.set_zero_length_zero_values(true);
+        for tag in tags.iter() {
+            writer.write(tag).expect("Error writing tag");
+        }
+        drop(writer);
+
+        let data = dest.into_inner();
+        assert_eq!(vec![
+            0x2a, 0xd7, 0xb1, 0x81, 0x00,
+            0x75, 0xa2, 0x81, 0x00,
+            0x44, 0x89, 0x88, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x44, 0x61, 0x88, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        ], data);
+
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        let read: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(tags, read);
+    }
+
+    #[test]
+    pub fn unbuffered_masters_are_not_filled() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[]);
+        iter.set_fill_defaults(true);
+        assert_eq!(5, iter.count());
+    }
+}
@@ -0,0 +1,145 @@
+#[cfg(feature = "derive-spec")]
+pub mod document {
+    use ebml_iterable::specs::{ebml_specification, EbmlTag, TagDataType, Master};
+    use ebml_iterable::error::DocumentError;
+    use ebml_iterable::{EbmlDocument, TagIterator, TagWriter};
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cluster,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Cluster)]
+        Count,
+
+        #[id(0x4103)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Segment)]
+        Title,
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::Title(String::from("a")),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)])),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)])),
+        ]))).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    fn read_all(data: Vec<u8>) -> Vec<TestSpec> {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[TestSpec::Segment(Master::Start)]);
+        iter.map(|t| t.unwrap()).collect()
+    }
+
+    #[test]
+    pub fn round_trip() {
+        let document: EbmlDocument<TestSpec> = EbmlDocument::read(Cursor::new(get_data())).unwrap();
+        let mut output = Vec::new();
+        document.write(&mut output).unwrap();
+        assert_eq!(get_data(), output);
+    }
+
+    #[test]
+    pub fn navigate() {
+        let document: EbmlDocument<TestSpec> = EbmlDocument::read(Cursor::new(get_data())).unwrap();
+        assert_eq!(1, document.roots().len());
+
+        let segment = document.roots()[0];
+        assert_eq!(TestSpec::Segment(Master::Start), *document.tag(segment));
+        assert_eq!(3, document.children(segment).len());
+
+        let cluster = document.find_child(segment, 0x1F43B675).unwrap();
+        assert_eq!(Some(segment), document.parent(cluster));
+        let count = document.children(cluster)[0];
+        assert_eq!(TestSpec::Count(1), *document.tag(count));
+        assert_eq!(Some(cluster), document.parent(count));
+        assert_eq!(None, document.parent(segment));
+
+        let ids: Vec<u64> = document.iter().map(|node| document.tag(node).get_id()).collect();
+        assert_eq!(vec![0x18538067, 0x4103, 0x1F43B675, 0x4100, 0x1F43B675, 0x4100], ids);
+        assert_eq!(vec![cluster, count], document.descendants(cluster).collect::<Vec<_>>());
+    }
+
+    #[test]
+    pub fn modify_and_write() {
+        let mut document: EbmlDocument<TestSpec> = EbmlDocument::read(Cursor::new(get_data())).unwrap();
+        let segment = document.roots()[0];
+        let title = document.children(segment)[0];
+        let first_cluster = document.children(segment)[1];
+        let second_cluster = document.children(segment)[2];
+
+        assert_eq!(TestSpec::Title(String::from("a")), document.set_tag(title, TestSpec::Title(String::from("longer title"))).unwrap());
+        let removed = document.remove(first_cluster);
+        assert_eq!(TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)])), removed);
+        document.append(Some(second_cluster), TestSpec::Count(3)).unwrap();
+        document.insert_before(second_cluster, TestSpec::Cluster(Master::Full(vec![TestSpec::Count(4), TestSpec::Count(5)])));
+
+        let mut output = Vec::new();
+        document.write(&mut output).unwrap();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Full(vec![
+                TestSpec::Title(String::from("longer title")),
+                TestSpec::Cluster(Master::Full(vec![TestSpec::Count(4), TestSpec::Count(5)])),
+                TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2), TestSpec::Count(3)])),
+            ]))
+        ], read_all(output));
+    }
+
+    #[test]
+    pub fn children_require_master() {
+        let mut document: EbmlDocument<TestSpec> = EbmlDocument::new();
+        let title = document.append(None, TestSpec::Title(String::from("a"))).unwrap();
+        assert!(matches!(document.append(Some(title), TestSpec::Count(1)), Err(DocumentError::NotAMaster { tag_id: 0x4103 })));
+    }
+
+    #[test]
+    pub fn build_from_tags() {
+        let tags = vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)])),
+            TestSpec::Segment(Master::End),
+        ];
+        let document = EbmlDocument::from_tags(tags.into_iter().map(Ok)).unwrap();
+        let segment = document.roots()[0];
+        assert_eq!(TestSpec::Segment(Master::Full(vec![
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)])),
+        ])), document.to_tag(segment));
+
+        let mismatched = vec![TestSpec::Segment(Master::Start), TestSpec::Cluster(Master::End)];
+        assert!(EbmlDocument::from_tags(mismatched.into_iter().map(Ok)).is_err());
+
+        let unclosed = vec![TestSpec::Segment(Master::Start), TestSpec::Cluster(Master::Start), TestSpec::Cluster(Master::End)];
+        assert!(EbmlDocument::from_tags(unclosed.into_iter().map(Ok)).is_err());
+    }
+
+    #[test]
+    pub fn set_tag_keeps_data_type() {
+        let mut document: EbmlDocument<TestSpec> = EbmlDocument::read(Cursor::new(get_data())).unwrap();
+        let segment = document.roots()[0];
+        let title = document.children(segment)[0];
+        let cluster = document.children(segment)[1];
+
+        assert!(matches!(document.set_tag(cluster, TestSpec::Count(1)), Err(DocumentError::DataTypeMismatch { tag_id: 0x4100, expected: TagDataType::Master, found: TagDataType::UnsignedInt })));
+        assert!(matches!(document.set_tag(title, TestSpec::Segment(Master::Start)), Err(DocumentError::DataTypeMismatch { tag_id: 0x18538067, .. })));
+        assert_eq!(TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1)])), document.to_tag(cluster));
+
+        let count = document.children(cluster)[0];
+        assert_eq!(TestSpec::Cluster(Master::Start), document.set_tag(cluster, TestSpec::Cluster(Master::Full(vec![TestSpec::Count(7), TestSpec::Count(8)]))).unwrap());
+        assert_eq!(TestSpec::Cluster(Master::Full(vec![TestSpec::Count(7), TestSpec::Count(8)])), document.to_tag(cluster));
+        assert!(!document.descendants(segment).any(|node| node == count));
+    }
+}
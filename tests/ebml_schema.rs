@@ -0,0 +1,86 @@
+#[cfg(all(feature = "derive-spec", feature = "schema"))]
+pub mod ebml_schema {
+    use ebml_iterable::specs::{ebml_schema, EbmlSpecification, EbmlTag, GlobalLevels, TagDataType, Master, Date, Occurs, ValueRange};
+    use ebml_iterable::{TagIterator, TagWriter};
+    use std::io::Cursor;
+    use std::ops::Bound;
+
+    ebml_schema!("tests/schemas/test.xml");
+
+    ebml_schema! {
+        #[derive(Clone, Debug, PartialEq)]
+        pub(crate) enum NamedSpec = "tests/schemas/test.xml";
+    }
+
+    #[test]
+    pub fn variant_names_and_types() {
+        assert_eq!(TagDataType::Master, TestSpec::get_tag_data_type(0x1a45dfa3));
+        assert_eq!(Some(TestSpec::EbmlMaxIdLength(4)), TestSpec::get_unsigned_int_tag(0x42f2, 4));
+        assert_eq!(Some(TestSpec::DocType(String::from("a"))), TestSpec::get_string_tag(0x4282, String::from("a")));
+        assert_eq!(Some(TestSpec::DateUtc(Date::from_nanos(1))), TestSpec::get_date_tag(0x4461, Date::from_nanos(1)));
+        assert_eq!(Some(TestSpec::Crc32(vec![0; 4])), TestSpec::get_binary_tag(0xbf, &[0; 4]));
+        assert_eq!(Some(TestSpec::SegmentUuid(vec![0; 16])), TestSpec::get_binary_tag(0x73a4, &[0; 16]));
+        assert_eq!(TestSpec::get_tag_data_type(0x4489), NamedSpec::get_tag_data_type(0x4489));
+    }
+
+    #[test]
+    pub fn parents_from_paths() {
+        let info = TestSpec::Info(Master::Start);
+        assert!(info.is_child(0x4489));
+        assert!(info.is_child(0xbf));
+        assert!(info.is_child(0xec));
+        assert!(!info.is_child(0x1254c367));
+        assert!(!info.is_child(0x18538067));
+        assert!(!info.is_child(0x1a45dfa3));
+        assert_eq!(&[0x4286, 0x42f2, 0x4282], TestSpec::get_mandatory_children(0x1a45dfa3));
+        assert_eq!(Some(GlobalLevels::new(1, None)), TestSpec::get_global_levels(0xbf));
+        assert_eq!(Some(GlobalLevels::ANY), TestSpec::get_global_levels(0xec));
+        assert_eq!(None, TestSpec::get_global_levels(0x4489));
+    }
+
+    #[test]
+    pub fn defaults_ranges_and_occurs() {
+        assert_eq!(Some(TestSpec::EbmlVersion(1)), TestSpec::get_default(0x4286));
+        assert_eq!(Some(TestSpec::DocType(String::from("test"))), TestSpec::get_default(0x4282));
+        assert_eq!(Some(TestSpec::Gain(0.5)), TestSpec::get_default(0x4490));
+        assert_eq!(Some(TestSpec::Offset(-2)), TestSpec::get_default(0x75a2));
+        assert_eq!(Some(TestSpec::Title(String::from("untitled"))), TestSpec::get_default(0x7ba9));
+        assert_eq!(Some(TestSpec::DateUtc(Date::from_nanos(5))), TestSpec::get_default(0x4461));
+
+        assert_eq!(Some(ValueRange::new(Bound::Excluded(0), Bound::Unbounded)), TestSpec::get_unsigned_int_range(0x4286));
+        assert_eq!(Some(ValueRange::new(Bound::Included(4), Bound::Included(4))), TestSpec::get_unsigned_int_range(0x42f2));
+        assert_eq!(Some(ValueRange::new(Bound::Excluded(0.0), Bound::Unbounded)), TestSpec::get_float_range(0x4489));
+        assert_eq!(Some(ValueRange::new(Bound::Included(-2.0), Bound::Excluded(3.0))), TestSpec::get_float_range(0x4490));
+        assert_eq!(Some(ValueRange::new(Bound::Included(-10), Bound::Included(10))), TestSpec::get_signed_int_range(0x75a2));
+        assert_eq!(Some(ValueRange::new(Bound::Included(1), Bound::Unbounded)), TestSpec::get_length_range(0x4282));
+        assert_eq!(Some(ValueRange::new(Bound::Included(4), Bound::Included(4))), TestSpec::get_length_range(0xbf));
+
+        assert_eq!(Some(Occurs::new(1, Some(1))), TestSpec::get_occurs(0x4286));
+        assert_eq!(Some(Occurs::new(0, Some(1))), TestSpec::get_occurs(0xbf));
+        assert_eq!(None, TestSpec::get_occurs(0xec));
+    }
+
+    #[test]
+    pub fn write_read_round_trip() {
+        let segment = TestSpec::Segment(Master::Full(vec![
+            TestSpec::Info(Master::Full(vec![
+                TestSpec::Duration(2.5),
+                TestSpec::Offset(-3),
+                TestSpec::Title(String::from("title")),
+            ])),
+            TestSpec::Tags(Master::Full(vec![
+                TestSpec::SimpleTag(Master::Full(vec![
+                    TestSpec::SimpleTag(Master::Full(vec![])),
+                ])),
+            ])),
+        ]));
+
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&segment).unwrap();
+        drop(writer);
+
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(dest.into_inner()), &[TestSpec::Segment(Master::Start)]);
+        assert!(iter.map(|t| t.unwrap()).eq(vec![segment]));
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(feature = "derive-spec")]
+pub mod error_recovery {
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::{TagIterator, TagWriter};
+    use ebml_iterable::error::TagIteratorError;
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Segment)]
+        Count,
+
+        #[id(0xa1)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(Segment)]
+        Block,
+    }
+
+    // Returns data with a corrupted `Block` tag id along with the offset of the corrupted byte.
+    fn get_corrupted_data() -> (Vec<u8>, usize) {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Count(0x01)).expect("Error writing tag");
+        writer.write(&TestSpec::Block(vec![0x00; 10])).expect("Error writing tag");
+        writer.write(&TestSpec::Count(0x02)).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        drop(writer);
+
+        let mut data = dest.into_inner();
+        let block_offset = 5 + 4;
+        assert_eq!(0xa1, data[block_offset]);
+        data[block_offset] = 0x00;
+        (data, block_offset)
+    }
+
+    #[test]
+    pub fn recover_from_corrupted_tag() {
+        let (data, block_offset) = get_corrupted_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        iter.set_error_recovery(true);
+
+        assert_eq!(TestSpec::Segment(Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::Count(0x01), iter.next().unwrap().unwrap());
+        match iter.next().unwrap() {
+            Err(TagIteratorError::Resynchronized { problem, offset, skipped_bytes }) => {
+                assert!(matches!(*problem, TagIteratorError::CorruptedFileData(_)));
+                assert_eq!(block_offset, offset);
+                assert_eq!(12, skipped_bytes);
+            },
+            other => panic!("Expected resynchronization, got {:?}", other),
+        }
+        assert_eq!(TestSpec::Count(0x02), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::Segment(Master::End), iter.next().unwrap().unwrap());
+        assert!(iter.next().is_none());
+    }
+
+    #[test]
+    pub fn corrupted_tag_without_recovery() {
+        let (data, _) = get_corrupted_data();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+
+        let error = iter.nth(2).unwrap().expect_err("Reading corrupted tag should fail");
+        assert!(matches!(error, TagIteratorError::CorruptedFileData(_)));
+    }
+
+    #[test]
+    pub fn oversized_child_is_corrupted() {
+        let mut data = vec![0x18, 0x53, 0x80, 0x67, 0x88];
+        // Count claims to be larger than the segment containing it, followed by a valid Count tag
+        data.extend_from_slice(&[0x41, 0x00, 0x90, 0x41, 0x00, 0x81, 0x05]);
+        data.push(0x00);
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        iter.set_error_recovery(true);
+
+        assert_eq!(TestSpec::Segment(Master::Start), iter.next().unwrap().unwrap());
+        assert!(matches!(iter.next().unwrap(), Err(TagIteratorError::Resynchronized { skipped_bytes: 3, .. })));
+        assert_eq!(TestSpec::Count(0x05), iter.next().unwrap().unwrap());
+    }
+}
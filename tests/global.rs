@@ -0,0 +1,141 @@
+#[cfg(feature = "derive-spec")]
+pub mod global {
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecification, EbmlTag, GlobalLevels, Master, TagDataType, CRC32_ID, VOID_ID};
+    use ebml_iterable::{TagIterator, TagIteratorSlice};
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1f43b675)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cluster,
+
+        #[id(0xe7)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Cluster)]
+        Timestamp,
+
+        #[id(0x4321)]
+        #[data_type(TagDataType::Binary)]
+        #[global]
+        Padding,
+
+        #[id(0x4322)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[global(..=1)]
+        Marker,
+
+        #[id(0xec)]
+        #[data_type(TagDataType::Binary)]
+        Void,
+
+        #[id(0xbf)]
+        #[data_type(TagDataType::Binary)]
+        Crc32,
+    }
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum LocalSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1f43b675)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cluster,
+
+        #[id(0xe7)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Cluster)]
+        Timestamp,
+
+        #[id(0x4321)]
+        #[data_type(TagDataType::Binary)]
+        Padding,
+    }
+
+    // An unknown sized segment containing an unknown sized cluster
+    fn get_data(cluster_content: &[u8]) -> Vec<u8> {
+        let mut data = vec![
+            0x18, 0x53, 0x80, 0x67, 0xff,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0xe7, 0x81, 0x01,
+        ];
+        data.extend_from_slice(cluster_content);
+        data.extend_from_slice(&[0xe7, 0x81, 0x02]);
+        data
+    }
+
+    #[test]
+    pub fn global_levels() {
+        assert_eq!(Some(GlobalLevels::ANY), TestSpec::get_global_levels(0x4321));
+        assert_eq!(Some(GlobalLevels::new(0, Some(1))), TestSpec::get_global_levels(0x4322));
+        assert_eq!(Some(GlobalLevels::ANY), TestSpec::get_global_levels(VOID_ID));
+        assert_eq!(Some(GlobalLevels::new(1, None)), TestSpec::get_global_levels(CRC32_ID));
+        assert_eq!(None, TestSpec::get_global_levels(0xe7));
+        assert_eq!(None, LocalSpec::get_global_levels(0x4321));
+        assert_eq!(None, TestSpec::get_parent_id(0x4321));
+
+        let timestamp = TestSpec::Timestamp(1);
+        let cluster = TestSpec::Cluster(Master::Start);
+        assert!(cluster.is_child(0x4321));
+        assert!(cluster.is_child(VOID_ID));
+        assert!(cluster.is_child(CRC32_ID));
+        assert!(timestamp.is_child(0x4321));
+        assert!(!cluster.is_child(0x18538067));
+        assert!(!LocalSpec::Cluster(Master::Start).is_child(0x4321));
+    }
+
+    #[test]
+    pub fn global_tag_stays_in_unknown_sized_parent() {
+        let data = get_data(&[0x43, 0x21, 0x81, 0x00]);
+        let expected = vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Timestamp(1), TestSpec::Padding(vec![0x00]), TestSpec::Timestamp(2)])),
+            TestSpec::Segment(Master::End),
+        ];
+        let tags: Vec<TestSpec> = TagIterator::new(Cursor::new(&data), &[TestSpec::Cluster(Master::Start)]).map(|tag| tag.unwrap()).collect();
+        assert_eq!(expected, tags);
+
+        let tags: Vec<TestSpec> = TagIteratorSlice::<TestSpec>::new(&data, &[TestSpec::Cluster(Master::Start)]).map(|tag| tag.unwrap().to_tag()).collect();
+        assert_eq!(expected, tags);
+    }
+
+    #[test]
+    pub fn top_level_tag_ends_unknown_sized_parent() {
+        let data = get_data(&[0x43, 0x21, 0x81, 0x00]);
+        let tags: Vec<LocalSpec> = TagIterator::new(Cursor::new(&data), &[]).map(|tag| tag.unwrap()).collect();
+        assert_eq!(vec![
+            LocalSpec::Segment(Master::Start),
+            LocalSpec::Cluster(Master::Start),
+            LocalSpec::Timestamp(1),
+            LocalSpec::Cluster(Master::End),
+            LocalSpec::Padding(vec![0x00]),
+            LocalSpec::Timestamp(2),
+            LocalSpec::Segment(Master::End),
+        ], tags);
+    }
+
+    #[test]
+    pub fn global_tag_outside_levels_ends_unknown_sized_parent() {
+        let data = get_data(&[0x43, 0x22, 0x81, 0x05]);
+        let tags: Vec<TestSpec> = TagIterator::new(Cursor::new(&data), &[]).map(|tag| tag.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Timestamp(1),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Marker(5),
+            TestSpec::Timestamp(2),
+            TestSpec::Segment(Master::End),
+        ], tags);
+    }
+}
@@ -0,0 +1,163 @@
+#[cfg(feature = "derive-spec")]
+pub mod iterator_parity {
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::{TagIterator, TagIteratorAsync, TagIteratorLimits, TagWriter};
+    use ebml_iterable::error::TagIteratorError;
+    use std::io::{Cursor, Read};
+    use std::pin::Pin;
+    use std::task::{Context, Poll};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cluster,
+
+        #[id(0xe7)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Cluster)]
+        Timestamp,
+
+        #[id(0xa3)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(Cluster)]
+        SimpleBlock,
+    }
+
+    // Returns data in small chunks to make sure tags split across reads are handled
+    struct Trickle<R> {
+        inner: R,
+        chunk: usize,
+    }
+
+    impl<R: Read> Read for Trickle<R> {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let length = self.chunk.min(buf.len());
+            self.inner.read(&mut buf[..length])
+        }
+    }
+
+    impl<R: Read + Unpin> futures::AsyncRead for Trickle<R> {
+        fn poll_read(self: Pin<&mut Self>, _cx: &mut Context<'_>, buf: &mut [u8]) -> Poll<std::io::Result<usize>> {
+            Poll::Ready(Read::read(self.get_mut(), buf))
+        }
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Timestamp(0x01), TestSpec::SimpleBlock(vec![0x01; 40])]))).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Timestamp(0x02), TestSpec::SimpleBlock(vec![0x02; 40])]))).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    // A live stream style segment where the segment and its clusters have unknown sizes and the file is cut off
+    fn get_live_data() -> Vec<u8> {
+        vec![
+            0x18, 0x53, 0x80, 0x67, 0xff,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0xe7, 0x81, 0x01,
+                    0xa3, 0x82, 0x01, 0x02,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0xe7, 0x81, 0x02,
+        ]
+    }
+
+    fn read_sync(data: &[u8], chunk: usize, tags_to_buffer: &[TestSpec], configure: impl Fn(&mut TagIterator<Trickle<&[u8]>, TestSpec>)) -> Vec<Result<TestSpec, String>> {
+        let mut iter = TagIterator::new(Trickle { inner: data, chunk }, tags_to_buffer);
+        configure(&mut iter);
+        let mut tags = Vec::new();
+        for tag in iter {
+            tags.push(tag.map_err(|e| e.to_string()));
+            if tags.last().unwrap().as_ref().is_err_and(|e| !e.starts_with("Skipped")) {
+                break;
+            }
+        }
+        tags
+    }
+
+    fn read_async(data: &[u8], chunk: usize, tags_to_buffer: &[TestSpec], configure: impl Fn(&mut TagIteratorAsync<Trickle<&[u8]>, TestSpec>)) -> Vec<Result<TestSpec, String>> {
+        let mut iter = TagIteratorAsync::new(Trickle { inner: data, chunk }, tags_to_buffer);
+        configure(&mut iter);
+        futures::executor::block_on(async {
+            let mut tags = Vec::new();
+            while let Some(tag) = iter.next().await {
+                tags.push(tag.map_err(|e| e.to_string()));
+                if tags.last().unwrap().as_ref().is_err_and(|e| !e.starts_with("Skipped")) {
+                    break;
+                }
+            }
+            tags
+        })
+    }
+
+    fn assert_parity(data: &[u8], tags_to_buffer: &[TestSpec], recovery: bool, limits: TagIteratorLimits) -> Vec<Result<TestSpec, String>> {
+        let expected = read_sync(data, usize::MAX, tags_to_buffer, |iter| { iter.set_error_recovery(recovery); iter.set_limits(limits); });
+        for chunk in [1, 3, 1024] {
+            assert_eq!(expected, read_sync(data, chunk, tags_to_buffer, |iter| { iter.set_error_recovery(recovery); iter.set_limits(limits); }), "sync with chunk size {}", chunk);
+            assert_eq!(expected, read_async(data, chunk, tags_to_buffer, |iter| { iter.set_error_recovery(recovery); iter.set_limits(limits); }), "async with chunk size {}", chunk);
+        }
+        expected
+    }
+
+    #[test]
+    pub fn plain_iteration() {
+        let tags = assert_parity(&get_data(), &[], false, TagIteratorLimits::default());
+        assert_eq!(10, tags.len());
+        assert!(tags.iter().all(|t| t.is_ok()));
+    }
+
+    #[test]
+    pub fn buffered_tags() {
+        let tags = assert_parity(&get_data(), &[TestSpec::Cluster(Master::Start)], false, TagIteratorLimits::default());
+        assert_eq!(Ok(TestSpec::Cluster(Master::Full(vec![TestSpec::Timestamp(0x01), TestSpec::SimpleBlock(vec![0x01; 40])]))), tags[1]);
+        assert_eq!(4, tags.len());
+    }
+
+    #[test]
+    pub fn unknown_sizes_closed_at_end_of_data() {
+        let tags = assert_parity(&get_live_data(), &[], false, TagIteratorLimits::default());
+        assert_eq!(Ok(TestSpec::Cluster(Master::End)), tags[tags.len() - 2]);
+        assert_eq!(Ok(TestSpec::Segment(Master::End)), tags[tags.len() - 1]);
+
+        let tags = assert_parity(&get_live_data(), &[TestSpec::Cluster(Master::Start)], false, TagIteratorLimits::default());
+        assert_eq!(vec![
+            Ok(TestSpec::Segment(Master::Start)),
+            Ok(TestSpec::Cluster(Master::Full(vec![TestSpec::Timestamp(0x01), TestSpec::SimpleBlock(vec![0x01, 0x02])]))),
+            Ok(TestSpec::Cluster(Master::Full(vec![TestSpec::Timestamp(0x02)]))),
+            Ok(TestSpec::Segment(Master::End)),
+        ], tags);
+    }
+
+    #[test]
+    pub fn unknown_size_primitive() {
+        let tags = assert_parity(&[0xe7, 0xff, 0x01], &[], false, TagIteratorLimits::default());
+        assert_eq!(vec![Err(TagIteratorError::CorruptedFileData(String::from("Unknown size for primitive not allowed")).to_string())], tags);
+    }
+
+    #[test]
+    pub fn error_recovery() {
+        let mut data = get_data();
+        let block_offset = data.iter().position(|b| *b == 0xa3).expect("data should contain a block");
+        data[block_offset] = 0x00;
+        let tags = assert_parity(&data, &[], true, TagIteratorLimits::default());
+        assert!(tags.iter().any(|t| t.as_ref().is_err_and(|e| e.starts_with("Skipped"))));
+        assert_eq!(Ok(TestSpec::Segment(Master::End)), tags[tags.len() - 1]);
+    }
+
+    #[test]
+    pub fn limits() {
+        let limits = TagIteratorLimits { max_tag_size: Some(10), ..Default::default() };
+        let tags = assert_parity(&get_data(), &[], false, limits);
+        assert!(tags[tags.len() - 1].is_err());
+    }
+}
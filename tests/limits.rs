@@ -0,0 +1,78 @@
+#[cfg(feature = "derive-spec")]
+pub mod limits {
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::{TagIterator, TagIteratorLimits, TagWriter};
+    use ebml_iterable::error::TagIteratorError;
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        Cluster,
+
+        #[id(0xa1)]
+        #[data_type(TagDataType::Binary)]
+        Block,
+    }
+
+    fn write(tags: &[TestSpec]) -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        for tag in tags {
+            writer.write(tag).expect("Error writing tag");
+        }
+        drop(writer);
+        dest.into_inner()
+    }
+
+    fn read_all(data: Vec<u8>, tags_to_buffer: &[TestSpec], limits: TagIteratorLimits) -> Result<Vec<TestSpec>, TagIteratorError> {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), tags_to_buffer);
+        iter.set_limits(limits);
+        iter.collect()
+    }
+
+    #[test]
+    pub fn huge_tag_size() {
+        // A block claiming to be ~2^48 bytes long
+        let data = vec![0xa1, 0x01, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0x00];
+        let limits = TagIteratorLimits { max_tag_size: Some(1024), ..Default::default() };
+        assert!(matches!(read_all(data, &[], limits), Err(TagIteratorError::TagSizeLimitExceeded { tag_id: 0xa1, limit: 1024, .. })));
+    }
+
+    #[test]
+    pub fn buffered_size() {
+        let data = write(&[TestSpec::Cluster(Master::Full(vec![TestSpec::Block(vec![0x00; 2000])]))]);
+        let limits = TagIteratorLimits { max_buffered_size: Some(1000), max_tag_size: Some(4000), ..Default::default() };
+        assert!(matches!(read_all(data.clone(), &[TestSpec::Cluster(Master::Start)], limits), Err(TagIteratorError::BufferedSizeLimitExceeded { tag_id: 0x1F43B675, .. })));
+        assert_eq!(3, read_all(data, &[], limits).expect("Unbuffered reading should succeed").len());
+    }
+
+    #[test]
+    pub fn nesting_depth() {
+        let data = write(&[TestSpec::Segment(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![TestSpec::Cluster(Master::Full(vec![]))]))]))]);
+        let limits = TagIteratorLimits { max_depth: Some(2), ..Default::default() };
+        assert!(matches!(read_all(data.clone(), &[], limits), Err(TagIteratorError::DepthLimitExceeded { tag_id: 0x1F43B675, limit: 2 })));
+        assert!(matches!(read_all(data.clone(), &[TestSpec::Segment(Master::Start)], limits), Err(TagIteratorError::DepthLimitExceeded { tag_id: 0x1F43B675, .. })));
+
+        let limits = TagIteratorLimits { max_depth: Some(3), ..Default::default() };
+        assert_eq!(6, read_all(data.clone(), &[], limits).expect("Reading should succeed").len());
+        assert_eq!(1, read_all(data, &[TestSpec::Segment(Master::Start)], limits).expect("Reading should succeed").len());
+    }
+
+    #[test]
+    pub fn total_bytes() {
+        let data = write(&[TestSpec::Segment(Master::Full(vec![TestSpec::Block(vec![0x00; 100])]))]);
+        let length = data.len();
+        let limits = TagIteratorLimits { max_total_bytes: Some(length - 1), ..Default::default() };
+        assert!(matches!(read_all(data.clone(), &[], limits), Err(TagIteratorError::TotalSizeLimitExceeded { .. })));
+
+        let limits = TagIteratorLimits { max_total_bytes: Some(length), ..Default::default() };
+        assert_eq!(3, read_all(data, &[], limits).expect("Reading should succeed").len());
+    }
+}
@@ -0,0 +1,150 @@
+#[cfg(feature = "derive-spec")]
+pub mod occurs {
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecification, TagDataType, Master, Occurs};
+    use ebml_iterable::{OccursValidator, OccursViolation, TagIterator, TagWriter};
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        #[occurs(1, 1)]
+        Segment,
+
+        #[id(0x1549a966)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        #[occurs(1, _)]
+        Info,
+
+        #[id(0x2ad7b1)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Info)]
+        #[occurs(1, 1)]
+        TimestampScale,
+
+        #[id(0x7ba9)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Info)]
+        #[occurs(0, 1)]
+        Title,
+
+        #[id(0x4d80)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Info)]
+        MuxingApp,
+
+        #[id(0x4282)]
+        #[data_type(TagDataType::String)]
+        #[parent(Info)]
+        #[occurs(1, 1)]
+        #[default("webm")]
+        DocType,
+    }
+
+    fn get_data(segments: Vec<TestSpec>) -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        for segment in segments {
+            writer.write(&segment).expect("Error writing tag");
+        }
+        drop(writer);
+        dest.into_inner()
+    }
+
+    fn validate(data: Vec<u8>, tags_to_buffer: &[TestSpec]) -> Vec<OccursViolation> {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), tags_to_buffer);
+        OccursValidator::validate_stream(std::iter::from_fn(|| iter.next_with_meta())).unwrap()
+    }
+
+    fn invalid_segment() -> TestSpec {
+        TestSpec::Segment(Master::Full(vec![
+            TestSpec::Info(Master::Full(vec![
+                TestSpec::Title(String::from("a")),
+                TestSpec::Title(String::from("b")),
+                TestSpec::MuxingApp(String::from("c")),
+                TestSpec::MuxingApp(String::from("d")),
+            ])),
+        ]))
+    }
+
+    #[test]
+    pub fn spec_occurs() {
+        assert_eq!(Some(Occurs::new(1, Some(1))), TestSpec::get_occurs(0x2ad7b1));
+        assert_eq!(Some(Occurs::new(1, None)), TestSpec::get_occurs(0x1549a966));
+        assert_eq!(None, TestSpec::get_occurs(0x4d80));
+        assert_eq!(&[0x1549a966], TestSpec::get_mandatory_children(0x18538067));
+        assert_eq!(&[0x2ad7b1, 0x4282], TestSpec::get_mandatory_children(0x1549a966));
+        assert!(TestSpec::get_mandatory_children(0x2ad7b1).is_empty());
+    }
+
+    #[test]
+    pub fn valid_stream() {
+        let data = get_data(vec![TestSpec::Segment(Master::Full(vec![
+            TestSpec::Info(Master::Full(vec![TestSpec::TimestampScale(1), TestSpec::Title(String::from("a"))])),
+            TestSpec::Info(Master::Full(vec![TestSpec::TimestampScale(2)])),
+        ]))]);
+        assert!(validate(data, &[]).is_empty());
+    }
+
+    #[test]
+    pub fn invalid_stream() {
+        let data = get_data(vec![invalid_segment(), TestSpec::Segment(Master::Full(vec![]))]);
+        // Segment (4 + 1 byte header) -> Info (4 + 1 byte header) -> Title, Title (2 + 1 + 1 bytes each)
+        assert_eq!(vec![
+            OccursViolation::TooMany { tag_id: 0x7ba9, parent_id: Some(0x1549a966), offset: Some(14), max: 1 },
+            OccursViolation::Missing { tag_id: 0x2ad7b1, parent_id: Some(0x1549a966), parent_offset: Some(5), found: 0, min: 1 },
+            OccursViolation::TooMany { tag_id: 0x18538067, parent_id: None, offset: Some(26), max: 1 },
+            OccursViolation::Missing { tag_id: 0x1549a966, parent_id: Some(0x18538067), parent_offset: Some(26), found: 0, min: 1 },
+        ], validate(data, &[]));
+    }
+
+    #[test]
+    pub fn buffered_stream() {
+        let data = get_data(vec![invalid_segment()]);
+        assert_eq!(vec![
+            OccursViolation::TooMany { tag_id: 0x7ba9, parent_id: Some(0x1549a966), offset: None, max: 1 },
+            OccursViolation::Missing { tag_id: 0x2ad7b1, parent_id: Some(0x1549a966), parent_offset: Some(5), found: 0, min: 1 },
+        ], validate(data, &[TestSpec::Info(Master::Start)]));
+    }
+
+    #[test]
+    pub fn full_tag() {
+        assert_eq!(vec![
+            OccursViolation::TooMany { tag_id: 0x7ba9, parent_id: Some(0x1549a966), offset: None, max: 1 },
+            OccursViolation::Missing { tag_id: 0x2ad7b1, parent_id: Some(0x1549a966), parent_offset: None, found: 0, min: 1 },
+        ], OccursValidator::validate_tag(&invalid_segment()));
+    }
+
+    #[test]
+    pub fn unclosed_tags_are_checked_on_finish() {
+        let mut validator: OccursValidator<TestSpec> = OccursValidator::new();
+        validator.push(&TestSpec::Segment(Master::Start), Some(0));
+        validator.push(&TestSpec::Info(Master::Start), Some(5));
+        assert!(validator.violations().is_empty());
+        assert_eq!(vec![
+            OccursViolation::Missing { tag_id: 0x2ad7b1, parent_id: Some(0x1549a966), parent_offset: Some(5), found: 0, min: 1 },
+        ], validator.finish());
+    }
+
+    #[test]
+    pub fn children_with_defaults_are_not_missing() {
+        let data = get_data(vec![TestSpec::Segment(Master::Full(vec![
+            TestSpec::Info(Master::Full(vec![TestSpec::TimestampScale(1)])),
+        ]))]);
+        assert!(validate(data, &[]).is_empty());
+    }
+
+    #[test]
+    pub fn missing_top_level_tags_are_checked_on_finish() {
+        let violations = validate(Vec::new(), &[]);
+        assert_eq!(vec![
+            OccursViolation::Missing { tag_id: 0x18538067, parent_id: None, parent_offset: None, found: 0, min: 1 },
+        ], violations);
+        assert_eq!("Tag id (408125543) occurred 0 times at the top level, but must occur at least 1 times.", violations[0].to_string());
+
+        // Complete tags are validated as if they were at the top level, so their own parents are not required
+        assert!(OccursValidator::validate_tag(&TestSpec::Info(Master::Full(vec![TestSpec::TimestampScale(1)]))).is_empty());
+    }
+}
@@ -0,0 +1,119 @@
+#[cfg(feature = "derive-spec")]
+pub mod query {
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecification, TagDataType, Master};
+    use ebml_iterable::error::QueryError;
+    use ebml_iterable::{EbmlDocument, TagIterator, TagQuery, TagWriter};
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cluster,
+
+        #[id(0xa0)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Cluster)]
+        BlockGroup,
+
+        #[id(0xa3)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(Cluster)]
+        SimpleBlock,
+
+        #[id(0xa1)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(BlockGroup)]
+        Block,
+
+        #[id(0xe7)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Cluster)]
+        Timestamp,
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Full(vec![
+            TestSpec::Cluster(Master::Full(vec![
+                TestSpec::Timestamp(1),
+                TestSpec::SimpleBlock(vec![0x01]),
+                TestSpec::BlockGroup(Master::Full(vec![TestSpec::Block(vec![0x02])])),
+            ])),
+            TestSpec::Cluster(Master::Full(vec![
+                TestSpec::Timestamp(2),
+                TestSpec::SimpleBlock(vec![0x03]),
+            ])),
+        ]))).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    #[test]
+    pub fn tag_names() {
+        assert_eq!(Some("Cluster"), TestSpec::get_tag_name(0x1F43B675));
+        assert_eq!(Some(0x1F43B675), TestSpec::get_tag_id_by_name("Cluster"));
+        assert_eq!(None, TestSpec::get_tag_name(0x4242));
+        assert_eq!(None, TestSpec::get_tag_id_by_name("Missing"));
+    }
+
+    #[test]
+    pub fn select_streaming() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[]);
+        let tags: Vec<TestSpec> = iter.select("Segment/Cluster/SimpleBlock").unwrap().map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::SimpleBlock(vec![0x01]), TestSpec::SimpleBlock(vec![0x03])], tags);
+    }
+
+    #[test]
+    pub fn select_streaming_masters() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[]);
+        let tags: Vec<TestSpec> = iter.select("Segment/*").unwrap().map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Cluster(Master::Start), TestSpec::Cluster(Master::Start)], tags);
+    }
+
+    #[test]
+    pub fn select_streaming_inside_buffered_tags() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[TestSpec::Cluster(Master::Start)]);
+        let tags: Vec<TestSpec> = iter.select("**/Block").unwrap().map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Block(vec![0x02])], tags);
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[TestSpec::Cluster(Master::Start)]);
+        let tags: Vec<TestSpec> = iter.select("Segment/**/Timestamp").unwrap().map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Timestamp(1), TestSpec::Timestamp(2)], tags);
+    }
+
+    #[test]
+    pub fn select_full_tree() {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[TestSpec::Segment(Master::Start)]);
+        let segment = iter.map(|t| t.unwrap()).next().unwrap();
+
+        let query = TagQuery::parse::<TestSpec>("Segment/Cluster/**/0xa1").unwrap();
+        assert_eq!(vec![&TestSpec::Block(vec![0x02])], query.select(&segment));
+
+        let query = TagQuery::parse::<TestSpec>("**").unwrap();
+        assert_eq!(9, query.select(&segment).len());
+    }
+
+    #[test]
+    pub fn select_document() {
+        let document: EbmlDocument<TestSpec> = EbmlDocument::read(Cursor::new(get_data())).unwrap();
+        let nodes = document.select("Segment/Cluster/Timestamp").unwrap();
+        let tags: Vec<&TestSpec> = nodes.iter().map(|node| document.tag(*node)).collect();
+        assert_eq!(vec![&TestSpec::Timestamp(1), &TestSpec::Timestamp(2)], tags);
+    }
+
+    #[test]
+    pub fn invalid_queries() {
+        assert!(matches!(TagQuery::parse::<TestSpec>(""), Err(QueryError::InvalidPath(_))));
+        assert!(matches!(TagQuery::parse::<TestSpec>("Segment//Cluster"), Err(QueryError::InvalidPath(_))));
+        assert!(matches!(TagQuery::parse::<TestSpec>("Segment/Missing"), Err(QueryError::UnknownTag(name)) if name == "Missing"));
+        assert!(TagQuery::parse::<TestSpec>("/Segment/Cluster").is_ok());
+    }
+}
@@ -0,0 +1,199 @@
+#[cfg(feature = "schema")]
+pub mod schema {
+    use ebml_iterable::specs::schema::{DynamicSpec, DynamicTag, DynamicValue, EbmlSchema, SchemaError};
+    use ebml_iterable::specs::{EbmlSpecificationInstance, EbmlTag, GlobalLevels, Master, Occurs, TagDataType, ValueRange};
+    use ebml_iterable::error::{TagWriterError, ToolError};
+    use ebml_iterable::{TagIterator, TagWriter};
+    use std::io::Cursor;
+    use std::ops::Bound;
+
+    const SCHEMA: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+        <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="test" version="2">
+            <element name="Segment" path="\Segment" id="0x18538067" type="master" minOccurs="1" maxOccurs="1">
+                <documentation lang="en" purpose="definition">The root element.</documentation>
+            </element>
+            <element name="Info" path="\Segment\Info" id="0x1549A966" type="master" minOccurs="1" maxOccurs="1"/>
+            <element name="TimestampScale" path="\Segment\Info\TimestampScale" id="0x2AD7B1" type="uinteger" minOccurs="1" maxOccurs="1" range="not 0" default="1000000"/>
+            <element name="Duration" path="\Segment\Info\Duration" id="0x4489" type="float" range="> 0x0p+0"/>
+            <element name="Offset" path="\Segment\Info\Offset" id="0x75A2" type="integer" range="-10-10" default="-1"/>
+            <element name="Title" path="\Segment\Info\Title" id="0x7BA9" type="utf-8" length="1-"/>
+            <element name="DocType" path="\Segment\Info\DocType" id="0x4282" type="string" default="test"/>
+            <element name="DateUTC" path="\Segment\Info\DateUTC" id="0x4461" type="date"/>
+            <element name="SegmentUUID" path="\Segment\Info\SegmentUUID" id="0x73A4" type="binary" length="16"/>
+            <element name="Cluster" path="\Segment\Cluster" id="0x1F43B675" type="master"/>
+            <element name="Timestamp" path="\Segment\Cluster\Timestamp" id="0xE7" type="uinteger" minOccurs="1" maxOccurs="1"/>
+            <element name="Tags" path="\Segment\Tags" id="0x1254C367" type="master"/>
+            <element name="SimpleTag" path="\Segment\Tags\+SimpleTag" id="0x67C8" type="master"/>
+            <element name="Void" path="\(-\)Void" id="0xEC" type="binary"/>
+        </EBMLSchema>"#;
+
+    fn spec() -> DynamicSpec {
+        DynamicSpec::new(EbmlSchema::parse(SCHEMA).unwrap()).unwrap()
+    }
+
+    fn uint(id: u64, value: u64) -> DynamicTag {
+        DynamicTag::new(id, DynamicValue::UnsignedInt(value))
+    }
+
+    fn master(id: u64, master: Master<DynamicTag>) -> DynamicTag {
+        DynamicTag::new(id, DynamicValue::Master(master))
+    }
+
+    fn write(spec: &DynamicSpec, tags: &[DynamicTag]) -> Result<Vec<u8>, TagWriterError> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        for tag in tags {
+            writer.write_with_spec(tag, spec)?;
+        }
+        drop(writer);
+        Ok(dest.into_inner())
+    }
+
+    #[test]
+    pub fn parse_schema() {
+        let schema = EbmlSchema::parse(SCHEMA).unwrap();
+        assert_eq!("test", schema.doc_type);
+        assert_eq!(2, schema.version);
+        assert_eq!(14, schema.elements.len());
+
+        let segment = schema.element(0x18538067).unwrap();
+        assert_eq!(None, segment.parent_id);
+        assert_eq!(Occurs::new(1, Some(1)), segment.occurs);
+        assert_eq!(Some("The root element."), segment.documentation.as_deref());
+
+        let scale = schema.element_by_name("TimestampScale").unwrap();
+        assert_eq!(0x2ad7b1, scale.id);
+        assert_eq!(TagDataType::UnsignedInt, scale.data_type);
+        assert_eq!(Some(0x1549a966), scale.parent_id);
+        assert_eq!(Some("1000000"), scale.default.as_deref());
+
+        let simple_tag = schema.element(0x67c8).unwrap();
+        assert!(simple_tag.recursive);
+        assert_eq!(Some(0x1254c367), simple_tag.parent_id);
+
+        let void = schema.element(0xec).unwrap();
+        assert_eq!(Some(GlobalLevels::ANY), void.global);
+        assert_eq!(None, void.parent_id);
+        assert_eq!(Occurs::default(), void.occurs);
+    }
+
+    #[test]
+    pub fn load_schema_file() {
+        let path = std::env::temp_dir().join(format!("ebml-iterable-schema-{}.xml", std::process::id()));
+        std::fs::write(&path, SCHEMA).unwrap();
+        let schema = EbmlSchema::load(&path);
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(EbmlSchema::parse(SCHEMA).unwrap(), schema.unwrap());
+
+        assert!(matches!(EbmlSchema::load(std::env::temp_dir().join("ebml-iterable-missing.xml")), Err(SchemaError::Io(_))));
+    }
+
+    #[test]
+    pub fn invalid_schemas() {
+        assert!(matches!(EbmlSchema::parse("<EBMLSchema"), Err(SchemaError::Xml(_))));
+        assert!(matches!(EbmlSchema::parse(r#"<Schema docType="a" version="1"/>"#), Err(SchemaError::NotASchema(_))));
+        assert!(matches!(EbmlSchema::parse(r#"<EBMLSchema docType="a" version="1"><element name="A" path="\A" type="master"/></EBMLSchema>"#), Err(SchemaError::MissingAttribute { attribute: "id", .. })));
+        assert!(matches!(EbmlSchema::parse(r#"<EBMLSchema docType="a" version="1"><element name="A" path="\A" id="0x80" type="list"/></EBMLSchema>"#), Err(SchemaError::InvalidAttribute { attribute: "type", .. })));
+        assert!(matches!(EbmlSchema::parse(r#"<EBMLSchema docType="a" version="1"><element name="A" path="\B\A" id="0x80" type="master"/></EBMLSchema>"#), Err(SchemaError::UnknownParent { .. })));
+        assert!(matches!(EbmlSchema::parse(r#"<EBMLSchema docType="a" version="1"><element name="A" path="\A" id="0x80" type="master"/><element name="B" path="\B" id="0x80" type="master"/></EBMLSchema>"#), Err(SchemaError::DuplicateId(0x80))));
+
+        let schema = EbmlSchema::parse(r#"<EBMLSchema docType="a" version="1"><element name="A" path="\A" id="0x80" type="uinteger" range="not 1"/></EBMLSchema>"#).unwrap();
+        assert!(matches!(DynamicSpec::new(schema), Err(SchemaError::InvalidAttribute { attribute: "range", .. })));
+        let schema = EbmlSchema::parse(r#"<EBMLSchema docType="a" version="1"><element name="A" path="\A" id="0x80" type="float" default="abc"/></EBMLSchema>"#).unwrap();
+        assert!(matches!(DynamicSpec::new(schema), Err(SchemaError::InvalidAttribute { attribute: "default", .. })));
+    }
+
+    #[test]
+    pub fn spec_lookups() {
+        let spec = spec();
+        assert_eq!(TagDataType::Float, spec.get_tag_data_type(0x4489));
+        assert_eq!(TagDataType::Binary, spec.get_tag_data_type(0x4321));
+        assert_eq!(Some("Duration"), spec.get_tag_name(0x4489));
+        assert_eq!(Some(0x4461), spec.get_tag_id_by_name("DateUTC"));
+        assert_eq!(Some(uint(0x2ad7b1, 1000000)), spec.get_default(0x2ad7b1));
+        assert_eq!(Some(DynamicTag::new(0x75a2, DynamicValue::Integer(-1))), spec.get_default(0x75a2));
+        assert_eq!(&[0x2ad7b1, 0x75a2, 0x4282], spec.get_children_with_defaults(0x1549a966));
+        assert_eq!(Some(ValueRange::new(Bound::Excluded(0), Bound::Unbounded)), spec.get_unsigned_int_range(0x2ad7b1));
+        assert_eq!(Some(ValueRange::new(Bound::Included(-10), Bound::Included(10))), spec.get_signed_int_range(0x75a2));
+        assert_eq!(Some(ValueRange::new(Bound::Excluded(0.0), Bound::Unbounded)), spec.get_float_range(0x4489));
+        assert_eq!(Some(ValueRange::new(Bound::Included(16), Bound::Included(16))), spec.get_length_range(0x73a4));
+        assert_eq!(&[0x1549a966], spec.get_mandatory_children(0x18538067));
+        assert_eq!(14, spec.get_tag_ids().len());
+        assert_eq!(Some(0x1549a966), spec.get_parent_id(0x4489));
+        assert_eq!(None, spec.get_parent_id(0xec));
+        assert_eq!(None, spec.get_unsigned_int_tag(0x4489, 1));
+        assert_eq!(Some(DynamicTag::new(0x7ba9, DynamicValue::Utf8(String::from("a")))), spec.get_utf8_tag(0x7ba9, String::from("a")));
+    }
+
+    #[test]
+    pub fn is_child() {
+        let spec = spec();
+        let cluster = master(0x1f43b675, Master::Start);
+        assert!(spec.is_child(&cluster, 0xe7));
+        assert!(spec.is_child(&cluster, 0xec));
+        assert!(spec.is_child(&cluster, 0x4321));
+        assert!(!spec.is_child(&cluster, 0x1f43b675));
+        assert!(!spec.is_child(&cluster, 0x1549a966));
+        assert!(!spec.is_child(&cluster, 0x18538067));
+
+        let simple_tag = master(0x67c8, Master::Start);
+        assert!(spec.is_child(&simple_tag, 0x67c8));
+        assert!(!spec.is_child(&simple_tag, 0x1254c367));
+
+        // Without a spec there is no hierarchy to check against
+        assert!(cluster.is_child(0x18538067));
+    }
+
+    #[test]
+    pub fn write_read_round_trip() {
+        let spec = spec();
+        let info = master(0x1549a966, Master::Full(vec![
+            uint(0x2ad7b1, 100000),
+            DynamicTag::new(0x4489, DynamicValue::Float(1.5)),
+            DynamicTag::new(0x75a2, DynamicValue::Integer(-4)),
+            DynamicTag::new(0x7ba9, DynamicValue::Utf8(String::from("title"))),
+            DynamicTag::new(0x4282, DynamicValue::String(String::from("test"))),
+            DynamicTag::new(0x73a4, DynamicValue::Binary(vec![0x01; 16])),
+            DynamicTag::new(0x4321, DynamicValue::Binary(vec![0x02])),
+        ]));
+        let segment = master(0x18538067, Master::Full(vec![info.clone()]));
+        let data = write(&spec, std::slice::from_ref(&segment)).unwrap();
+
+        let iter = TagIterator::with_spec(Cursor::new(&data), &spec, &[master(0x18538067, Master::Start)]);
+        assert!(iter.map(|t| t.unwrap()).eq(vec![segment]));
+
+        let mut iter = TagIterator::with_spec(Cursor::new(&data), &spec, &[]);
+        assert_eq!(master(0x18538067, Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(master(0x1549a966, Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(uint(0x2ad7b1, 100000), iter.next().unwrap().unwrap());
+    }
+
+    #[test]
+    pub fn constraints_and_defaults() {
+        let spec = spec();
+        assert!(matches!(write(&spec, &[uint(0x2ad7b1, 0)]), Err(TagWriterError::InvalidTagData { tag_id: 0x2ad7b1, problem: ToolError::ValueOutOfRange { .. } })));
+        assert!(matches!(write(&spec, &[DynamicTag::new(0x73a4, DynamicValue::Binary(vec![0x01; 4]))]), Err(TagWriterError::InvalidTagData { tag_id: 0x73a4, problem: ToolError::LengthOutOfRange { length: 4, .. } })));
+
+        let data = write(&spec, &[master(0x1549a966, Master::Full(vec![DynamicTag::new(0x4489, DynamicValue::Float(2.0))]))]).unwrap();
+        let mut iter = TagIterator::with_spec(Cursor::new(data), &spec, &[master(0x1549a966, Master::Start)]);
+        iter.set_fill_defaults(true);
+        assert_eq!(master(0x1549a966, Master::Full(vec![
+            DynamicTag::new(0x4489, DynamicValue::Float(2.0)),
+            uint(0x2ad7b1, 1000000),
+            DynamicTag::new(0x75a2, DynamicValue::Integer(-1)),
+            DynamicTag::new(0x4282, DynamicValue::String(String::from("test"))),
+        ])), iter.next().unwrap().unwrap());
+    }
+
+    #[test]
+    pub fn independent_specs() {
+        let spec = spec();
+        let other = DynamicSpec::new(EbmlSchema::parse(r#"<EBMLSchema docType="other" version="1"><element name="Name" path="\Name" id="0x4489" type="utf-8"/></EBMLSchema>"#).unwrap()).unwrap();
+        assert_eq!(Some("Duration"), spec.get_tag_name(0x4489));
+        assert_eq!(Some("Name"), other.get_tag_name(0x4489));
+
+        let data = write(&other, &[DynamicTag::new(0x4489, DynamicValue::Utf8(String::from("a")))]).unwrap();
+        let mut iter = TagIterator::with_spec(Cursor::new(&data), &other, &[]);
+        assert_eq!(DynamicTag::new(0x4489, DynamicValue::Utf8(String::from("a"))), iter.next().unwrap().unwrap());
+    }
+}
@@ -0,0 +1,125 @@
+#[cfg(all(feature = "derive-spec", feature = "schema"))]
+pub mod schema_export {
+    use ebml_iterable::specs::{ebml_schema, ebml_specification, TagDataType, Occurs};
+    use ebml_iterable::specs::schema::EbmlSchema;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        #[occurs(1, 1)]
+        Segment,
+
+        #[id(0x1549a966)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Info,
+
+        #[id(0x2ad7b1)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Info)]
+        #[default(1000000)]
+        #[range(1..)]
+        #[occurs(1, 1)]
+        TimestampScale,
+
+        #[id(0x4489)]
+        #[data_type(TagDataType::Float)]
+        #[parent(Info)]
+        #[default(1.5)]
+        #[range(> 0.0, <= 8000.0)]
+        Duration,
+
+        #[id(0x75a2)]
+        #[data_type(TagDataType::Integer)]
+        #[parent(Info)]
+        #[range(-10..=10)]
+        Offset,
+
+        #[id(0x7ba9)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Info)]
+        #[default("<untitled & \"unnamed\">")]
+        Title,
+
+        #[id(0x4461)]
+        #[data_type(TagDataType::Date)]
+        #[parent(Info)]
+        #[default(5)]
+        DateUtc,
+
+        #[id(0x73a4)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(Info)]
+        #[length(16)]
+        #[default([0x01, 0x02])]
+        SegmentUid,
+
+        #[id(0xec)]
+        #[data_type(TagDataType::Binary)]
+        Void,
+    }
+
+    ebml_schema! {
+        #[derive(Clone, Debug, PartialEq)]
+        pub enum GeneratedSpec = "tests/schemas/test.xml";
+    }
+
+    #[test]
+    pub fn spec_to_schema() {
+        let schema = EbmlSchema::from_spec::<TestSpec>("test", 3);
+        assert_eq!("test", schema.doc_type);
+        assert_eq!(3, schema.version);
+        assert_eq!(9, schema.elements.len());
+
+        let segment = schema.element(0x18538067).unwrap();
+        assert_eq!("\\Segment", segment.path);
+        assert_eq!(Occurs::new(1, Some(1)), segment.occurs);
+
+        let scale = schema.element_by_name("TimestampScale").unwrap();
+        assert_eq!("\\Segment\\Info\\TimestampScale", scale.path);
+        assert_eq!(Some(0x1549a966), scale.parent_id);
+        assert_eq!(TagDataType::UnsignedInt, scale.data_type);
+        assert_eq!(Some("1000000"), scale.default.as_deref());
+        assert_eq!(Some("1-"), scale.range.as_deref());
+
+        let duration = schema.element(0x4489).unwrap();
+        assert_eq!(Some("0x1.8p+0"), duration.default.as_deref());
+        assert_eq!(Some(">0x0p+0,<=0x1.f4p+12"), duration.range.as_deref());
+
+        assert_eq!(Some(">=-10,<=10"), schema.element(0x75a2).unwrap().range.as_deref());
+        assert_eq!(Some("5"), schema.element(0x4461).unwrap().default.as_deref());
+        assert_eq!(Some("16"), schema.element(0x73a4).unwrap().length.as_deref());
+        assert_eq!(None, schema.element(0x73a4).unwrap().default);
+        assert_eq!(None, schema.element(0xec).unwrap().parent_id);
+    }
+
+    #[test]
+    pub fn xml_round_trip() {
+        let schema = EbmlSchema::from_spec::<TestSpec>("test", 3);
+        let xml = schema.to_xml();
+        assert!(xml.starts_with("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n<EBMLSchema xmlns=\"urn:ietf:rfc:8794\" docType=\"test\" version=\"3\">\n"));
+        assert!(xml.contains("  <element name=\"TimestampScale\" path=\"\\Segment\\Info\\TimestampScale\" id=\"0x2AD7B1\" type=\"uinteger\" range=\"1-\" default=\"1000000\" minOccurs=\"1\" maxOccurs=\"1\"/>\n"));
+        assert!(xml.contains("default=\"&lt;untitled &amp; &quot;unnamed&quot;&gt;\""));
+        assert_eq!(schema, EbmlSchema::parse(&xml).unwrap());
+    }
+
+    #[test]
+    pub fn generated_spec_round_trip() {
+        let original = EbmlSchema::load("tests/schemas/test.xml").unwrap();
+        let exported = EbmlSchema::parse(&EbmlSchema::from_spec::<GeneratedSpec>("test", 1).to_xml()).unwrap();
+        assert_eq!(original.elements.len(), exported.elements.len());
+        for (original, exported) in original.elements.iter().zip(exported.elements.iter()) {
+            assert_eq!(original.id, exported.id);
+            assert_eq!(original.data_type, exported.data_type);
+            assert_eq!(original.occurs, exported.occurs);
+            assert_eq!(original.default_value().unwrap(), exported.default_value().unwrap());
+            assert_eq!(original.unsigned_int_range().unwrap(), exported.unsigned_int_range().unwrap());
+            assert_eq!(original.float_range().unwrap(), exported.float_range().unwrap());
+            assert_eq!(original.length_range().unwrap(), exported.length_range().unwrap());
+            assert_eq!(original.global, exported.global);
+            assert_eq!(original.parent_id, exported.parent_id);
+        }
+    }
+}
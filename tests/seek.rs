@@ -0,0 +1,109 @@
+#[cfg(feature = "derive-spec")]
+pub mod seek {
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::{TagIterator, TagWriter};
+    use ebml_iterable::error::TagIteratorError;
+    use std::io::{Cursor, Read, Seek, SeekFrom};
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        Cluster,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        Count,
+
+        #[id(0xa1)]
+        #[data_type(TagDataType::Binary)]
+        Block,
+    }
+
+    struct CountingReader {
+        inner: Cursor<Vec<u8>>,
+        bytes_read: usize,
+    }
+
+    impl Read for CountingReader {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let read = self.inner.read(buf)?;
+            self.bytes_read += read;
+            Ok(read)
+        }
+    }
+
+    impl Seek for CountingReader {
+        fn seek(&mut self, pos: SeekFrom) -> std::io::Result<u64> {
+            self.inner.seek(pos)
+        }
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Block(vec![0x00; 0x40000])).expect("Error writing tag");
+        writer.write(&TestSpec::Count(0x01)).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::End)).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(0x05)]))).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    #[test]
+    pub fn skip_master() {
+        let mut src = CountingReader { inner: Cursor::new(get_data()), bytes_read: 0 };
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::with_capacity(&mut src, &[], 1024);
+
+        assert_eq!(TestSpec::Segment(Master::Start), iter.next().unwrap().unwrap());
+        assert_eq!(TestSpec::Cluster(Master::Start), iter.next().unwrap().unwrap());
+        iter.skip_current_master().expect("Skipping should succeed");
+
+        let remaining: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(vec![
+            TestSpec::Cluster(Master::End),
+            TestSpec::Cluster(Master::Start),
+            TestSpec::Count(0x05),
+            TestSpec::Cluster(Master::End),
+            TestSpec::Segment(Master::End),
+        ], remaining);
+        assert!(src.bytes_read < 0x40000, "Skipped content should not be read");
+    }
+
+    #[test]
+    pub fn skip_without_open_master() {
+        let mut src = Cursor::new(get_data());
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&mut src, &[]);
+
+        assert!(matches!(iter.skip_current_master(), Err(TagIteratorError::InvalidSeek(_))));
+    }
+
+    #[test]
+    pub fn seek_to_tag() {
+        let mut src = Cursor::new(get_data());
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(&mut src, &[]);
+
+        let mut count_offset = None;
+        while let Some(tag) = iter.next_with_meta() {
+            let (tag, meta) = tag.unwrap();
+            if tag == TestSpec::Count(0x01) {
+                count_offset = Some(meta.start);
+            }
+        }
+
+        let count_offset = count_offset.expect("Count tag should have been read");
+        iter.seek_to(count_offset).expect("Seeking should succeed");
+        let (tag, meta) = iter.next_with_meta().unwrap().unwrap();
+        assert_eq!(TestSpec::Count(0x01), tag);
+        assert_eq!(count_offset, meta.start);
+        assert_eq!(TestSpec::Cluster(Master::Start), iter.next().unwrap().unwrap());
+    }
+}
@@ -0,0 +1,182 @@
+#[cfg(all(feature = "derive-spec", feature = "schema"))]
+pub mod spec_instance {
+    use ebml_iterable::specs::schema::{DynamicSpec, DynamicTag, DynamicValue, EbmlSchema};
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecificationInstance, Master, StaticSpecification, TagDataType, ValueRange};
+    use ebml_iterable::error::{TagIteratorError, TagWriterError};
+    use ebml_iterable::{TagIterator, TagIteratorAsync, TagParser, TagWriter};
+    use std::io::Cursor;
+    use std::ops::Bound;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x1549a966)]
+        #[data_type(TagDataType::Master)]
+        Info,
+
+        #[id(0x2ad7b1)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Info)]
+        TimestampScale,
+
+        #[id(0x7ba9)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Info)]
+        Title,
+    }
+
+    // A specification carrying state - the largest allowed "TimestampScale" is chosen at runtime.
+    struct LimitedScale {
+        inner: StaticSpecification<TestSpec>,
+        max_scale: u64,
+    }
+
+    impl LimitedScale {
+        fn new(max_scale: u64) -> Self {
+            LimitedScale { inner: StaticSpecification::new(), max_scale }
+        }
+    }
+
+    impl EbmlSpecificationInstance<TestSpec> for LimitedScale {
+        fn get_tag_data_type(&self, id: u64) -> TagDataType { self.inner.get_tag_data_type(id) }
+        fn get_unsigned_int_tag(&self, id: u64, data: u64) -> Option<TestSpec> { self.inner.get_unsigned_int_tag(id, data) }
+        fn get_signed_int_tag(&self, id: u64, data: i64) -> Option<TestSpec> { self.inner.get_signed_int_tag(id, data) }
+        fn get_utf8_tag(&self, id: u64, data: String) -> Option<TestSpec> { self.inner.get_utf8_tag(id, data) }
+        fn get_binary_tag(&self, id: u64, data: &[u8]) -> Option<TestSpec> { self.inner.get_binary_tag(id, data) }
+        fn get_float_tag(&self, id: u64, data: f64) -> Option<TestSpec> { self.inner.get_float_tag(id, data) }
+        fn get_master_tag(&self, id: u64, data: Master<TestSpec>) -> Option<TestSpec> { self.inner.get_master_tag(id, data) }
+        fn get_raw_tag(&self, id: u64, data: &[u8]) -> TestSpec { self.inner.get_raw_tag(id, data) }
+
+        fn get_unsigned_int_range(&self, id: u64) -> Option<ValueRange<u64>> {
+            if id == 0x2ad7b1 {
+                Some(ValueRange::new(Bound::Included(1), Bound::Included(self.max_scale)))
+            } else {
+                None
+            }
+        }
+    }
+
+    fn schema(doc_type: &str, title_type: &str) -> String {
+        format!(r#"<?xml version="1.0" encoding="utf-8"?>
+            <EBMLSchema xmlns="urn:ietf:rfc:8794" docType="{}" version="1">
+                <element name="Info" path="\Info" id="0x1549A966" type="master"/>
+                <element name="TimestampScale" path="\Info\TimestampScale" id="0x2AD7B1" type="uinteger" range="1-1000"/>
+                <element name="Title" path="\Info\Title" id="0x7BA9" type="{}"/>
+            </EBMLSchema>"#, doc_type, title_type)
+    }
+
+    fn dynamic_spec(doc_type: &str, title_type: &str) -> DynamicSpec {
+        DynamicSpec::new(EbmlSchema::parse(&schema(doc_type, title_type)).unwrap()).unwrap()
+    }
+
+    fn master(id: u64, master: Master<DynamicTag>) -> DynamicTag {
+        DynamicTag::new(id, DynamicValue::Master(master))
+    }
+
+    fn encode(tags: &[TestSpec]) -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        for tag in tags {
+            writer.write(tag).unwrap();
+        }
+        drop(writer);
+        dest.into_inner()
+    }
+
+    #[test]
+    pub fn static_specification_matches_default() {
+        let tags = vec![
+            TestSpec::Info(Master::Start),
+            TestSpec::TimestampScale(1000000),
+            TestSpec::Title(String::from("hi")),
+            TestSpec::Info(Master::End),
+        ];
+        let data = encode(&tags);
+
+        let by_type: Vec<TestSpec> = TagIterator::new(Cursor::new(&data), &[]).map(|tag| tag.unwrap()).collect();
+        let by_instance: Vec<TestSpec> = TagIterator::with_spec(Cursor::new(&data), StaticSpecification::new(), &[]).map(|tag| tag.unwrap()).collect();
+        assert_eq!(tags, by_type);
+        assert_eq!(tags, by_instance);
+    }
+
+    #[test]
+    pub fn stateful_specification() {
+        let data = encode(&[TestSpec::Info(Master::Full(vec![TestSpec::TimestampScale(5000)]))]);
+
+        let mut relaxed = TagIterator::with_spec(Cursor::new(&data), LimitedScale::new(10000), &[]);
+        relaxed.set_strict_mode(true);
+        assert!(relaxed.all(|tag| tag.is_ok()));
+
+        let mut strict = TagIterator::with_spec(Cursor::new(&data), LimitedScale::new(1000), &[]);
+        strict.set_strict_mode(true);
+        assert!(matches!(strict.nth(1), Some(Err(TagIteratorError::InvalidTagData { tag_id: 0x2ad7b1, .. }))));
+
+        let mut writer = TagWriter::new(Cursor::new(Vec::new()));
+        assert!(writer.write_with_spec(&TestSpec::TimestampScale(5000), &LimitedScale::new(10000)).is_ok());
+        assert!(matches!(writer.write_with_spec(&TestSpec::TimestampScale(5000), &LimitedScale::new(1000)), Err(TagWriterError::InvalidTagData { tag_id: 0x2ad7b1, .. })));
+    }
+
+    #[test]
+    pub fn dynamic_specs() {
+        let data = encode(&[TestSpec::Info(Master::Full(vec![TestSpec::Title(String::from("hi"))]))]);
+        let text = dynamic_spec("text", "utf-8");
+        let raw = dynamic_spec("raw", "binary");
+
+        let info = master(0x1549a966, Master::Start);
+        let tags: Vec<DynamicTag> = TagIterator::with_spec(Cursor::new(&data), &text, std::slice::from_ref(&info)).map(|tag| tag.unwrap()).collect();
+        assert_eq!(vec![master(0x1549a966, Master::Full(vec![DynamicTag::new(0x7ba9, DynamicValue::Utf8(String::from("hi")))]))], tags);
+
+        let tags: Vec<DynamicTag> = TagIterator::with_spec(Cursor::new(&data), &raw, std::slice::from_ref(&info)).map(|tag| tag.unwrap()).collect();
+        assert_eq!(vec![master(0x1549a966, Master::Full(vec![DynamicTag::new(0x7ba9, DynamicValue::Binary(b"hi".to_vec()))]))], tags);
+
+        let mut iter = TagIterator::with_spec(Cursor::new(&data), &text, &[]);
+        let titles: Vec<DynamicTag> = iter.select("Info/Title").unwrap().map(|tag| tag.unwrap()).collect();
+        assert_eq!(1, titles.len());
+    }
+
+    #[test]
+    pub fn specification_trait_objects() {
+        let data = encode(&[TestSpec::Info(Master::Full(vec![TestSpec::Title(String::from("hi"))]))]);
+
+        for (doc_type, expected) in [("text", DynamicValue::Utf8(String::from("hi"))), ("raw", DynamicValue::Binary(b"hi".to_vec()))] {
+            let spec: Box<dyn EbmlSpecificationInstance<DynamicTag>> = match doc_type {
+                "text" => Box::new(dynamic_spec(doc_type, "utf-8")),
+                _ => Box::new(dynamic_spec(doc_type, "binary")),
+            };
+
+            let mut parser = TagParser::with_spec(spec, &[]);
+            parser.feed(&data);
+            parser.finish();
+            let tags: Vec<DynamicTag> = parser.drain().map(|tag| tag.unwrap()).collect();
+            assert_eq!(DynamicTag::new(0x7ba9, expected), tags[1]);
+            assert_eq!(Some("Title"), parser.spec().get_tag_name(0x7ba9));
+        }
+    }
+
+    #[test]
+    pub fn async_iterator_with_spec() {
+        let data = encode(&[TestSpec::Info(Master::Full(vec![TestSpec::TimestampScale(5000)]))]);
+        let spec = dynamic_spec("text", "utf-8");
+
+        futures::executor::block_on(async {
+            let mut iter = TagIteratorAsync::with_spec(&data[..], &spec, &[]);
+            iter.set_strict_mode(true);
+            assert_eq!(Some(master(0x1549a966, Master::Start)), iter.next().await.map(|tag| tag.unwrap()));
+            assert!(matches!(iter.next().await, Some(Err(TagIteratorError::InvalidTagData { tag_id: 0x2ad7b1, .. }))));
+        });
+    }
+
+    #[test]
+    pub fn write_with_dynamic_spec() {
+        let spec = dynamic_spec("text", "utf-8");
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        let info = master(0x1549a966, Master::Full(vec![DynamicTag::new(0x2ad7b1, DynamicValue::UnsignedInt(100))]));
+        writer.write_with_spec(&info, &spec).unwrap();
+        assert!(matches!(writer.write_with_spec(&DynamicTag::new(0x2ad7b1, DynamicValue::UnsignedInt(5000)), &spec), Err(TagWriterError::InvalidTagData { tag_id: 0x2ad7b1, .. })));
+        drop(writer);
+
+        let tags: Vec<DynamicTag> = TagIterator::with_spec(Cursor::new(dest.into_inner()), &spec, std::slice::from_ref(&info)).map(|tag| tag.unwrap()).collect();
+        assert_eq!(vec![info], tags);
+    }
+}
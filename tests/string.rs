@@ -0,0 +1,124 @@
+#[cfg(feature = "derive-spec")]
+pub mod string {
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecification, EbmlTag, TagDataType, Master};
+    use ebml_iterable::error::{TagIteratorError, TagWriterError, ToolError};
+    use ebml_iterable::{BorrowedTagData, EbmlVisitor, TagIterator, TagIteratorAsync, TagIteratorSlice, TagWriter};
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x1a45dfa3)]
+        #[data_type(TagDataType::Master)]
+        Ebml,
+
+        #[id(0x4282)]
+        #[data_type(TagDataType::String)]
+        #[parent(Ebml)]
+        DocType,
+
+        #[id(0x7ba9)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Ebml)]
+        Title,
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Ebml(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::DocType(String::from("matroska"))).expect("Error writing tag");
+        writer.write(&TestSpec::Title(String::from("caf\u{e9}"))).expect("Error writing tag");
+        writer.write(&TestSpec::Ebml(Master::End)).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    fn expected_tags() -> Vec<TestSpec> {
+        vec![
+            TestSpec::Ebml(Master::Start),
+            TestSpec::DocType(String::from("matroska")),
+            TestSpec::Title(String::from("caf\u{e9}")),
+            TestSpec::Ebml(Master::End),
+        ]
+    }
+
+    #[test]
+    pub fn derive_string_tags() {
+        assert_eq!(TagDataType::String, TestSpec::get_tag_data_type(0x4282));
+        let tag = TestSpec::get_string_tag(0x4282, String::from("webm")).unwrap();
+        assert_eq!(Some("webm"), tag.as_string());
+        assert_eq!(None, tag.as_utf8());
+        assert_eq!(None, TestSpec::get_string_tag(0x7ba9, String::from("webm")));
+        assert_eq!(None, TestSpec::Title(String::from("webm")).as_string());
+    }
+
+    #[test]
+    pub fn read_string() {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(expected_tags(), tags);
+    }
+
+    #[test]
+    pub fn read_string_async() {
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(futures::io::Cursor::new(get_data()), &[]);
+        let mut tags = Vec::new();
+        futures::executor::block_on(async {
+            while let Some(tag) = iter.next().await {
+                tags.push(tag.unwrap());
+            }
+        });
+        assert_eq!(expected_tags(), tags);
+    }
+
+    #[test]
+    pub fn read_string_slice() {
+        let data = get_data();
+        let iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[]);
+        let tags: Vec<_> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(BorrowedTagData::String("matroska"), tags[1].data);
+        assert_eq!(expected_tags(), tags.iter().map(|tag| tag.to_tag()).collect::<Vec<TestSpec>>());
+    }
+
+    #[test]
+    pub fn visit_string() {
+        #[derive(Default)]
+        struct Strings {
+            ascii: Vec<String>,
+            unicode: Vec<String>,
+        }
+        impl EbmlVisitor for Strings {
+            fn string(&mut self, _id: u64, value: &str) {
+                self.ascii.push(value.to_string());
+            }
+            fn utf8(&mut self, _id: u64, value: &str) {
+                self.unicode.push(value.to_string());
+            }
+        }
+
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[]);
+        let mut strings = Strings::default();
+        iter.visit(&mut strings).unwrap();
+        assert_eq!(vec![String::from("matroska")], strings.ascii);
+        assert_eq!(vec![String::from("caf\u{e9}")], strings.unicode);
+    }
+
+    #[test]
+    pub fn read_rejects_non_printable() {
+        let data = vec![0x42, 0x82, 0x83, 0x61, 0x0a, 0x62];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::CorruptedTagData { tag_id: 0x4282, problem: ToolError::InvalidAscii(_) }))));
+
+        let mut iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[]);
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::CorruptedTagData { tag_id: 0x4282, problem: ToolError::InvalidAscii(_) }))));
+    }
+
+    #[test]
+    pub fn write_rejects_non_ascii() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        let result = writer.write(&TestSpec::DocType(String::from("caf\u{e9}")));
+        assert!(matches!(result, Err(TagWriterError::InvalidTagData { tag_id: 0x4282, problem: ToolError::InvalidAscii(_) })));
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(feature = "derive-spec")]
+pub mod tag_filter {
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::{TagIterator, TagIteratorAsync, TagWriter};
+    use std::collections::HashSet;
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1549A966)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Info,
+
+        #[id(0x7BA9)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Info)]
+        Title,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cluster,
+
+        #[id(0xe7)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Cluster)]
+        Timestamp,
+
+        #[id(0xa3)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(Cluster)]
+        SimpleBlock,
+
+        #[id(0x1C53BB6B)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cues,
+
+        #[id(0xBB)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Cues)]
+        CuePoint,
+
+        #[id(0xB3)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(CuePoint)]
+        CueTime,
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Info(Master::Start)).expect("Error writing tag");
+        // Invalid utf8 proves that skipped tags are never decoded
+        writer.write_raw(0x7BA9, &[0xff, 0xfe]).expect("Error writing tag");
+        writer.write(&TestSpec::Info(Master::End)).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Timestamp(0x01), TestSpec::SimpleBlock(vec![0x01; 100])]))).expect("Error writing tag");
+        writer.write(&TestSpec::Cues(Master::Full(vec![TestSpec::CuePoint(Master::Full(vec![TestSpec::CueTime(0x01)]))]))).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    fn get_cue_ids() -> HashSet<u64> {
+        [0x18538067, 0x1C53BB6B, 0xBB, 0xB3].iter().copied().collect()
+    }
+
+    fn get_expected_cues() -> Vec<TestSpec> {
+        vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cues(Master::Start),
+            TestSpec::CuePoint(Master::Start),
+            TestSpec::CueTime(0x01),
+            TestSpec::CuePoint(Master::End),
+            TestSpec::Cues(Master::End),
+            TestSpec::Segment(Master::End),
+        ]
+    }
+
+    #[test]
+    pub fn filter_by_id() {
+        let ids = get_cue_ids();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[]);
+        iter.set_tag_filter(move |id, _| ids.contains(&id));
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+
+        assert_eq!(get_expected_cues(), tags);
+    }
+
+    #[test]
+    pub fn filter_by_depth() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[]);
+        iter.set_tag_filter(|_, depth| depth < 1);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+
+        assert_eq!(vec![TestSpec::Segment(Master::Start), TestSpec::Segment(Master::End)], tags);
+    }
+
+    #[test]
+    pub fn filter_unknown_size_master() {
+        let data = vec![
+            0x18, 0x53, 0x80, 0x67, 0xff,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0xe7, 0x81, 0x01,
+                    0xa3, 0x82, 0x01, 0x02,
+                0x1c, 0x53, 0xbb, 0x6b, 0x85,
+                    0xbb, 0x83,
+                        0xb3, 0x81, 0x01,
+        ];
+        let ids = get_cue_ids();
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        iter.set_tag_filter(move |id, _| ids.contains(&id));
+
+        let mut tags = Vec::new();
+        let mut depths = Vec::new();
+        while let Some(tag) = iter.next() {
+            tags.push(tag.unwrap());
+            depths.push(iter.depth());
+        }
+
+        assert_eq!(get_expected_cues(), tags);
+        assert_eq!(vec![1, 2, 3, 3, 2, 1, 0], depths);
+    }
+
+    #[test]
+    pub fn filter_async() {
+        let ids = get_cue_ids();
+        let mut iter: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(futures::io::Cursor::new(get_data()), &[]);
+        iter.set_tag_filter(move |id, _| ids.contains(&id));
+
+        let mut tags = Vec::new();
+        futures::executor::block_on(async {
+            while let Some(tag) = iter.next().await {
+                tags.push(tag.unwrap());
+            }
+        });
+        assert_eq!(get_expected_cues(), tags);
+    }
+}
@@ -0,0 +1,172 @@
+#[cfg(feature = "derive-spec")]
+pub mod tag_iterator_slice {
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::{BorrowedTag, BorrowedTagData, TagIterator, TagIteratorLimits, TagIteratorSlice, TagWriter};
+    use ebml_iterable::error::TagIteratorError;
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cluster,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Cluster)]
+        Count,
+
+        #[id(0x4101)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Segment)]
+        Title,
+
+        #[id(0xa1)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(Cluster)]
+        Block,
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Title(String::from("A title"))).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Count(0x03), TestSpec::Block(vec![0x01, 0x02, 0x03])]))).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    // An unknown sized segment containing unknown sized clusters, the first of which is ended by a title
+    fn get_live_data() -> Vec<u8> {
+        vec![
+            0x18, 0x53, 0x80, 0x67, 0xff,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0x41, 0x00, 0x81, 0x01,
+                    0xa1, 0x82, 0x01, 0x02,
+                0x41, 0x01, 0x81, 0x61,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0x41, 0x00, 0x81, 0x02,
+        ]
+    }
+
+    fn assert_matches_tag_iterator(data: &[u8], tags_to_buffer: &[TestSpec]) {
+        let expected: Vec<TestSpec> = TagIterator::new(Cursor::new(data), tags_to_buffer).map(|t| t.unwrap()).collect();
+        let read_tags: Vec<TestSpec> = TagIteratorSlice::new(data, tags_to_buffer).map(|t| t.unwrap().to_tag()).collect();
+        assert_eq!(expected, read_tags);
+    }
+
+    #[test]
+    pub fn matches_tag_iterator() {
+        let data = get_data();
+        let expected: Vec<TestSpec> = TagIterator::new(Cursor::new(&data), &[TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap()).collect();
+
+        let iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[TestSpec::Cluster(Master::Start)]);
+        let read_tags: Vec<TestSpec> = iter.map(|t| t.unwrap().to_tag()).collect();
+
+        assert_eq!(expected, read_tags);
+    }
+
+    #[test]
+    pub fn data_is_borrowed() {
+        let data = get_data();
+        let tags: Vec<BorrowedTag> = TagIteratorSlice::<TestSpec>::new(&data, &[]).map(|t| t.unwrap()).collect();
+
+        let data_range = data.as_ptr_range();
+        match tags[1].data {
+            BorrowedTagData::Utf8(title) => {
+                assert_eq!("A title", title);
+                assert!(data_range.contains(&title.as_ptr()));
+            },
+            ref other => panic!("Expected utf8 data, got {:?}", other),
+        }
+        match tags[4].data {
+            BorrowedTagData::Binary(block) => {
+                assert_eq!(&[0x01, 0x02, 0x03], block);
+                assert!(data_range.contains(&block.as_ptr()));
+            },
+            ref other => panic!("Expected binary data, got {:?}", other),
+        }
+        assert_eq!(BorrowedTagData::Master(Master::End), tags[6].data);
+    }
+
+    #[test]
+    pub fn unknown_sizes_match_tag_iterator() {
+        let data = get_live_data();
+        assert_matches_tag_iterator(&data, &[]);
+        assert_matches_tag_iterator(&data, &[TestSpec::Cluster(Master::Start)]);
+        assert_matches_tag_iterator(&data, &[TestSpec::Segment(Master::Start)]);
+
+        let tags: Vec<TestSpec> = TagIteratorSlice::new(&data, &[TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap().to_tag()).collect();
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(1), TestSpec::Block(vec![0x01, 0x02])])),
+            TestSpec::Title(String::from("a")),
+            TestSpec::Cluster(Master::Full(vec![TestSpec::Count(2)])),
+            TestSpec::Segment(Master::End),
+        ], tags);
+    }
+
+    #[test]
+    pub fn open_tags_end_with_data() {
+        // A segment claiming to be larger than the data
+        let data = vec![0x18, 0x53, 0x80, 0x67, 0x90, 0x41, 0x01, 0x81, 0x61];
+        assert_matches_tag_iterator(&data, &[]);
+
+        let tags: Vec<BorrowedTag> = TagIteratorSlice::<TestSpec>::new(&data, &[]).map(|t| t.unwrap()).collect();
+        assert_eq!(BorrowedTagData::Master(Master::End), tags[2].data);
+
+        let mut iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[TestSpec::Segment(Master::Start)]);
+        assert!(matches!(iter.next(), Some(Err(TagIteratorError::CorruptedFileData(_)))));
+    }
+
+    #[test]
+    pub fn filter_and_limits_match_tag_iterator() {
+        let data = get_live_data();
+
+        let mut expected: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[]);
+        expected.set_tag_filter(|id, _| id != 0xa1);
+        let mut iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[]);
+        iter.set_tag_filter(|id, _| id != 0xa1);
+        let read_tags: Vec<TestSpec> = iter.map(|t| t.unwrap().to_tag()).collect();
+        assert_eq!(expected.map(|t| t.unwrap()).collect::<Vec<TestSpec>>(), read_tags);
+
+        let limits = TagIteratorLimits { max_buffered_size: Some(4), ..Default::default() };
+        let mut iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[TestSpec::Cluster(Master::Start)]);
+        iter.set_limits(limits);
+        assert!(matches!(iter.nth(1), Some(Err(TagIteratorError::BufferedSizeLimitExceeded { tag_id: 0x1F43B675, .. }))));
+
+        let limits = TagIteratorLimits { max_depth: Some(1), ..Default::default() };
+        let mut iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[]);
+        iter.set_limits(limits);
+        assert!(matches!(iter.nth(1), Some(Err(TagIteratorError::DepthLimitExceeded { tag_id: 0x1F43B675, limit: 1 }))));
+    }
+
+    #[test]
+    pub fn reading_continues_after_buffered_size_error() {
+        let mut data = get_data();
+        data.extend(get_live_data());
+        for limit in [4, 6] {
+            let limits = TagIteratorLimits { max_buffered_size: Some(limit), ..Default::default() };
+            let mut expected: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(&data), &[TestSpec::Cluster(Master::Start)]);
+            expected.set_limits(limits);
+            expected.set_tag_filter(|id, _| id != 0x4101);
+            let expected: Vec<String> = expected.map(|t| format!("{:?}", t)).collect();
+
+            let mut iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[TestSpec::Cluster(Master::Start)]);
+            iter.set_limits(limits);
+            iter.set_tag_filter(|id, _| id != 0x4101);
+            let read_tags: Vec<String> = iter.map(|t| format!("{:?}", t.map(|t| t.to_tag::<TestSpec>()))).collect();
+
+            assert!(expected.iter().any(|t| t.contains("BufferedSizeLimitExceeded")));
+            assert_eq!(expected, read_tags);
+        }
+    }
+}
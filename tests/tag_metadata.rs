@@ -0,0 +1,79 @@
+#[cfg(feature = "derive-spec")]
+pub mod tag_metadata {
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::{TagIterator, TagIteratorAsync, TagMetadata, TagWriter};
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x1a45dfa3)]
+        #[data_type(TagDataType::Master)]
+        Ebml,
+
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x83)]
+        #[data_type(TagDataType::UnsignedInt)]
+        TrackType,
+    }
+
+    fn get_data() -> Vec<u8> {
+        let tags: Vec<TestSpec> = vec![
+            TestSpec::Ebml(Master::Start),
+            TestSpec::Segment(Master::Start),
+            TestSpec::TrackType(0x01),
+            TestSpec::Segment(Master::End),
+            TestSpec::Ebml(Master::End),
+        ];
+
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        for tag in tags.iter() {
+            writer.write(tag).expect("Test shouldn't error");
+        }
+        dest.into_inner()
+    }
+
+    fn expected() -> Vec<(TestSpec, TagMetadata)> {
+        let ebml = TagMetadata { start: 0, header_len: 5, id_len: 4, size_len: 1, data_size: Some(8) };
+        let segment = TagMetadata { start: 5, header_len: 5, id_len: 4, size_len: 1, data_size: Some(3) };
+        let track_type = TagMetadata { start: 10, header_len: 2, id_len: 1, size_len: 1, data_size: Some(1) };
+        vec![
+            (TestSpec::Ebml(Master::Start), ebml),
+            (TestSpec::Segment(Master::Start), segment),
+            (TestSpec::TrackType(0x01), track_type),
+            (TestSpec::Segment(Master::End), segment),
+            (TestSpec::Ebml(Master::End), ebml),
+        ]
+    }
+
+    #[test]
+    pub fn read_tag_metadata() {
+        let mut src = Cursor::new(get_data());
+        let mut reader: TagIterator<_, TestSpec> = TagIterator::new(&mut src, &[]);
+        let mut read_tags = Vec::new();
+        while let Some(tag) = reader.next_with_meta() {
+            read_tags.push(tag.unwrap());
+        }
+
+        assert_eq!(expected(), read_tags);
+        assert_eq!(Some(13), read_tags[0].1.end());
+        assert_eq!(12, read_tags[2].1.data_start());
+    }
+
+    #[test]
+    pub fn read_tag_metadata_async() {
+        let mut reader: TagIteratorAsync<_, TestSpec> = TagIteratorAsync::new(futures::io::Cursor::new(get_data()), &[]);
+        let mut read_tags = Vec::new();
+        futures::executor::block_on(async {
+            while let Some(tag) = reader.next_with_meta().await {
+                read_tags.push(tag.unwrap());
+            }
+        });
+
+        assert_eq!(expected(), read_tags);
+    }
+}
@@ -0,0 +1,87 @@
+#[cfg(feature = "derive-spec")]
+pub mod tag_parser {
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master};
+    use ebml_iterable::{TagIterator, TagParser, TagWriter};
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cluster,
+
+        #[id(0xe7)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Cluster)]
+        Timestamp,
+
+        #[id(0xa3)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(Cluster)]
+        SimpleBlock,
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Timestamp(0x01), TestSpec::SimpleBlock(vec![0x01; 300])]))).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Full(vec![TestSpec::Timestamp(0x02), TestSpec::SimpleBlock(vec![0x02; 300])]))).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    #[test]
+    pub fn parse_chunks() {
+        let data = get_data();
+        let expected: Vec<TestSpec> = TagIterator::new(Cursor::new(&data), &[TestSpec::Cluster(Master::Start)]).map(|t| t.unwrap()).collect();
+
+        for chunk_size in [1, 7, 128, data.len()] {
+            let mut parser: TagParser<TestSpec> = TagParser::new(&[TestSpec::Cluster(Master::Start)]);
+            let mut tags = Vec::new();
+            for chunk in data.chunks(chunk_size) {
+                parser.feed(chunk);
+                tags.extend(parser.drain().map(|t| t.unwrap()));
+            }
+            parser.finish();
+            tags.extend(parser.drain().map(|t| t.unwrap()));
+            assert_eq!(expected, tags, "chunk size {}", chunk_size);
+        }
+    }
+
+    #[test]
+    pub fn partial_element_waits_for_data() {
+        let mut parser: TagParser<TestSpec> = TagParser::new(&[]);
+        parser.feed(&[0x18, 0x53, 0x80, 0x67, 0xff, 0x1f, 0x43]);
+        assert_eq!(Some(TestSpec::Segment(Master::Start)), parser.next_tag().map(|t| t.unwrap()));
+        assert!(parser.next_tag().is_none());
+        assert_eq!(1, parser.depth());
+
+        parser.feed(&[0xb6, 0x75, 0x83, 0xe7, 0x81]);
+        assert_eq!(Some(TestSpec::Cluster(Master::Start)), parser.next_tag().map(|t| t.unwrap()));
+        assert!(parser.next_tag().is_none());
+
+        parser.feed(&[0x05]);
+        assert_eq!(vec![TestSpec::Timestamp(0x05), TestSpec::Cluster(Master::End)], parser.drain().map(|t| t.unwrap()).collect::<Vec<TestSpec>>());
+        assert!(parser.next_tag().is_none());
+
+        parser.finish();
+        assert_eq!(vec![TestSpec::Segment(Master::End)], parser.drain().map(|t| t.unwrap()).collect::<Vec<TestSpec>>());
+    }
+
+    #[test]
+    pub fn truncated_element_at_finish() {
+        let mut parser: TagParser<TestSpec> = TagParser::new(&[]);
+        parser.feed(&[0xa3, 0x85, 0x01, 0x02]);
+        assert!(parser.next_tag().is_none());
+        parser.finish();
+        assert!(parser.next_tag().expect("Truncated tag should be reported").is_err());
+    }
+}
@@ -0,0 +1,149 @@
+#[cfg(feature = "derive-spec")]
+pub mod unknown_size {
+    use ebml_iterable::specs::{ebml_specification, EbmlSpecificationInstance, StaticSpecification, TagDataType, Master};
+    use ebml_iterable::{TagIterator, TagParser};
+    use std::cell::Cell;
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cluster,
+
+        #[id(0xa0)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Cluster)]
+        BlockGroup,
+
+        #[id(0xe7)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Cluster)]
+        Timestamp,
+
+        #[id(0xa1)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(BlockGroup)]
+        Block,
+    }
+
+    // A live stream style segment where the segment and its clusters all have unknown sizes
+    fn get_live_data() -> Vec<u8> {
+        vec![
+            0x18, 0x53, 0x80, 0x67, 0xff,
+                0x1f, 0x43, 0xb6, 0x75, 0x01, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+                    0xe7, 0x81, 0x01,
+                    0xa0, 0xff,
+                        0xa1, 0x82, 0x01, 0x02,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0xe7, 0x81, 0x02,
+        ]
+    }
+
+    fn get_first_cluster() -> TestSpec {
+        TestSpec::Cluster(Master::Full(vec![
+            TestSpec::Timestamp(0x01),
+            TestSpec::BlockGroup(Master::Full(vec![TestSpec::Block(vec![0x01, 0x02])])),
+        ]))
+    }
+
+    fn get_second_cluster() -> TestSpec {
+        TestSpec::Cluster(Master::Full(vec![TestSpec::Timestamp(0x02)]))
+    }
+
+    #[test]
+    pub fn buffer_unknown_size_clusters() {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_live_data()), &[TestSpec::Cluster(Master::Start)]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            get_first_cluster(),
+            get_second_cluster(),
+            TestSpec::Segment(Master::End),
+        ], tags);
+    }
+
+    #[test]
+    pub fn buffer_unknown_size_segment() {
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_live_data()), &[TestSpec::Segment(Master::Start)]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+
+        assert_eq!(vec![TestSpec::Segment(Master::Full(vec![get_first_cluster(), get_second_cluster()]))], tags);
+    }
+
+    #[test]
+    pub fn unknown_size_ends_with_known_size_parent() {
+        let data = vec![
+            0x18, 0x53, 0x80, 0x67, 0x88,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0xe7, 0x81, 0x02,
+            0x18, 0x53, 0x80, 0x67, 0x80,
+        ];
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[TestSpec::Cluster(Master::Start)]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            get_second_cluster(),
+            TestSpec::Segment(Master::End),
+            TestSpec::Segment(Master::Start),
+            TestSpec::Segment(Master::End),
+        ], tags);
+    }
+
+    // Counts how many tags are created, to make sure buffered children are only decoded once
+    #[derive(Default)]
+    struct CountingSpec {
+        inner: StaticSpecification<TestSpec>,
+        created: Cell<usize>,
+    }
+
+    impl EbmlSpecificationInstance<TestSpec> for CountingSpec {
+        fn get_tag_data_type(&self, id: u64) -> TagDataType { self.inner.get_tag_data_type(id) }
+        fn get_unsigned_int_tag(&self, id: u64, data: u64) -> Option<TestSpec> {
+            self.created.set(self.created.get() + 1);
+            self.inner.get_unsigned_int_tag(id, data)
+        }
+        fn get_signed_int_tag(&self, id: u64, data: i64) -> Option<TestSpec> { self.inner.get_signed_int_tag(id, data) }
+        fn get_utf8_tag(&self, id: u64, data: String) -> Option<TestSpec> { self.inner.get_utf8_tag(id, data) }
+        fn get_binary_tag(&self, id: u64, data: &[u8]) -> Option<TestSpec> { self.inner.get_binary_tag(id, data) }
+        fn get_float_tag(&self, id: u64, data: f64) -> Option<TestSpec> { self.inner.get_float_tag(id, data) }
+        fn get_master_tag(&self, id: u64, data: Master<TestSpec>) -> Option<TestSpec> { self.inner.get_master_tag(id, data) }
+        fn get_raw_tag(&self, id: u64, data: &[u8]) -> TestSpec { self.inner.get_raw_tag(id, data) }
+        fn is_child(&self, parent: &TestSpec, id: u64) -> bool { self.inner.is_child(parent, id) }
+    }
+
+    #[test]
+    pub fn buffering_resumes_after_more_data() {
+        let mut data = vec![0x18, 0x53, 0x80, 0x67, 0xff, 0x1f, 0x43, 0xb6, 0x75, 0xff];
+        for timestamp in 0..100 {
+            data.extend_from_slice(&[0xe7, 0x81, timestamp]);
+        }
+
+        let spec = CountingSpec::default();
+        let mut parser = TagParser::with_spec(&spec, &[TestSpec::Cluster(Master::Start)]);
+        let mut tags = Vec::new();
+        for byte in data {
+            parser.feed(&[byte]);
+            tags.extend(parser.drain().map(|t| t.unwrap()));
+            // Only the segment is open - the partially buffered cluster hasn't been emitted yet
+            assert_eq!(tags.len(), parser.depth());
+        }
+        parser.finish();
+        tags.extend(parser.drain().map(|t| t.unwrap()));
+
+        assert_eq!(vec![
+            TestSpec::Segment(Master::Start),
+            TestSpec::Cluster(Master::Full((0..100).map(TestSpec::Timestamp).collect())),
+            TestSpec::Segment(Master::End),
+        ], tags);
+        assert_eq!(100, spec.created.get());
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(feature = "derive-spec")]
+pub mod visitor {
+    use ebml_iterable::specs::{ebml_specification, EbmlTag, TagDataType, Master};
+    use ebml_iterable::{EbmlVisitor, TagIterator, TagWriter};
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x18538067)]
+        #[data_type(TagDataType::Master)]
+        Segment,
+
+        #[id(0x1F43B675)]
+        #[data_type(TagDataType::Master)]
+        #[parent(Segment)]
+        Cluster,
+
+        #[id(0x4489)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Segment)]
+        Duration,
+
+        #[id(0x4100)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Cluster)]
+        Count,
+
+        #[id(0x4101)]
+        #[data_type(TagDataType::Integer)]
+        #[parent(Cluster)]
+        Offset,
+
+        #[id(0x4102)]
+        #[data_type(TagDataType::Float)]
+        #[parent(Cluster)]
+        Rate,
+
+        #[id(0x4103)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Cluster)]
+        Title,
+
+        #[id(0x4104)]
+        #[data_type(TagDataType::Binary)]
+        #[parent(Cluster)]
+        Payload,
+    }
+
+    #[derive(Debug, PartialEq)]
+    enum Event {
+        Start(u64),
+        End(u64),
+        Unsigned(u64, u64),
+        Signed(u64, i64),
+        Float(u64, f64),
+        Utf8(u64, String),
+        Binary(u64, Vec<u8>),
+        Unknown(u64, Vec<u8>),
+    }
+
+    #[derive(Default)]
+    struct Recorder(Vec<Event>);
+
+    impl EbmlVisitor for Recorder {
+        fn start_master(&mut self, id: u64) { self.0.push(Event::Start(id)); }
+        fn end_master(&mut self, id: u64) { self.0.push(Event::End(id)); }
+        fn unsigned(&mut self, id: u64, value: u64) { self.0.push(Event::Unsigned(id, value)); }
+        fn signed(&mut self, id: u64, value: i64) { self.0.push(Event::Signed(id, value)); }
+        fn float(&mut self, id: u64, value: f64) { self.0.push(Event::Float(id, value)); }
+        fn utf8(&mut self, id: u64, value: &str) { self.0.push(Event::Utf8(id, value.to_string())); }
+        fn binary(&mut self, id: u64, data: &[u8]) { self.0.push(Event::Binary(id, data.to_vec())); }
+        fn unknown(&mut self, id: u64, data: &[u8]) { self.0.push(Event::Unknown(id, data.to_vec())); }
+    }
+
+    fn get_data() -> Vec<u8> {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Segment(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::Start)).expect("Error writing tag");
+        writer.write(&TestSpec::Count(5)).expect("Error writing tag");
+        writer.write(&TestSpec::Offset(-3)).expect("Error writing tag");
+        writer.write(&TestSpec::Rate(1.5)).expect("Error writing tag");
+        writer.write(&TestSpec::Title(String::from("title"))).expect("Error writing tag");
+        writer.write(&TestSpec::Payload(vec![0x01, 0x02])).expect("Error writing tag");
+        writer.write_raw(0x4105, &[0x03]).expect("Error writing tag");
+        writer.write(&TestSpec::Cluster(Master::End)).expect("Error writing tag");
+        writer.write(&TestSpec::Segment(Master::End)).expect("Error writing tag");
+        drop(writer);
+        dest.into_inner()
+    }
+
+    fn expected_events() -> Vec<Event> {
+        vec![
+            Event::Start(0x18538067),
+            Event::Start(0x1F43B675),
+            Event::Unsigned(0x4100, 5),
+            Event::Signed(0x4101, -3),
+            Event::Float(0x4102, 1.5),
+            Event::Utf8(0x4103, String::from("title")),
+            Event::Binary(0x4104, vec![0x01, 0x02]),
+            Event::Unknown(0x4105, vec![0x03]),
+            Event::End(0x1F43B675),
+            Event::End(0x18538067),
+        ]
+    }
+
+    #[test]
+    pub fn visit_all_types() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[]);
+        let mut recorder = Recorder::default();
+        iter.visit(&mut recorder).unwrap();
+
+        assert_eq!(expected_events(), recorder.0);
+    }
+
+    #[test]
+    pub fn visit_buffered_masters() {
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(get_data()), &[TestSpec::Cluster(Master::Start)]);
+        let mut recorder = Recorder::default();
+        iter.visit(&mut recorder).unwrap();
+
+        assert_eq!(expected_events(), recorder.0);
+    }
+
+    #[test]
+    pub fn visit_ends_unknown_size_parent() {
+        let data = vec![
+            0x18, 0x53, 0x80, 0x67, 0xff,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0x41, 0x00, 0x81, 0x01,
+                0x1f, 0x43, 0xb6, 0x75, 0xff,
+                    0x41, 0x00, 0x81, 0x02,
+                0x44, 0x89, 0x81, 0x09,
+        ];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        let mut recorder = Recorder::default();
+        iter.visit(&mut recorder).unwrap();
+
+        assert_eq!(vec![
+            Event::Start(0x18538067),
+            Event::Start(0x1F43B675),
+            Event::Unsigned(0x4100, 1),
+            Event::End(0x1F43B675),
+            Event::Start(0x1F43B675),
+            Event::Unsigned(0x4100, 2),
+            Event::End(0x1F43B675),
+            Event::Unsigned(0x4489, 9),
+            Event::End(0x18538067),
+        ], recorder.0);
+    }
+
+    #[test]
+    pub fn visit_reports_corrupted_data() {
+        let data = vec![
+            0x41, 0x03, 0x82, 0xc3, 0x28,
+            0x41, 0x00, 0x81, 0x07,
+        ];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        let mut recorder = Recorder::default();
+        assert!(iter.visit(&mut recorder).is_err());
+        assert!(recorder.0.is_empty());
+
+        // Visiting can continue after the failing element, and the iterator decodes tags again afterwards
+        let tag = iter.next().unwrap().unwrap();
+        assert_eq!(0x4100, tag.get_id());
+        assert_eq!(Some(&7), tag.as_unsigned_int());
+    }
+}
@@ -0,0 +1,132 @@
+#[cfg(feature = "derive-spec")]
+pub mod zero_length {
+    use ebml_iterable::specs::{ebml_specification, TagDataType, Master, Date};
+    use ebml_iterable::{BorrowedTagData, EbmlVisitor, TagIterator, TagIteratorSlice, TagWriter};
+    use std::io::Cursor;
+
+    #[ebml_specification]
+    #[derive(Clone, Debug, PartialEq)]
+    pub enum TestSpec {
+        #[id(0x1549a966)]
+        #[data_type(TagDataType::Master)]
+        Info,
+
+        #[id(0x2ad7b1)]
+        #[data_type(TagDataType::UnsignedInt)]
+        #[parent(Info)]
+        TimestampScale,
+
+        #[id(0x75a2)]
+        #[data_type(TagDataType::Integer)]
+        #[parent(Info)]
+        DiscardPadding,
+
+        #[id(0x4489)]
+        #[data_type(TagDataType::Float)]
+        #[parent(Info)]
+        Duration,
+
+        #[id(0x4461)]
+        #[data_type(TagDataType::Date)]
+        #[parent(Info)]
+        DateUtc,
+
+        #[id(0x7ba9)]
+        #[data_type(TagDataType::Utf8)]
+        #[parent(Info)]
+        Title,
+
+        #[id(0x4282)]
+        #[data_type(TagDataType::String)]
+        #[parent(Info)]
+        DocType,
+    }
+
+    fn zero_tags() -> Vec<TestSpec> {
+        vec![
+            TestSpec::TimestampScale(0),
+            TestSpec::DiscardPadding(0),
+            TestSpec::Duration(0.0),
+            TestSpec::DateUtc(Date::from_nanos(0)),
+        ]
+    }
+
+    #[test]
+    pub fn read_empty_numbers_as_zero() {
+        let data = vec![0x2a, 0xd7, 0xb1, 0x80, 0x75, 0xa2, 0x80, 0x44, 0x89, 0x80, 0x44, 0x61, 0x80];
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(zero_tags(), tags);
+
+        let iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap().to_tag()).collect();
+        assert_eq!(zero_tags(), tags);
+    }
+
+    #[test]
+    pub fn visit_empty_numbers() {
+        #[derive(Default)]
+        struct Numbers(Vec<f64>);
+        impl EbmlVisitor for Numbers {
+            fn unsigned(&mut self, _id: u64, value: u64) {
+                self.0.push(value as f64);
+            }
+            fn signed(&mut self, _id: u64, value: i64) {
+                self.0.push(value as f64);
+            }
+            fn float(&mut self, _id: u64, value: f64) {
+                self.0.push(value);
+            }
+        }
+
+        let data = vec![0x2a, 0xd7, 0xb1, 0x80, 0x75, 0xa2, 0x80, 0x44, 0x89, 0x80];
+        let mut iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data), &[]);
+        let mut numbers = Numbers::default();
+        iter.visit(&mut numbers).unwrap();
+        assert_eq!(vec![0.0, 0.0, 0.0], numbers.0);
+    }
+
+    #[test]
+    pub fn strip_string_padding() {
+        let data = vec![0x7b, 0xa9, 0x84, 0x61, 0x62, 0x00, 0x00, 0x42, 0x82, 0x86, 0x77, 0x65, 0x62, 0x6d, 0x00, 0x00];
+        let iter: TagIterator<_, TestSpec> = TagIterator::new(Cursor::new(data.clone()), &[]);
+        let tags: Vec<TestSpec> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(vec![TestSpec::Title(String::from("ab")), TestSpec::DocType(String::from("webm"))], tags);
+
+        let iter: TagIteratorSlice<TestSpec> = TagIteratorSlice::new(&data, &[]);
+        let tags: Vec<_> = iter.map(|t| t.unwrap()).collect();
+        assert_eq!(BorrowedTagData::Utf8("ab"), tags[0].data);
+        assert_eq!(BorrowedTagData::String("webm"), tags[1].data);
+    }
+
+    #[test]
+    pub fn write_zero_length_zero_values() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.set_zero_length_zero_values(true);
+        for tag in zero_tags() {
+            writer.write(&tag).expect("Error writing tag");
+        }
+        writer.write(&TestSpec::Duration(-0.0)).expect("Error writing tag");
+        writer.write(&TestSpec::TimestampScale(1)).expect("Error writing tag");
+        drop(writer);
+
+        assert_eq!(vec![
+            0x2a, 0xd7, 0xb1, 0x80,
+            0x75, 0xa2, 0x80,
+            0x44, 0x89, 0x80,
+            0x44, 0x61, 0x80,
+            0x44, 0x89, 0x88, 0x80, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+            0x2a, 0xd7, 0xb1, 0x81, 0x01,
+        ], dest.into_inner());
+    }
+
+    #[test]
+    pub fn write_zero_values_in_full_by_default() {
+        let mut dest = Cursor::new(Vec::new());
+        let mut writer = TagWriter::new(&mut dest);
+        writer.write(&TestSpec::Info(Master::Full(vec![TestSpec::TimestampScale(0)]))).expect("Error writing tag");
+        drop(writer);
+        assert_eq!(vec![0x15, 0x49, 0xa9, 0x66, 0x85, 0x2a, 0xd7, 0xb1, 0x81, 0x00], dest.into_inner());
+    }
+}